@@ -0,0 +1,9 @@
+-- Add down migration script here
+
+ALTER TABLE metadata ADD COLUMN "urls" TEXT; -- Pipe-separated URLs
+
+UPDATE metadata SET urls = (
+    SELECT group_concat(url, '|') FROM resume_urls WHERE resume_urls.resume_id = metadata.resume_id
+);
+
+DROP TABLE resume_urls;
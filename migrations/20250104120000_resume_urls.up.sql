@@ -0,0 +1,34 @@
+-- Add up migration script here
+
+-- URL Table (Many-to-One with Resume)
+CREATE TABLE resume_urls (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT, -- Serial ID for unique identification
+    "resume_id" INTEGER NOT NULL,         -- Foreign key tied to Resume.id
+    "kind" TEXT NOT NULL,                 -- posting, company, linkedin, portal, status-check
+    "label" TEXT,                         -- Optional human readable label
+    "url" TEXT NOT NULL,                  -- The URL itself, validated before insert
+    FOREIGN KEY (resume_id) REFERENCES resumes(id) ON DELETE CASCADE,
+    UNIQUE(resume_id, url) -- the same URL should not be stored twice for a resume
+);
+
+-- split the old pipe-separated urls column into rows
+-- there is no way to know what each url was for, so guess linkedin and default to posting
+INSERT OR IGNORE INTO resume_urls (resume_id, kind, url)
+WITH RECURSIVE split(resume_id, url, rest) AS (
+    SELECT resume_id, '', urls || '|' FROM metadata WHERE urls IS NOT NULL AND urls != ''
+    UNION ALL
+    SELECT
+        resume_id,
+        trim(substr(rest, 1, instr(rest, '|') - 1)),
+        substr(rest, instr(rest, '|') + 1)
+    FROM split
+    WHERE rest != ''
+)
+SELECT
+    resume_id,
+    CASE WHEN url LIKE '%linkedin.com%' THEN 'linkedin' ELSE 'posting' END,
+    url
+FROM split
+WHERE url != '';
+
+ALTER TABLE metadata DROP COLUMN urls;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TABLE resume_tags;
+
+DROP TABLE tags;
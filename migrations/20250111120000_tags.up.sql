@@ -0,0 +1,16 @@
+-- Add up migration script here
+
+-- Tag Table, names are normalized to lowercase before insert
+CREATE TABLE tags (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT, -- Serial ID for unique identification
+    "name" TEXT NOT NULL UNIQUE           -- Tag name, e.g. remote, dream, new-grad
+);
+
+-- Join Table (Many-to-Many between Resume and Tag)
+CREATE TABLE resume_tags (
+    "resume_id" INTEGER NOT NULL,         -- Foreign key tied to Resume.id
+    "tag_id" INTEGER NOT NULL,            -- Foreign key tied to Tag.id
+    PRIMARY KEY (resume_id, tag_id),
+    FOREIGN KEY (resume_id) REFERENCES resumes(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
@@ -0,0 +1,19 @@
+-- Add up migration script here
+
+-- Offer Table (Many-to-One with Resume, a resume can get a revised offer)
+CREATE TABLE offers (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT, -- Serial ID for unique identification
+    "resume_id" INTEGER NOT NULL,         -- Foreign key tied to Resume.id
+    "base" REAL NOT NULL,                 -- Base pay per period
+    "period" TEXT NOT NULL,               -- Pay period of base: hour, week, month or year
+    "currency" TEXT NOT NULL,             -- ISO currency code, no conversion is done
+    "bonus" REAL DEFAULT 0 NOT NULL,      -- Signing/yearly bonus, counted once per year
+    "equity" REAL DEFAULT 0 NOT NULL,     -- Total equity grant value
+    "equity_years" INTEGER DEFAULT 4 NOT NULL, -- Vesting schedule of the equity grant in years
+    "relocation" REAL DEFAULT 0 NOT NULL, -- One-time relocation package
+    "start_date" DATE,                    -- Optional start date
+    "deadline" DATE,                      -- Optional decision deadline
+    "location" TEXT,                      -- Optional location, can differ from the posting
+    "notes" TEXT DEFAULT '' NOT NULL,     -- Anything else, default empty
+    FOREIGN KEY (resume_id) REFERENCES resumes(id) ON DELETE CASCADE
+);
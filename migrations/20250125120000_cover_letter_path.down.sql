@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE resumes DROP COLUMN cover_letter_path;
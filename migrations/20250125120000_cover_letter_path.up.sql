@@ -0,0 +1,3 @@
+-- Add up migration script here
+
+ALTER TABLE resumes ADD COLUMN "cover_letter_path" TEXT; -- File path to the cover letter, null if there is none
@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+-- Submission Table (Many-to-One with Resume, one row per file frozen by `resuman submit`)
+CREATE TABLE submissions (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT, -- Serial ID for unique identification
+    "resume_id" INTEGER NOT NULL,         -- Foreign key tied to Resume.id
+    "kind" TEXT NOT NULL,                 -- What was frozen: resume, pdf or cover_letter
+    "name" TEXT NOT NULL,                 -- The frozen copy, relative to the resume folder
+    "sha256" TEXT NOT NULL,               -- Hex SHA-256 of the frozen copy
+    "submitted_at" DATETIME NOT NULL,     -- When it was frozen
+    UNIQUE(resume_id, kind),
+    FOREIGN KEY (resume_id) REFERENCES resumes(id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE metadata DROP COLUMN copied_from_id;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+ALTER TABLE metadata ADD COLUMN "copied_from_id" INTEGER REFERENCES resumes(id) ON DELETE SET NULL; -- The resume copied_from points at, null if it is not managed
+
+-- resumes copied with --file before this point at the .tex of another resume
+UPDATE metadata SET copied_from_id = (
+    SELECT resumes.id FROM resumes
+    WHERE resumes.file_path = metadata.copied_from AND resumes.id != metadata.resume_id
+)
+WHERE copied_from IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+ALTER TABLE resumes DROP COLUMN company_id;
+DROP TABLE company_aliases;
+DROP TABLE companies;
@@ -0,0 +1,44 @@
+-- Add up migration script here
+
+-- Company Table, the canonical name of every company applied to
+CREATE TABLE companies (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT, -- Serial ID for unique identification
+    "name" TEXT NOT NULL UNIQUE,          -- Canonical name, what stats and lists show
+    "website" TEXT,                       -- Optional homepage
+    "industry" TEXT,                      -- Optional industry, e.g. fintech
+    "size" TEXT,                          -- Optional size, e.g. 50-200 or startup
+    "hq" TEXT                             -- Optional location of the headquarters
+);
+
+-- Alias Table (Many-to-One with Company), other ways the name was written
+CREATE TABLE company_aliases (
+    "name" TEXT NOT NULL PRIMARY KEY COLLATE NOCASE, -- e.g. Jane-Street for Jane Street
+    "company_id" INTEGER NOT NULL,        -- Foreign key tied to Company.id
+    FOREIGN KEY (company_id) REFERENCES companies(id) ON DELETE CASCADE
+);
+
+ALTER TABLE resumes ADD COLUMN "company_id" INTEGER REFERENCES companies(id) ON DELETE SET NULL; -- The company, resumes.company keeps the name as it was typed
+
+-- names that only differ in case, spaces and punctuation are one company, named
+-- like its first resume, resuman itself also ignores legal forms like Inc.
+CREATE TEMP TABLE company_keys AS
+SELECT id, company, lower(replace(replace(replace(replace(replace(company, ' ', ''), '-', ''), '_', ''), '.', ''), ',', '')) AS key
+FROM resumes;
+
+INSERT INTO companies (name)
+SELECT company FROM company_keys k
+WHERE k.id = (SELECT MIN(id) FROM company_keys other WHERE other.key = k.key)
+ORDER BY k.id;
+
+UPDATE resumes SET company_id = (
+    SELECT companies.id FROM companies JOIN company_keys first ON first.company = companies.name
+    JOIN company_keys this ON this.key = first.key
+    WHERE this.id = resumes.id
+);
+
+INSERT OR IGNORE INTO company_aliases (name, company_id)
+SELECT DISTINCT resumes.company, resumes.company_id FROM resumes
+JOIN companies ON companies.id = resumes.company_id
+WHERE resumes.company != companies.name;
+
+DROP TABLE company_keys;
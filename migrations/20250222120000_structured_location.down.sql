@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+ALTER TABLE metadata DROP COLUMN work_mode;
+ALTER TABLE metadata DROP COLUMN country;
+ALTER TABLE metadata DROP COLUMN region;
+ALTER TABLE metadata DROP COLUMN city;
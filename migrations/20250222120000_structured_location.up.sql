@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- parsed from location, existing locations are parsed the next time the db is opened
+ALTER TABLE metadata ADD COLUMN "city" TEXT;
+ALTER TABLE metadata ADD COLUMN "region" TEXT; -- state or province code, e.g. NY or ON
+ALTER TABLE metadata ADD COLUMN "country" TEXT; -- ISO 3166 code, e.g. US or CA
+ALTER TABLE metadata ADD COLUMN "work_mode" TEXT; -- onsite, hybrid or remote
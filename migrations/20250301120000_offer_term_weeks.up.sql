@@ -0,0 +1,3 @@
+-- Add up migration script here
+
+ALTER TABLE offers ADD COLUMN "term_weeks" INTEGER; -- Length of the job when it differs from the resume's, 0 when open-ended
@@ -0,0 +1,244 @@
+// copies of the db taken with VACUUM INTO, which gives a consistent snapshot
+// even while another process is writing, unlike copying the file
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use chrono::Utc;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteJournalMode},
+    Connection, SqliteConnection, SqlitePool,
+};
+
+use crate::{
+    config::Config,
+    error::{Error, Result},
+    manager, MIGRATOR,
+};
+
+pub const JOURNAL_MODES: [&str; 6] = ["wal", "delete", "truncate", "persist", "memory", "off"];
+
+// every backup resuman names itself starts with this, rotation only touches those
+const PREFIX: &str = "resuman-";
+
+/// The journal mode the db is opened with, `journal_mode` or WAL
+pub fn journal_mode(cfg: &Config) -> Result<SqliteJournalMode> {
+    let mode = cfg.journal_mode.as_deref().unwrap_or("wal");
+    if !JOURNAL_MODES.contains(&mode.to_lowercase().as_str()) {
+        return Err(Error::Invalid(format!(
+            "journal_mode {:?} is not one of {}",
+            mode,
+            JOURNAL_MODES.join(", ")
+        )));
+    }
+    SqliteJournalMode::from_str(mode).map_err(Error::from)
+}
+
+/// Opens the db at `path` with the journal mode of the config, creating it if missing
+pub async fn connect(cfg: &Config, path: &Path) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .journal_mode(journal_mode(cfg)?)
+        .create_if_missing(true);
+    Ok(SqlitePool::connect_with(options).await?)
+}
+
+/// Where backups go, `backup_dir` relative to `root_dir`
+pub fn backup_dir(cfg: &Config) -> PathBuf {
+    cfg.root_dir
+        .join(cfg.backup_dir.clone().unwrap_or(PathBuf::from("backups")))
+}
+
+/// Writes a snapshot of the db to `dest`, which must not exist yet
+pub async fn snapshot(pool: &SqlitePool, dest: &Path) -> Result<()> {
+    if dest.exists() {
+        return Err(Error::Invalid(format!("{} already exists", dest.display())));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    sqlx::query("VACUUM INTO ?")
+        .bind(dest.to_string_lossy())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Takes a snapshot into the backup dir, named after the time and `label`, and
+/// removes the oldest backups past `backup_keep`
+pub async fn backup(pool: &SqlitePool, cfg: &Config, label: Option<&str>) -> Result<PathBuf> {
+    // the millis keep two backups in the same second apart, and sort like the time
+    let mut name = format!("{}{}", PREFIX, Utc::now().format("%Y%m%d-%H%M%S-%3f"));
+    if let Some(label) = label {
+        name = format!("{}-{}", name, label);
+    }
+    let path = backup_dir(cfg).join(format!("{}.sqlite", name));
+    snapshot(pool, &path).await?;
+
+    for old in rotate(cfg)? {
+        log::info!("Removed old backup {}", old.display());
+    }
+    Ok(path)
+}
+
+/// The backups in the backup dir, oldest first
+pub fn list(cfg: &Config) -> Result<Vec<PathBuf>> {
+    let dir = backup_dir(cfg);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with(PREFIX) && name.ends_with(".sqlite") {
+            backups.push(path);
+        }
+    }
+    backups.sort();
+    Ok(backups)
+}
+
+/// Removes all but the newest `backup_keep` backups, returns the removed ones
+pub fn rotate(cfg: &Config) -> Result<Vec<PathBuf>> {
+    let keep = cfg.backup_keep.unwrap_or(10) as usize;
+    let backups = list(cfg)?;
+    if keep == 0 || backups.len() <= keep {
+        return Ok(Vec::new());
+    }
+    let old = backups[..backups.len() - keep].to_vec();
+    for path in &old {
+        fs::remove_file(path)?;
+    }
+    Ok(old)
+}
+
+/// The versions of the migrations that were applied to a db, oldest first
+///
+/// A db without the migrations table, like a new one, has none.
+pub async fn applied_versions(conn: &mut SqliteConnection) -> Result<Vec<i64>> {
+    let table: Option<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    if table.is_none() {
+        return Ok(Vec::new());
+    }
+    Ok(sqlx::query_scalar(
+        "SELECT version FROM _sqlx_migrations WHERE success = 1 ORDER BY version",
+    )
+    .fetch_all(&mut *conn)
+    .await?)
+}
+
+/// The versions of the migrations this build of resuman knows, oldest first
+pub fn known_versions() -> Vec<i64> {
+    MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .collect()
+}
+
+/// The known migrations that were not applied to the db yet, oldest first
+pub async fn pending_versions(pool: &SqlitePool) -> Result<Vec<i64>> {
+    let applied = applied_versions(&mut *pool.acquire().await?).await?;
+    Ok(known_versions()
+        .into_iter()
+        .filter(|v| !applied.contains(v))
+        .collect())
+}
+
+/// Whether the db has been migrated before but misses some of the migrations
+///
+/// A new db has nothing to lose, so it does not count.
+pub async fn needs_migration(pool: &SqlitePool) -> Result<bool> {
+    let applied = applied_versions(&mut *pool.acquire().await?).await?;
+    Ok(!applied.is_empty() && !pending_versions(pool).await?.is_empty())
+}
+
+/// Opens a backup read only and checks that it is intact and that this resuman
+/// knows its schema, returns its schema version
+pub async fn check(path: &Path) -> Result<i64> {
+    if !path.is_file() {
+        return Err(Error::Invalid(format!("{} is not a file", path.display())));
+    }
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let mut conn = SqliteConnection::connect_with(&options)
+        .await
+        .map_err(|e| Error::Invalid(format!("{} is not a db: {}", path.display(), e)))?;
+
+    let integrity: String = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_one(&mut conn)
+        .await
+        .map_err(|e| Error::Invalid(format!("{} is not a db: {}", path.display(), e)))?;
+    if integrity != "ok" {
+        return Err(Error::Invalid(format!(
+            "{} is damaged: {}",
+            path.display(),
+            integrity
+        )));
+    }
+
+    let applied = applied_versions(&mut conn).await?;
+    conn.close().await?;
+    let Some(&version) = applied.last() else {
+        return Err(Error::Invalid(format!(
+            "{} is not a resuman db, it has no migrations",
+            path.display()
+        )));
+    };
+    let known = known_versions();
+    if let Some(unknown) = applied.iter().find(|v| !known.contains(v)) {
+        return Err(Error::Invalid(format!(
+            "{} has migration {}, which this resuman does not know, it was made by a newer one",
+            path.display(),
+            unknown
+        )));
+    }
+    Ok(version)
+}
+
+/// Replaces the db with the backup at `path`, after checking it and backing up the
+/// current db, returns the schema version of the backup and the backup of the old db
+///
+/// A backup with an older schema is migrated the next time the db is opened.
+pub async fn restore(cfg: &Config, path: &Path) -> Result<(i64, Option<PathBuf>)> {
+    let version = check(path).await?;
+    let db_path = manager::db_path(cfg);
+
+    // copied first, the backup of the current db may rotate `path` away
+    let staged = db_path.with_extension("restore");
+    fs::copy(path, &staged)?;
+
+    let previous = if db_path.exists() {
+        let pool = connect(cfg, &db_path).await?;
+        let previous = backup(&pool, cfg, Some("pre-restore")).await;
+        pool.close().await;
+        match previous {
+            Ok(previous) => Some(previous),
+            Err(e) => {
+                let _ = fs::remove_file(&staged);
+                return Err(e);
+            }
+        }
+    } else {
+        None
+    };
+
+    // a left over wal would be replayed onto the restored db
+    for suffix in ["-wal", "-shm"] {
+        let mut side = db_path.clone().into_os_string();
+        side.push(suffix);
+        let side = PathBuf::from(side);
+        if side.exists() {
+            fs::remove_file(side)?;
+        }
+    }
+    fs::rename(&staged, &db_path)?;
+    Ok((version, previous))
+}
@@ -0,0 +1,85 @@
+// turns the .tex of a resume, and of its cover letter, into a PDF next to it with
+// `build_command`, latexmk unless the config says otherwise
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    config::Config,
+    error::{Error, Result},
+};
+
+pub const DEFAULT_BUILD_COMMAND: &str = "latexmk -pdf -interaction=nonstopmode -quiet";
+
+// what latexmk and pdflatex leave next to the .tex, kept out of the git history
+pub const BYPRODUCTS: [&str; 6] = [
+    "*.aux",
+    "*.log",
+    "*.fls",
+    "*.fdb_latexmk",
+    "*.out",
+    "*.synctex.gz",
+];
+
+/// Runs `build_command` on `tex` in its folder and returns the PDF it made
+///
+/// On unix the command runs through the shell with the file as its last argument, so
+/// values like `tectonic -X compile` work like they do in a terminal.
+pub fn build(cfg: &Config, tex: &Path) -> Result<PathBuf> {
+    let build_command = cfg
+        .build_command
+        .clone()
+        .unwrap_or(DEFAULT_BUILD_COMMAND.to_string());
+    let folder = tex.parent().unwrap_or(Path::new("."));
+    let file = tex.file_name().unwrap_or(tex.as_os_str());
+
+    let mut command = if cfg!(target_os = "windows") {
+        let mut parts = build_command.split_whitespace();
+        let mut command = Command::new(parts.next().unwrap_or("latexmk"));
+        command.args(parts);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(format!("{} \"$1\"", build_command))
+            .arg("sh");
+        command
+    };
+    let output = command
+        .arg(file)
+        .current_dir(folder)
+        .output()
+        .map_err(|e| {
+            Error::Build(
+                tex.to_path_buf(),
+                format!("could not run {}: {}", build_command, e),
+            )
+        })?;
+    if !output.status.success() {
+        // latexmk puts the error on stdout, most other tools on stderr
+        let printed = [output.stdout, output.stderr].concat();
+        let printed = String::from_utf8_lossy(&printed);
+        let last = printed
+            .trim()
+            .lines()
+            .last()
+            .unwrap_or_default()
+            .to_string();
+        return Err(Error::Build(
+            tex.to_path_buf(),
+            format!("{} exited with {}: {}", build_command, output.status, last),
+        ));
+    }
+
+    let pdf = tex.with_extension("pdf");
+    if !pdf.exists() {
+        return Err(Error::Build(
+            tex.to_path_buf(),
+            format!("{} made no {}", build_command, pdf.display()),
+        ));
+    }
+    Ok(pdf)
+}
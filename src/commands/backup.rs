@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{backup, config::Config};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "backup",
+    about = "Take a snapshot of the db into backup_dir, keeping the newest backup_keep",
+    long_about = "Take a snapshot of the db into backup_dir, keeping the newest backup_keep. Safe while resuman is in use elsewhere, the snapshot is taken with VACUUM INTO"
+)]
+pub struct BackupCommand {
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        help = "Write the snapshot here instead, it is never rotated",
+        conflicts_with = "list"
+    )]
+    pub output: Option<PathBuf>,
+
+    #[arg(
+        short,
+        long,
+        help = "List the backups and their schema versions instead"
+    )]
+    pub list: bool,
+}
+
+pub async fn execute(cfg: Config, args: &BackupCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Backup with arguments:\n{:#?}", args);
+
+    if args.list {
+        for path in backup::list(&cfg)? {
+            let version = match backup::check(&path).await {
+                Ok(version) => version.to_string(),
+                Err(e) => format!("invalid: {}", e),
+            };
+            println!("{}\t{}", path.display(), version);
+        }
+        return Ok(());
+    }
+
+    let path = match &args.output {
+        Some(output) => {
+            backup::snapshot(pool, output).await?;
+            output.clone()
+        }
+        None => backup::backup(pool, &cfg, None).await?,
+    };
+    let version = backup::applied_versions(&mut *pool.acquire().await?)
+        .await?
+        .last()
+        .copied();
+    println!(
+        "{}\t{}",
+        path.display(),
+        version.map_or("null".to_string(), |v| v.to_string())
+    );
+    Ok(())
+}
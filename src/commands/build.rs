@@ -0,0 +1,30 @@
+use anyhow::Result;
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{config::Config, manager::Resuman};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "build",
+    about = "Build the PDF of resumes and their .tex cover letters",
+    long_about = "Build the PDF of resumes and their .tex cover letters next to them with build_command, latexmk by default. The paths of the PDFs are printed, one per line",
+    visible_aliases = ["compile"],
+    arg_required_else_help = true
+)]
+pub struct BuildCommand {
+    #[arg(index = 1, required = true, help = "IDs of the resumes to build")]
+    pub ids: Vec<i64>,
+}
+
+pub async fn execute(cfg: Config, args: &BuildCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Building with arguments:\n{:#?}", args);
+
+    let resuman = Resuman::from_pool(cfg, pool.clone());
+    for id in &args.ids {
+        for pdf in resuman.build(*id).await? {
+            println!("{}", pdf.display());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,343 @@
+use anyhow::Result;
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{
+    company::{self, Company},
+    config::Config,
+    duplicate, Error,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "company",
+    about = "List, rename and merge the companies applied to",
+    long_about = "List, rename and merge the companies applied to. Each resume is linked to a company by its name, names that only differ in case, punctuation and legal forms like Inc. are the same company, and other names can be added as aliases",
+    visible_aliases = ["companies"],
+    arg_required_else_help = true
+)]
+pub struct CompanyCommand {
+    #[command(subcommand)]
+    pub command: Option<CompanyCommands>,
+}
+
+#[derive(Parser)]
+pub enum CompanyCommands {
+    List(ListCompanyCommand),
+    Show(ShowCompanyCommand),
+    Alias(AliasCommand),
+    Merge(MergeCompanyCommand),
+    Set(SetCompanyCommand),
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "list", about = "List companies with their aliases and how many applications went to them", visible_aliases = ["ls"])]
+pub struct ListCompanyCommand {}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "show",
+    about = "Show a company and every application sent to it"
+)]
+pub struct ShowCompanyCommand {
+    #[arg(index = 1, help = "ID, name or alias of the company")]
+    pub company: String,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "alias", about = "Add or remove other names of a company")]
+pub struct AliasCommand {
+    #[arg(index = 1, help = "ID, name or alias of the company")]
+    pub company: String,
+
+    #[arg(index = 2, required = true, help = "Names to add")]
+    pub names: Vec<String>,
+
+    #[arg(short, long, help = "Remove the names instead")]
+    pub remove: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "merge",
+    about = "Merge companies into one, their applications and aliases move to it",
+    long_about = "Merge companies into one, their applications and aliases move to it, their names become aliases of it and details it is missing are taken from them"
+)]
+pub struct MergeCompanyCommand {
+    #[arg(index = 1, help = "ID, name or alias of the company to keep")]
+    pub into: String,
+
+    #[arg(
+        index = 2,
+        required = true,
+        help = "IDs, names or aliases of the companies to merge into it"
+    )]
+    pub from: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "set",
+    about = "Set the details of a company",
+    arg_required_else_help = true
+)]
+pub struct SetCompanyCommand {
+    #[arg(index = 1, help = "ID, name or alias of the company")]
+    pub company: String,
+
+    #[arg(
+        short,
+        long,
+        help = "New canonical name, the old one is kept as an alias"
+    )]
+    pub name: Option<String>,
+
+    #[arg(short, long)]
+    pub website: Option<String>,
+
+    #[arg(short, long)]
+    pub industry: Option<String>,
+
+    #[arg(short, long, help = "Company size, e.g. 1000-5000")]
+    pub size: Option<String>,
+
+    #[arg(long, help = "Headquarters")]
+    pub hq: Option<String>,
+}
+
+/// Errors when `name` already belongs to a company other than `id`
+async fn check_free(pool: &SqlitePool, id: i64, name: &str) -> Result<()> {
+    if let Some(other) = company::resolve(pool, name).await? {
+        if other != id {
+            return Err(Error::Invalid(format!(
+                "{} is already company {}, merge them with `resuman company merge {} {}`",
+                name, other, id, other
+            ))
+            .into());
+        }
+    }
+    Ok(())
+}
+
+async fn list(pool: &SqlitePool) -> Result<()> {
+    let counts = sqlx::query!(
+        r#"SELECT company_id AS "company_id!: i64", COUNT(*) AS "count!: i64" FROM resumes WHERE company_id IS NOT NULL GROUP BY company_id"#
+    )
+    .fetch_all(pool)
+    .await?;
+    println!("id\tname\taliases\tapplications\twebsite\tindustry\tsize\thq");
+    for c in company::all(pool).await? {
+        let count = counts
+            .iter()
+            .find(|r| r.company_id == c.id)
+            .map_or(0, |r| r.count);
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            c.id,
+            c.name,
+            c.aliases.join(", "),
+            count,
+            c.website.unwrap_or_default(),
+            c.industry.unwrap_or_default(),
+            c.size.unwrap_or_default(),
+            c.hq.unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+async fn show(pool: &SqlitePool, c: Company) -> Result<()> {
+    println!("id\t{}", c.id);
+    println!("name\t{}", c.name);
+    println!("aliases\t{}", c.aliases.join(", "));
+    println!("website\t{}", c.website.unwrap_or_default());
+    println!("industry\t{}", c.industry.unwrap_or_default());
+    println!("size\t{}", c.size.unwrap_or_default());
+    println!("hq\t{}", c.hq.unwrap_or_default());
+
+    let applications = sqlx::query!(
+        r#"
+SELECT resumes.id, "group", company, position, created_at, applied_time, status
+FROM resumes JOIN metadata ON metadata.resume_id = resumes.id
+WHERE company_id = ? ORDER BY created_at, resumes.id
+        "#,
+        c.id
+    )
+    .fetch_all(pool)
+    .await?;
+    println!();
+    println!("id\tgroup\tcompany\tposition\tcreated_at\tapplied_time\tstatus");
+    for a in applications {
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            a.id,
+            a.group,
+            a.company,
+            a.position,
+            a.created_at.format("%Y-%m-%d"),
+            a.applied_time
+                .map(|t| t.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            a.status
+        );
+    }
+    Ok(())
+}
+
+async fn alias(pool: &SqlitePool, args: &AliasCommand) -> Result<()> {
+    let c = company::find(pool, &args.company).await?;
+    for name in &args.names {
+        if args.remove {
+            let removed = sqlx::query!(
+                "DELETE FROM company_aliases WHERE name = ? AND company_id = ?",
+                name,
+                c.id
+            )
+            .execute(pool)
+            .await?;
+            if removed.rows_affected() == 0 {
+                return Err(
+                    Error::Invalid(format!("{} is not an alias of {}", name, c.name)).into(),
+                );
+            }
+            println!("removed\t{}\t{}", name, c.name);
+            continue;
+        }
+        check_free(pool, c.id, name).await?;
+        sqlx::query!(
+            "INSERT OR IGNORE INTO company_aliases (name, company_id) VALUES (?, ?)",
+            name,
+            c.id
+        )
+        .execute(pool)
+        .await?;
+        println!("added\t{}\t{}", name, c.name);
+    }
+    Ok(())
+}
+
+async fn merge(pool: &SqlitePool, args: &MergeCompanyCommand) -> Result<()> {
+    let into = company::find(pool, &args.into).await?;
+    let mut from = Vec::new();
+    for arg in &args.from {
+        let c = company::find(pool, arg).await?;
+        if c.id == into.id {
+            return Err(
+                Error::Invalid(format!("Company {} cannot be merged into itself", c.name)).into(),
+            );
+        }
+        from.push(c);
+    }
+
+    let mut tx = pool.begin().await?;
+    for c in &from {
+        sqlx::query!(
+            "UPDATE resumes SET company_id = ?1 WHERE company_id = ?2",
+            into.id,
+            c.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE OR IGNORE company_aliases SET company_id = ?1 WHERE company_id = ?2",
+            into.id,
+            c.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!("DELETE FROM companies WHERE id = ?", c.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            "INSERT OR IGNORE INTO company_aliases (name, company_id) VALUES (?, ?)",
+            c.name,
+            into.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+UPDATE companies SET website = COALESCE(website, ?2), industry = COALESCE(industry, ?3),
+    size = COALESCE(size, ?4), hq = COALESCE(hq, ?5)
+WHERE id = ?1
+            "#,
+            into.id,
+            c.website,
+            c.industry,
+            c.size,
+            c.hq
+        )
+        .execute(&mut *tx)
+        .await?;
+        println!("merged\t{}\t{}", c.name, into.name);
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn set(pool: &SqlitePool, args: &SetCompanyCommand) -> Result<()> {
+    let c = company::find(pool, &args.company).await?;
+    if let Some(name) = &args.name {
+        check_free(pool, c.id, name).await?;
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM company_aliases WHERE name = ? AND company_id = ?",
+            name,
+            c.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!("UPDATE companies SET name = ? WHERE id = ?", name, c.id)
+            .execute(&mut *tx)
+            .await?;
+        // a change of case or punctuation is not worth an alias
+        if duplicate::normalize_company(name) != duplicate::normalize_company(&c.name) {
+            sqlx::query!(
+                "INSERT OR IGNORE INTO company_aliases (name, company_id) VALUES (?, ?)",
+                c.name,
+                c.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+    }
+    sqlx::query!(
+        r#"
+UPDATE companies SET website = COALESCE(?2, website), industry = COALESCE(?3, industry),
+    size = COALESCE(?4, size), hq = COALESCE(?5, hq)
+WHERE id = ?1
+        "#,
+        c.id,
+        args.website,
+        args.industry,
+        args.size,
+        args.hq
+    )
+    .execute(pool)
+    .await?;
+    show(pool, company::find(pool, &c.id.to_string()).await?).await
+}
+
+pub async fn execute(_cfg: Config, args: &CompanyCommand, pool: &SqlitePool) -> Result<()> {
+    match &args.command {
+        Some(CompanyCommands::List(_)) => list(pool).await,
+        Some(CompanyCommands::Show(args)) => {
+            log::debug!("Showing company with arguments:\n{:#?}", args);
+            show(pool, company::find(pool, &args.company).await?).await
+        }
+        Some(CompanyCommands::Alias(args)) => {
+            log::debug!("Aliasing company with arguments:\n{:#?}", args);
+            alias(pool, args).await
+        }
+        Some(CompanyCommands::Merge(args)) => {
+            log::debug!("Merging companies with arguments:\n{:#?}", args);
+            merge(pool, args).await
+        }
+        Some(CompanyCommands::Set(args)) => {
+            log::debug!("Setting company with arguments:\n{:#?}", args);
+            set(pool, args).await
+        }
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,410 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::format::{Item, StrftimeItems};
+use clap::Parser;
+use toml_edit::DocumentMut;
+
+use crate::{
+    backup,
+    config::{self, Config, Data, MAIN_PROFILE},
+    letter, manager, utils, Error,
+};
+
+// the placeholders the folder, file, metadata and letter names may use
+const NAME_PLACEHOLDERS: [&str; 3] = ["{company}", "{position}", "{date}"];
+
+#[derive(Parser)]
+#[command(
+    name = "config",
+    about = "Inspect and edit the config file",
+    arg_required_else_help = true
+)]
+pub struct ConfigCommand {
+    #[command(subcommand)]
+    pub command: Option<ConfigCommands>,
+}
+
+#[derive(Parser)]
+pub enum ConfigCommands {
+    Path(PathCommand),
+    Show(ShowCommand),
+    Validate(ValidateCommand),
+    Set(SetCommand),
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "path",
+    about = "Print the config file in use and why it was picked"
+)]
+pub struct PathCommand;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "show",
+    about = "Print the effective config, with the defaults filled in"
+)]
+pub struct ShowCommand;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "validate",
+    about = "Check the name patterns, date format and directories",
+    visible_aliases = ["check"]
+)]
+pub struct ValidateCommand;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "set",
+    about = "Set a value in the config file, keeping its comments and layout"
+)]
+pub struct SetCommand {
+    #[arg(index = 1, help = "Key to set, e.g. main.default_group")]
+    pub key: String,
+
+    #[arg(
+        index = 2,
+        help = "New value, parsed as TOML if possible (16, true, \"text\") and as a string otherwise"
+    )]
+    pub value: String,
+}
+
+// unlike the other commands this runs before the config is loaded,
+// so it still works when the config is broken
+pub fn execute(
+    config_path: Option<PathBuf>,
+    profile: Option<&str>,
+    set: &[String],
+    args: &ConfigCommand,
+) -> Result<()> {
+    match &args.command {
+        Some(ConfigCommands::Path(_)) => path(config_path),
+        Some(ConfigCommands::Show(_)) => show(config_path, profile, set),
+        Some(ConfigCommands::Validate(_)) => validate(config_path, profile, set),
+        Some(ConfigCommands::Set(args)) => set_value(config_path, args),
+        None => Ok(()),
+    }
+}
+
+fn path(config_path: Option<PathBuf>) -> Result<()> {
+    let location = utils::locate_config(config_path)?;
+    // only the path goes to stdout, so $(resuman config path) works
+    println!("{}", location.path.display());
+
+    if location.explicit {
+        eprintln!("passed with --config, no other file was looked at");
+        return Ok(());
+    }
+    eprintln!("first existing file, in search order:");
+    for candidate in &location.candidates {
+        let state = if *candidate == location.path {
+            "used"
+        } else if candidate.exists() {
+            // only files after the used one can exist
+            "ignored"
+        } else {
+            "missing"
+        };
+        eprintln!("  {}\t{}", state, candidate.display());
+    }
+    if location.path == location.candidates[0] {
+        eprintln!(
+            "{} in the current directory is meant for debugging, it takes priority over the others",
+            location.path.display()
+        );
+    }
+    Ok(())
+}
+
+/// The name of the selected profile, and its config with defaults filled in and
+/// root_dir resolved
+fn resolved(
+    config_path: Option<PathBuf>,
+    profile: Option<&str>,
+    set: &[String],
+) -> Result<(String, Config)> {
+    let data = utils::load_config(config_path, set)?;
+    let cfg = data.profile(profile)?.with_defaults();
+    Ok((data.active_profile(profile).to_string(), cfg))
+}
+
+/// Unknown placeholders in a name pattern, e.g. `{compnay}`
+fn unknown_placeholders(pattern: &str) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            unknown.push(rest[start..].to_string());
+            break;
+        };
+        let placeholder = &rest[start..start + len + 1];
+        if !NAME_PLACEHOLDERS.contains(&placeholder) {
+            unknown.push(placeholder.to_string());
+        }
+        rest = &rest[start + len + 1..];
+    }
+    unknown
+}
+
+/// Prints every value, with the layer it comes from as a comment
+fn show(config_path: Option<PathBuf>, profile: Option<&str>, set: &[String]) -> Result<()> {
+    let data = utils::load_config(config_path, set)?;
+    let name = data.active_profile(profile);
+    let cfg = data.profile(profile)?.with_defaults();
+    let main = toml::Table::try_from(&data.main)?;
+    let profile_table = data.profiles.get(name);
+
+    println!("# profile {}", name);
+    for (key, value) in toml::Table::try_from(&cfg)? {
+        let set_here = set.iter().any(|pair| {
+            pair.split_once('=')
+                .is_some_and(|(k, _)| k.trim().trim_start_matches("main.") == key)
+        });
+        let origin = if set_here {
+            "--set".to_string()
+        } else if std::env::var(config::env_var(&key)).is_ok() {
+            config::env_var(&key)
+        } else if profile_table.is_some_and(|table| table.contains_key(&key)) {
+            format!("[profiles.{}]", name)
+        } else if key == "root_dir" && name != MAIN_PROFILE {
+            "[main], in a folder named after the profile".to_string()
+        } else if main.contains_key(&key) {
+            "[main]".to_string()
+        } else {
+            "default".to_string()
+        };
+        println!("{} = {} # {}", key, value, origin);
+    }
+    Ok(())
+}
+
+fn validate(config_path: Option<PathBuf>, profile: Option<&str>, set: &[String]) -> Result<()> {
+    let (name, cfg) = resolved(config_path, profile, set)?;
+    println!("ok\tprofile {}", name);
+    let mut errors = 0;
+    let mut report = |level: &str, message: String| {
+        if level == "error" {
+            errors += 1;
+        }
+        println!("{}\t{}", level, message);
+    };
+
+    let patterns = [
+        ("folder_pattern", &cfg.folder_pattern),
+        ("file_pattern", &cfg.file_pattern),
+        ("metadata_name", &cfg.metadata_name),
+        ("cover_letter_name", &cfg.cover_letter_name),
+    ];
+    for (key, pattern) in patterns {
+        let pattern = pattern.as_deref().unwrap_or_default();
+        let unknown = unknown_placeholders(pattern);
+        if !unknown.is_empty() {
+            report(
+                "error",
+                format!(
+                    "{} {:?} has unknown placeholders {}, use {}",
+                    key,
+                    pattern,
+                    unknown.join(" "),
+                    NAME_PLACEHOLDERS.join(" ")
+                ),
+            );
+        } else if pattern.contains('/') {
+            report(
+                "error",
+                format!("{} {:?} must not contain '/'", key, pattern),
+            );
+        } else {
+            report("ok", format!("{} {:?}", key, pattern));
+        }
+    }
+    // without these two, a second resume for the same job would collide with the first
+    let folder_pattern = cfg.folder_pattern.as_deref().unwrap_or_default();
+    if !folder_pattern.contains("{company}") || !folder_pattern.contains("{date}") {
+        report(
+            "warn",
+            format!(
+                "folder_pattern {:?} should contain {{company}} and {{date}}",
+                folder_pattern
+            ),
+        );
+    }
+    let file_pattern = cfg.file_pattern.as_deref().unwrap_or_default();
+    if !file_pattern.ends_with(".tex") {
+        report(
+            "warn",
+            format!("file_pattern {:?} should end with .tex", file_pattern),
+        );
+    }
+
+    let date_format = cfg.date_format.as_deref().unwrap_or_default();
+    if StrftimeItems::new(date_format).any(|item| item == Item::Error) {
+        report(
+            "error",
+            format!(
+                "date_format {:?} is not a valid strftime format",
+                date_format
+            ),
+        );
+    } else {
+        let example = chrono::Utc::now().format(date_format).to_string();
+        if example.contains('/') {
+            report(
+                "error",
+                format!(
+                    "date_format {:?} gives {:?}, which must not contain '/'",
+                    date_format, example
+                ),
+            );
+        } else {
+            report(
+                "ok",
+                format!("date_format {:?} gives {:?}", date_format, example),
+            );
+        }
+    }
+
+    match backup::journal_mode(&cfg) {
+        Ok(_) => report(
+            "ok",
+            format!(
+                "journal_mode {:?}",
+                cfg.journal_mode.as_deref().unwrap_or_default()
+            ),
+        ),
+        Err(e) => report("error", e.to_string()),
+    }
+
+    let template_dir = manager::template_dir(&cfg);
+    let default_template = template_dir
+        .join(cfg.default_template.as_deref().unwrap_or_default())
+        .with_extension("tex");
+    let letter_dir = letter::letter_template_dir(&cfg);
+    let db_path = cfg.root_dir.join(cfg.db_path.clone().unwrap_or_default());
+    for (name, path, is_dir) in [
+        ("root_dir", &cfg.root_dir, true),
+        ("template_dir", &template_dir, true),
+        ("default_template", &default_template, false),
+        ("letter_template_dir", &letter_dir, true),
+        ("db_path", &db_path, false),
+    ] {
+        if !path.exists() {
+            // every one of these is created by init or on first use
+            report(
+                "warn",
+                format!("{} {} does not exist yet", name, path.display()),
+            );
+        } else if path.is_dir() != is_dir {
+            let kind = if is_dir { "a directory" } else { "a file" };
+            report(
+                "error",
+                format!("{} {} is not {}", name, path.display(), kind),
+            );
+        } else {
+            report("ok", format!("{} {}", name, path.display()));
+        }
+    }
+
+    if errors > 0 {
+        return Err(Error::Invalid(format!("The config has {} error(s)", errors)).into());
+    }
+    Ok(())
+}
+
+fn set_value(config_path: Option<PathBuf>, args: &SetCommand) -> Result<()> {
+    let location = utils::locate_config(config_path)?;
+
+    // main.<key> or profiles.<name>.<key>, a bare key is under [main]
+    let parts: Vec<&str> = args.key.split('.').collect();
+    let (tables, key) = match parts.as_slice() {
+        [key] => (vec![MAIN_PROFILE], *key),
+        [MAIN_PROFILE, key] => (vec![MAIN_PROFILE], *key),
+        ["profiles", name, key] => (vec!["profiles", *name], *key),
+        _ => (Vec::new(), ""),
+    };
+    let known = Config::keys();
+    if tables.is_empty() || !known.iter().any(|k| k == key) {
+        return Err(Error::Invalid(format!(
+            "Unknown config key {}, the keys are main.<key> or profiles.<name>.<key> with <key> one of {}",
+            args.key,
+            known.join(", ")
+        ))
+        .into());
+    }
+
+    // 16 or true keep their type, anything that is not valid TOML is a string
+    let value = args
+        .value
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| toml_edit::Value::from(args.value.as_str()));
+    let contents = edit_config(&location.path, &tables, key, Some(value))?;
+
+    // check the types before anything is written
+    let profile = if tables[0] == "profiles" {
+        tables[1]
+    } else {
+        MAIN_PROFILE
+    };
+    let checked = toml::from_str::<Data>(&contents)
+        .map_err(|e| e.message().to_string())
+        .and_then(|data| data.profile(Some(profile)).map_err(|e| e.to_string()));
+    if let Err(e) = checked {
+        return Err(Error::Invalid(format!(
+            "{} is not a valid value for {}: {}",
+            args.value, args.key, e
+        ))
+        .into());
+    }
+    fs::write(&location.path, contents)?;
+    println!("{}", location.path.display());
+    Ok(())
+}
+
+/// Sets or with `None` removes `key` in the nested `tables` of a config file, keeping
+/// its comments and layout, and returns the new contents without writing them
+pub fn edit_config(
+    path: &Path,
+    tables: &[&str],
+    key: &str,
+    value: Option<toml_edit::Value>,
+) -> Result<String> {
+    let mut doc: DocumentMut = fs::read_to_string(path)?
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Could not parse {}: {}", path.display(), e))?;
+
+    let mut table = doc.as_table_mut();
+    for name in tables {
+        if !table.contains_key(name) {
+            let mut new = toml_edit::Table::new();
+            // [profiles.x] does not need a [profiles] above it
+            new.set_implicit(true);
+            table.insert(name, toml_edit::Item::Table(new));
+        }
+        table = table[name]
+            .as_table_mut()
+            .ok_or_else(|| Error::Invalid(format!("{} is not a table", name)))?;
+    }
+
+    match (
+        table.get_mut(key).and_then(|item| item.as_value_mut()),
+        value,
+    ) {
+        (Some(old), Some(mut value)) => {
+            // keep the comment after the old value
+            *value.decor_mut() = old.decor().clone();
+            *old = value;
+        }
+        (None, Some(value)) => {
+            table.insert(key, toml_edit::Item::Value(value));
+        }
+        (_, None) => {
+            table.remove(key);
+        }
+    }
+    Ok(doc.to_string())
+}
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{backup, config::Config, Error, Resuman, MIGRATOR};
+
+#[derive(Parser)]
+#[command(
+    name = "db",
+    about = "Show, apply and roll back the migrations of the db",
+    long_about = "Show, apply and roll back the migrations of the db. Every other command applies the pending migrations first, unless given --no-auto-migrate",
+    arg_required_else_help = true
+)]
+pub struct DbCommand {
+    #[command(subcommand)]
+    pub command: Option<DbCommands>,
+}
+
+#[derive(Parser)]
+pub enum DbCommands {
+    Status(StatusCommand),
+    Migrate(MigrateCommand),
+    Rollback(RollbackCommand),
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "status",
+    about = "List the migrations: version, description, state, installed on and checksum",
+    long_about = "List the migrations: version, description, state, installed on and checksum. The state is applied, pending, changed when the migration was edited after it was applied, or unknown when it comes from a newer resuman"
+)]
+pub struct StatusCommand;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "migrate",
+    about = "Apply the pending migrations, after backing up the db"
+)]
+pub struct MigrateCommand;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "rollback",
+    about = "Revert the migrations newer than a version, after backing up the db",
+    long_about = "Revert the migrations newer than a version, after backing up the db. The next command migrates the db again unless given --no-auto-migrate, so this is mostly for going back to an older resuman"
+)]
+pub struct RollbackCommand {
+    #[arg(
+        long,
+        value_name = "VERSION",
+        help = "Version to go back to, 0 reverts every migration"
+    )]
+    pub to: i64,
+}
+
+// a row of _sqlx_migrations
+type Applied = (i64, String, String, Vec<u8>);
+
+async fn applied(pool: &SqlitePool) -> Result<Vec<Applied>> {
+    if backup::applied_versions(&mut *pool.acquire().await?)
+        .await?
+        .is_empty()
+    {
+        return Ok(Vec::new());
+    }
+    Ok(sqlx::query_as(
+        "SELECT version, description, installed_on, checksum FROM _sqlx_migrations
+    WHERE success = 1 ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+/// The start of a checksum, like a short git hash
+fn short(checksum: &[u8]) -> String {
+    checksum
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn description(version: i64) -> String {
+    MIGRATOR
+        .iter()
+        .find(|m| m.version == version)
+        .map_or("null".to_string(), |m| m.description.to_string())
+}
+
+// runs on a db that was not migrated, so status can show what is pending
+pub async fn execute(cfg: Config, args: &DbCommand, pool: &SqlitePool) -> Result<()> {
+    let resuman = Resuman::from_pool(cfg.clone(), pool.clone());
+    match &args.command {
+        Some(DbCommands::Status(_)) => {
+            let applied = applied(pool).await?;
+            let mut versions: Vec<i64> = applied.iter().map(|a| a.0).collect();
+            versions.extend(backup::known_versions());
+            versions.sort();
+            versions.dedup();
+
+            for version in versions {
+                let known = MIGRATOR
+                    .iter()
+                    .find(|m| m.version == version && !m.migration_type.is_down_migration());
+                let row = applied.iter().find(|a| a.0 == version);
+                let (state, installed_on, checksum) = match (known, row) {
+                    (Some(m), None) => ("pending", "null".to_string(), short(&m.checksum)),
+                    (Some(m), Some(a)) if *m.checksum != *a.3 => {
+                        ("changed", a.2.clone(), short(&a.3))
+                    }
+                    (Some(_), Some(a)) => ("applied", a.2.clone(), short(&a.3)),
+                    (None, Some(a)) => ("unknown", a.2.clone(), short(&a.3)),
+                    (None, None) => continue,
+                };
+                let description = row.map_or(description(version), |a| a.1.clone());
+                println!(
+                    "{}\t{}\t{}\t{}\t{}",
+                    version, description, state, installed_on, checksum
+                );
+            }
+            Ok(())
+        }
+        Some(DbCommands::Migrate(_)) => {
+            let pending = backup::pending_versions(pool).await?;
+            resuman.migrate().await?;
+            if pending.is_empty() {
+                eprintln!("The db is up to date");
+            }
+            for version in pending {
+                println!("applied\t{}\t{}", version, description(version));
+            }
+            Ok(())
+        }
+        Some(DbCommands::Rollback(args)) => {
+            if args.to != 0 && !backup::known_versions().contains(&args.to) {
+                return Err(Error::Invalid(format!(
+                    "Unknown migration {}, see `resuman db status` for the versions",
+                    args.to
+                ))
+                .into());
+            }
+            let reverted: Vec<i64> = backup::applied_versions(&mut *pool.acquire().await?)
+                .await?
+                .into_iter()
+                .filter(|v| *v > args.to)
+                .rev()
+                .collect();
+            if reverted.is_empty() {
+                eprintln!("Nothing to roll back, the db is at or before {}", args.to);
+                return Ok(());
+            }
+
+            let path = backup::backup(pool, &cfg, Some("pre-rollback")).await?;
+            eprintln!("Backed up the db to {}", path.display());
+            MIGRATOR
+                .undo(pool, args.to)
+                .await
+                .map_err(Error::Migration)?;
+            for version in reverted {
+                println!("reverted\t{}\t{}", version, description(version));
+            }
+            eprintln!(
+                "Run the next commands with --no-auto-migrate to keep the db at {}",
+                args.to
+            );
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
@@ -2,7 +2,7 @@
 // --file flag should be used to remove the actual file
 // there's no reason to remove the file but not from the db...
 
-use crate::config::Config;
+use crate::{config::Config, manager::Resuman};
 use anyhow::Result;
 use clap::Parser;
 use sqlx::SqlitePool;
@@ -17,26 +17,11 @@ pub struct DeleteCommand {
     pub file: bool,
 }
 
-pub async fn execute(_cfg: Config, args: &DeleteCommand, pool: &SqlitePool) -> Result<()> {
+pub async fn execute(cfg: Config, args: &DeleteCommand, pool: &SqlitePool) -> Result<()> {
     log::debug!("Deleting with arguments:\n{:#?}", args);
 
-    if args.file {
-        // run sql queries to get file path from db
-        // run fs commands to delete file
-        let path = sqlx::query!("SELECT file_path FROM resumes WHERE id = ?", args.id)
-            .fetch_one(pool)
-            .await?
-            .file_path;
-
-        // get the entire directory of the file and remove that
-        std::fs::remove_dir_all(path)?;
-    }
-
-    // run sql queries to delete resume from db
-    // run sql queries to delete resume from db
-    sqlx::query!("DELETE FROM resumes WHERE id = ?", args.id)
-        .execute(pool)
+    Resuman::from_pool(cfg, pool.clone())
+        .delete(args.id.into(), args.file)
         .await?;
-
     Ok(())
 }
@@ -0,0 +1,188 @@
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{commands::submit, config::Config, manager::Resuman, Error};
+
+// lines around each change, like diff -u
+const CONTEXT: usize = 3;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "diff",
+    about = "Show what changed in the files of a resume since it was submitted",
+    arg_required_else_help = true
+)]
+pub struct DiffCommand {
+    #[arg(index = 1, help = "ID of the resume")]
+    pub id: i64,
+
+    // the only thing to compare with for now, so also what a bare `diff <id>` does
+    #[arg(
+        long,
+        help = "Compare with the files frozen by `resuman submit`, the default"
+    )]
+    pub submitted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The lines of `old` and `new` matched up by their longest common subsequence
+fn lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    // common[i][j] is the length of the common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Line::Same(old[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(Line::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// A unified diff of two texts, empty if they have the same lines
+pub fn unified(old_name: &str, new_name: &str, old: &str, new: &str) -> String {
+    let lines = lines(old, new);
+    let changed: Vec<usize> = (0..lines.len())
+        .filter(|i| !matches!(lines[*i], Line::Same(_)))
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // changes closer than twice the context share a hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &i in &changed {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    for (start, end) in hunks {
+        // where the hunk starts in each file, counted from 1
+        let before = &lines[..start];
+        let old_start = before
+            .iter()
+            .filter(|l| !matches!(l, Line::Added(_)))
+            .count();
+        let new_start = before
+            .iter()
+            .filter(|l| !matches!(l, Line::Removed(_)))
+            .count();
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|l| !matches!(l, Line::Added(_))).count();
+        let new_len = hunk
+            .iter()
+            .filter(|l| !matches!(l, Line::Removed(_)))
+            .count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            old_len,
+            new_start + 1,
+            new_len
+        ));
+        for line in hunk {
+            let (sign, text) = match line {
+                Line::Same(t) => (' ', t),
+                Line::Removed(t) => ('-', t),
+                Line::Added(t) => ('+', t),
+            };
+            out.push_str(&format!("{}{}\n", sign, text));
+        }
+    }
+    out
+}
+
+/// The diff of a frozen file against the file it was copied from
+fn compare(frozen: &Path, current: &Path) -> Result<String> {
+    let name = |p: &Path| p.display().to_string();
+    if !current.exists() {
+        return Ok(format!(
+            "{} is gone, only the frozen {} is left\n",
+            name(current),
+            name(frozen)
+        ));
+    }
+    let (old, new) = (fs::read(frozen)?, fs::read(current)?);
+    if old == new {
+        return Ok(String::new());
+    }
+    match (String::from_utf8(old), String::from_utf8(new)) {
+        (Ok(old), Ok(new)) => Ok(unified(&name(frozen), &name(current), &old, &new)),
+        // the PDF, which can only be told apart
+        _ => Ok(format!(
+            "Binary files {} and {} differ\n",
+            name(frozen),
+            name(current)
+        )),
+    }
+}
+
+pub async fn execute(cfg: Config, args: &DiffCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Diff with arguments:\n{:#?}", args);
+
+    let resuman = Resuman::from_pool(cfg, pool.clone());
+    let resume = resuman.get(args.id).await?;
+    let submissions = submit::submissions(pool, Some(args.id)).await?;
+    if submissions.is_empty() {
+        return Err(Error::Invalid(format!(
+            "Resume {} was not submitted, freeze what was sent with `resuman submit {}`",
+            args.id, args.id
+        ))
+        .into());
+    }
+
+    let mut changed = false;
+    for s in &submissions {
+        let frozen = s.path(&resume);
+        let Some(current) = s.source(&resume) else {
+            continue;
+        };
+        if !frozen.exists() {
+            return Err(Error::InvalidPath(frozen, "the frozen copy is gone".to_string()).into());
+        }
+        let diff = compare(&frozen, &current)?;
+        changed |= !diff.is_empty();
+        print!("{}", diff);
+    }
+    if !changed {
+        eprintln!(
+            "Nothing changed since resume {} was submitted on {}",
+            args.id,
+            submissions[0].submitted_at.format("%Y-%m-%d")
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use std::{fs, io::IsTerminal};
+
+use anyhow::Result;
+use clap::Parser;
+use dialoguer::{theme::ColorfulTheme, Select};
+use sqlx::SqlitePool;
+
+use crate::{
+    commands::submit, config::Config, duplicate, manager::Resuman, resume::Patch, tag, url, Error,
+    Resume,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "dupes",
+    about = "List groups of resumes that look like the same application and merge them",
+    long_about = "List groups of resumes that look like the same application and merge them. Company and position match without case, punctuation and legal forms like Inc., up to a typo, and the resumes were created within duplicate_window_days of each other",
+    visible_aliases = ["duplicates"]
+)]
+pub struct DupesCommand {
+    #[command(subcommand)]
+    pub command: Option<DupesCommands>,
+
+    #[arg(
+        long,
+        value_name = "DAYS",
+        help = "How many days apart duplicates can be, duplicate_window_days without it"
+    )]
+    pub days: Option<u16>,
+
+    #[arg(
+        short,
+        long,
+        help = "Ask which resume of each group to keep and merge the others into it"
+    )]
+    pub merge: bool,
+}
+
+#[derive(Parser)]
+pub enum DupesCommands {
+    Merge(MergeCommand),
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "merge",
+    about = "Merge resumes into one, their tags, urls, offers and notes move to it",
+    long_about = "Merge resumes into one, their tags, urls, offers and notes move to it and their rows are deleted. Their folders are kept unless --file is given, and resumes that were submitted cannot be merged away"
+)]
+pub struct MergeCommand {
+    #[arg(index = 1, help = "ID of the resume to keep")]
+    pub keep: i64,
+
+    #[arg(
+        index = 2,
+        required = true,
+        help = "IDs of the resumes to merge into it"
+    )]
+    pub ids: Vec<i64>,
+
+    #[arg(short, long, help = "Remove the folders of the merged resumes")]
+    pub file: bool,
+}
+
+fn describe(r: &Resume) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        r.id,
+        r.company,
+        r.position,
+        r.created_at.format("%Y-%m-%d"),
+        r.metadata.status
+    )
+}
+
+/// Moves everything attached to `ids` to `keep`, deletes them and returns them
+///
+/// The ids are all checked before anything is written, and the db is changed in one
+/// transaction, so a merge that fails leaves every resume as it was.
+pub async fn merge(
+    resuman: &Resuman,
+    keep: i64,
+    ids: &[i64],
+    remove_files: bool,
+) -> Result<Vec<Resume>> {
+    let pool = resuman.pool();
+    let kept = resuman.get(keep).await?;
+    let mut merged: Vec<Resume> = Vec::new();
+    for id in ids {
+        if *id == keep {
+            return Err(
+                Error::Invalid(format!("Resume {} cannot be merged into itself", id)).into(),
+            );
+        }
+        // `dupes merge 1 2 2` merges 2 once
+        if merged.iter().any(|r| r.id == *id) {
+            continue;
+        }
+        if !submit::submissions(pool, Some(*id)).await?.is_empty() {
+            return Err(Error::Invalid(format!(
+                "Resume {} was submitted, keep it instead so its frozen files stay tracked",
+                id
+            ))
+            .into());
+        }
+        merged.push(resuman.get(*id).await?);
+    }
+
+    // notes are put together, and the earliest time it was sent is kept
+    let mut notes = vec![kept.metadata.notes.clone()];
+    for r in &merged {
+        if !notes.contains(&r.metadata.notes) {
+            notes.push(r.metadata.notes.clone());
+        }
+    }
+    notes.retain(|n| !n.trim().is_empty());
+    let notes = notes.join("\n");
+    let applied_time = merged
+        .iter()
+        .chain([&kept])
+        .filter_map(|r| r.metadata.applied_time)
+        .min();
+    let patch = Patch {
+        notes: (notes != kept.metadata.notes).then_some(notes),
+        applied_time: applied_time.filter(|t| Some(*t) != kept.metadata.applied_time),
+        ..Default::default()
+    };
+
+    let mut tx = pool.begin().await?;
+    for r in &merged {
+        sqlx::query!(
+            "INSERT OR IGNORE INTO resume_tags (resume_id, tag_id) SELECT ?1, tag_id FROM resume_tags WHERE resume_id = ?2",
+            keep,
+            r.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        // the same posting is often attached to both
+        sqlx::query!(
+            "UPDATE resume_urls SET resume_id = ?1 WHERE resume_id = ?2 AND url NOT IN (SELECT url FROM resume_urls WHERE resume_id = ?1)",
+            keep,
+            r.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE offers SET resume_id = ?1 WHERE resume_id = ?2",
+            keep,
+            r.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE metadata SET copied_from_id = ?1 WHERE copied_from_id = ?2 AND resume_id != ?1",
+            keep,
+            r.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!("DELETE FROM resumes WHERE id = ?", r.id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    sqlx::query!(
+        "UPDATE metadata SET notes = COALESCE(?1, notes), applied_time = COALESCE(?2, applied_time) WHERE resume_id = ?3",
+        patch.notes,
+        patch.applied_time,
+        keep
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    // the files follow the db, the update writes the notes to the metadata file again
+    // and commits the folder
+    if remove_files {
+        for r in &merged {
+            let path = std::path::PathBuf::from(&r.file_path);
+            fs::remove_dir_all(path.parent().unwrap_or(&path))?;
+        }
+    }
+    url::sync_metadata_urls(pool, keep).await?;
+    tag::sync_metadata_tags(pool, keep).await?;
+    resuman.update(keep, patch).await?;
+    Ok(merged)
+}
+
+/// Prints what was merged, and where the folders that were kept are
+fn report(merged: &[Resume], keep: i64, removed: bool) {
+    for r in merged {
+        println!("merged\t{}\t{}", r.id, keep);
+        if !removed {
+            if let Some(folder) = std::path::Path::new(&r.file_path).parent() {
+                eprintln!("Kept the folder of {} at {}", r.id, folder.display());
+            }
+        }
+    }
+}
+
+pub async fn execute(cfg: Config, args: &DupesCommand, pool: &SqlitePool) -> Result<()> {
+    let resuman = Resuman::from_pool(cfg, pool.clone());
+    if let Some(DupesCommands::Merge(args)) = &args.command {
+        log::debug!("Merging with arguments:\n{:#?}", args);
+        let merged = merge(&resuman, args.keep, &args.ids, args.file).await?;
+        report(&merged, args.keep, args.file);
+        return Ok(());
+    }
+
+    let days = args
+        .days
+        .map(i64::from)
+        .unwrap_or(duplicate::window_days(resuman.config()));
+    let resumes = resuman.list().await?;
+    let clusters = duplicate::clusters(&resumes, days);
+    let by_id = |id: &i64| resumes.iter().find(|r| r.id == *id);
+
+    if !args.merge {
+        println!("group\tid\tcompany\tposition\tcreated_at\tstatus");
+        for (i, cluster) in clusters.iter().enumerate() {
+            for r in cluster.iter().filter_map(by_id) {
+                println!("{}\t{}", i + 1, describe(r));
+            }
+        }
+        if !clusters.is_empty() {
+            eprintln!(
+                "Merge a group with `resuman dupes merge <keep> <ids>...` or `resuman dupes --merge`"
+            );
+        }
+        return Ok(());
+    }
+
+    if !std::io::stdin().is_terminal() {
+        return Err(Error::Invalid(
+            "--merge asks which resume to keep, use `resuman dupes merge <keep> <ids>...` without a terminal"
+                .to_string(),
+        )
+        .into());
+    }
+    let theme = ColorfulTheme::default();
+    for cluster in &clusters {
+        let group: Vec<&Resume> = cluster.iter().filter_map(by_id).collect();
+        let mut items: Vec<String> = group
+            .iter()
+            .map(|r| format!("Keep {}", describe(r).replace('\t', "  ")))
+            .collect();
+        items.push("Skip, they are different applications".to_string());
+        let choice = Select::with_theme(&theme)
+            .with_prompt("These look like the same application")
+            .items(&items)
+            .default(0)
+            .interact()?;
+        let Some(keep) = group.get(choice) else {
+            continue;
+        };
+        let ids: Vec<i64> = group
+            .iter()
+            .map(|r| r.id)
+            .filter(|id| *id != keep.id)
+            .collect();
+        match merge(&resuman, keep.id, &ids, false).await {
+            Ok(merged) => report(&merged, keep.id, false),
+            Err(e) => eprintln!("Could not merge into {}: {}", keep.id, e),
+        }
+    }
+    Ok(())
+}
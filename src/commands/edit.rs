@@ -0,0 +1,333 @@
+use std::{env, fs, path::Path};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use crate::{
+    config::Config,
+    manager::Resuman,
+    metadata,
+    resume::{Patch, Resume},
+    tag::{self, TagEdit},
+    utils,
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "edit",
+    about = "Edit the metadata of a resume in $EDITOR",
+    long_about = "Edit the metadata of a resume in $VISUAL or $EDITOR. On save the changes are checked, printed and applied like update would, an invalid edit opens the editor again with the error on top",
+    arg_required_else_help = true
+)]
+pub struct EditCommand {
+    #[arg(index = 1, help = "ID of the resume to edit")]
+    pub id: i64,
+
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = Format::Json,
+        help = "How the metadata is shown in the editor"
+    )]
+    pub format: Format,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Json,
+    Toml,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Toml => "toml",
+        }
+    }
+
+    // JSON has no comments, so lines starting with // are taken out before parsing
+    fn comment(self) -> &'static str {
+        match self {
+            Format::Json => "//",
+            Format::Toml => "#",
+        }
+    }
+}
+
+/// What can be edited, the fields update can change
+///
+/// urls are left out since update can only add them, see `resuman url`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Fields {
+    company: String,
+    position: String,
+    group: String,
+    template: String,
+    status: String,
+    #[serde(serialize_with = "metadata::serialize_timestamp")]
+    created_at: DateTime<Utc>,
+    #[serde(serialize_with = "metadata::serialize_optional_timestamp")]
+    applied_time: Option<DateTime<Utc>>,
+    length: u16,
+    location: Option<String>,
+    has_cover_letter: bool,
+    copied_from: Option<String>,
+    tags: Vec<String>,
+    notes: String,
+}
+
+impl From<&Resume> for Fields {
+    fn from(resume: &Resume) -> Self {
+        Fields {
+            company: resume.company.clone(),
+            position: resume.position.clone(),
+            group: resume.group.clone(),
+            template: resume.template.clone(),
+            status: resume.metadata.status.clone(),
+            created_at: resume.created_at,
+            applied_time: resume.metadata.applied_time,
+            length: resume.metadata.length.try_into().unwrap_or(u16::MAX),
+            location: resume.metadata.location.clone(),
+            has_cover_letter: resume.has_cover_letter,
+            copied_from: resume.metadata.copied_from.clone(),
+            tags: resume.tags.clone(),
+            notes: resume.metadata.notes.clone(),
+        }
+    }
+}
+
+/// The comments on top of the file: the error of the last try, how to use it and,
+/// since toml has no null, which values are not set
+fn header(current: &Fields, format: Format, id: i64, error: Option<&str>) -> String {
+    let comment = format.comment();
+    let mut text = String::new();
+    if let Some(error) = error {
+        let error = error.replace('\n', " ");
+        text.push_str(&format!("{} Error: {}\n{}\n", comment, error, comment));
+    }
+    text.push_str(&format!(
+        "{c} Editing resume {}, save and quit to apply, empty the file to cancel\n\
+         {c} urls are edited with `resuman url`, cleared values are not supported\n",
+        id,
+        c = comment
+    ));
+    let unset: Vec<&str> = [
+        ("applied_time", current.applied_time.is_none()),
+        ("location", current.location.is_none()),
+        ("copied_from", current.copied_from.is_none()),
+    ]
+    .into_iter()
+    .filter_map(|(key, unset)| unset.then_some(key))
+    .collect();
+    if format == Format::Toml && !unset.is_empty() {
+        text.push_str(&format!("{} not set: {}\n", comment, unset.join(", ")));
+    }
+    text
+}
+
+fn render(fields: &Fields, format: Format) -> Result<String> {
+    Ok(match format {
+        Format::Json => format!("{}\n", serde_json::to_string_pretty(fields)?),
+        Format::Toml => format!("\n{}", toml::to_string_pretty(fields)?),
+    })
+}
+
+fn parse(text: &str, format: Format) -> Result<Fields, String> {
+    match format {
+        Format::Json => {
+            let json: String = text
+                .lines()
+                .filter(|l| !l.trim_start().starts_with("//"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            serde_json::from_str(&json).map_err(|e| e.to_string())
+        }
+        Format::Toml => toml::from_str(text).map_err(|e| e.message().to_string()),
+    }
+}
+
+/// Checks the edit the way create and update check their flags, and lowercases the tags
+fn check(current: &Fields, edited: &mut Fields) -> Result<(), String> {
+    for (key, value) in [
+        ("company", &edited.company),
+        ("position", &edited.position),
+        ("group", &edited.group),
+        ("template", &edited.template),
+        ("status", &edited.status),
+    ] {
+        if value.trim().is_empty() {
+            return Err(format!("{} cannot be empty", key));
+        }
+    }
+    for (key, cleared) in [
+        (
+            "applied_time",
+            current.applied_time.is_some() && edited.applied_time.is_none(),
+        ),
+        (
+            "location",
+            current.location.is_some() && edited.location.is_none(),
+        ),
+        (
+            "copied_from",
+            current.copied_from.is_some() && edited.copied_from.is_none(),
+        ),
+    ] {
+        if cleared {
+            return Err(format!("{} cannot be cleared, put the old value back", key));
+        }
+    }
+    let mut tags = Vec::new();
+    for name in &edited.tags {
+        tags.push(tag::normalize_tag(name).map_err(|e| format!("tag {:?}: {}", name, e))?);
+    }
+    tags.sort();
+    tags.dedup();
+    edited.tags = tags;
+    Ok(())
+}
+
+/// Every field that differs, as (key, old, new) in JSON
+fn diff(current: &Fields, edited: &Fields) -> Vec<(String, String, String)> {
+    let current = json!(current);
+    let edited = json!(edited);
+    let mut changes = Vec::new();
+    if let (Some(current), Some(edited)) = (current.as_object(), edited.as_object()) {
+        for (key, old) in current {
+            let new = &edited[key];
+            if old != new {
+                changes.push((key.clone(), old.to_string(), new.to_string()));
+            }
+        }
+    }
+    changes
+}
+
+fn patch(current: &Fields, edited: &Fields) -> Patch {
+    let changed = |old: &String, new: &String| (old != new).then(|| new.clone());
+    let mut tags = Vec::new();
+    for name in &current.tags {
+        if !edited.tags.contains(name) {
+            tags.push(TagEdit::Remove(name.clone()));
+        }
+    }
+    for name in &edited.tags {
+        if !current.tags.contains(name) {
+            tags.push(TagEdit::Add(name.clone()));
+        }
+    }
+
+    Patch {
+        company: changed(&current.company, &edited.company),
+        group: changed(&current.group, &edited.group),
+        template: changed(&current.template, &edited.template),
+        position: changed(&current.position, &edited.position),
+        status: changed(&current.status, &edited.status),
+        notes: changed(&current.notes, &edited.notes),
+        created_at: (current.created_at != edited.created_at).then_some(edited.created_at),
+        applied_time: edited
+            .applied_time
+            .filter(|_| current.applied_time != edited.applied_time),
+        length: (current.length != edited.length).then_some(edited.length),
+        location: edited
+            .location
+            .clone()
+            .filter(|_| current.location != edited.location),
+        has_cover_letter: (current.has_cover_letter != edited.has_cover_letter)
+            .then_some(edited.has_cover_letter),
+        copied_from: edited
+            .copied_from
+            .clone()
+            .filter(|_| current.copied_from != edited.copied_from)
+            .map(Into::into),
+        tags,
+        ..Default::default()
+    }
+}
+
+/// Opens `path` in $VISUAL or $EDITOR, vi without either
+fn open_editor(path: &Path) -> Result<()> {
+    utils::open_in_editor(&utils::editor(), path).map_err(|e| anyhow!("{}, nothing was changed", e))
+}
+
+pub async fn execute(cfg: Config, args: &EditCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Edit with arguments:\n{:#?}", args);
+
+    let resuman = Resuman::from_pool(cfg, pool.clone());
+    let resume = resuman.get(args.id).await?;
+    let current = Fields::from(&resume);
+
+    let path = env::temp_dir().join(format!(
+        "resuman-{}-{}.{}",
+        args.id,
+        std::process::id(),
+        args.format.extension()
+    ));
+    let comment = args.format.comment();
+    let mut error: Option<String> = None;
+    let mut body = render(&current, args.format)?;
+    let edited = loop {
+        let shown = format!(
+            "{}{}",
+            header(&current, args.format, args.id, error.as_deref()),
+            body
+        );
+        fs::write(&path, &shown)?;
+        let text = match open_editor(&path).and_then(|_| Ok(fs::read_to_string(&path)?)) {
+            Ok(text) => text,
+            Err(e) => {
+                let _ = fs::remove_file(&path);
+                return Err(e);
+            }
+        };
+
+        // like git commit, an empty file gives up, and so does saving an error as it was
+        let empty = text
+            .lines()
+            .all(|l| l.trim().is_empty() || l.trim_start().starts_with(comment));
+        if empty || (error.is_some() && text == shown) {
+            let _ = fs::remove_file(&path);
+            eprintln!("Edit cancelled, nothing was changed");
+            return Ok(());
+        }
+
+        let result = parse(&text, args.format).and_then(|mut edited| {
+            check(&current, &mut edited)?;
+            Ok(edited)
+        });
+        match result {
+            Ok(edited) => break edited,
+            Err(e) => {
+                eprintln!("Invalid edit: {}", e);
+                // what was typed is kept, under a new header with the error
+                body = text
+                    .lines()
+                    .skip_while(|l| l.trim_start().starts_with(comment))
+                    .map(|l| format!("{}\n", l))
+                    .collect();
+                error = Some(e);
+            }
+        }
+    };
+    let _ = fs::remove_file(&path);
+
+    let changes = diff(&current, &edited);
+    if changes.is_empty() {
+        eprintln!("No changes");
+        return Ok(());
+    }
+    for (key, old, new) in &changes {
+        println!("{}\t{}\t{}", key, old, new);
+    }
+
+    let resume = resuman.update(args.id, patch(&current, &edited)).await?;
+    eprintln!("Updated {}", resume.metadata.metadata_file_path);
+    Ok(())
+}
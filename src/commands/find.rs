@@ -1,156 +1,147 @@
-use crate::config::Config;
+use crate::{config::Config, manager::Resuman, resume::Filter};
 use anyhow::Result;
 use clap::Parser;
 use sqlx::SqlitePool;
 
+// the filters that can be given as FIELD:VALUE terms
+const TERM_FIELDS: [&str; 11] = [
+    "company", "group", "template", "position", "location", "status", "tag", "city", "region",
+    "country", "mode",
+];
+
+fn parse_term(term: &str) -> Result<(String, String), String> {
+    match term.split_once(':') {
+        Some((field, value)) if TERM_FIELDS.contains(&field) => {
+            Ok((field.to_string(), value.to_string()))
+        }
+        _ => Err(format!(
+            "expected FIELD:VALUE with FIELD one of {}",
+            TERM_FIELDS.join(", ")
+        )),
+    }
+}
+
 #[derive(Parser, Debug)]
-#[command(name = "find", about = "Find a resume and return its id", visible_aliases = ["search", "query"])]
+#[command(
+    name = "find",
+    about = "Find a resume and return its id",
+    long_about = "Find a resume and return its id. Filters are given as flags or as FIELD:VALUE terms, e.g. `resuman find country:CA mode:remote`, and all of them must match",
+    visible_aliases = ["search", "query"]
+)]
 pub struct FindCommand {
+    #[clap(index = 1, value_name = "FIELD:VALUE", value_parser = parse_term, help = "Filter terms, FIELD is one of company, group, template, position, location, status, tag, city, region, country or mode")]
+    pub terms: Vec<(String, String)>,
+
     // filters
     #[clap(short, long, help = "Filter by company")]
-    company: Option<String>,
+    pub company: Option<String>,
 
     #[clap(short, long, help = "Filter by group")]
-    group: Option<String>,
+    pub group: Option<String>,
 
     #[clap(short, long, help = "Filter by template")]
-    template: Option<String>,
+    pub template: Option<String>,
 
     #[clap(short, long, help = "Filter by position")]
-    position: Option<String>,
+    pub position: Option<String>,
 
     #[clap(long = "letter", help = "Filter by cover letter status")]
-    has_cover_letter: Option<bool>,
+    pub has_cover_letter: Option<bool>,
 
     #[clap(long, help = "Filter by created date")]
-    created_at: Option<String>,
+    pub created_at: Option<String>,
 
     #[clap(long, help = "Filter by date applied")]
-    applied_time: Option<String>,
+    pub applied_time: Option<String>,
 
     #[clap(long, help = "Filter by length of job")]
-    length: Option<u16>,
+    pub length: Option<u16>,
 
     #[clap(short, long, help = "Filter by location")]
-    location: Option<String>,
+    pub location: Option<String>,
+
+    #[clap(long, help = "Filter by city, e.g. Toronto or nyc")]
+    pub city: Option<String>,
+
+    #[clap(long, help = "Filter by state or province, e.g. ON or Ontario")]
+    pub region: Option<String>,
+
+    #[clap(long, help = "Filter by country, e.g. CA or Canada")]
+    pub country: Option<String>,
+
+    #[clap(long, help = "Filter by work mode: onsite, hybrid or remote")]
+    pub mode: Option<String>,
 
     #[clap(short, long, help = "Filter by status")]
-    status: Option<String>,
+    pub status: Option<String>,
 
     #[clap(short, long, help = "Filter by file path")]
-    file_path: Option<String>,
+    pub file_path: Option<String>,
 
     #[clap(long, help = "Filter by copied from")]
-    copied_from: Option<String>,
+    pub copied_from: Option<String>,
+
+    #[clap(long = "tag", help = "Filter by tag, can be repeated (all must match)", value_parser = crate::tag::normalize_tag)]
+    pub tags: Vec<String>,
 
     #[clap(short, long, help = "Filter by all")]
-    all: Option<String>,
+    pub all: Option<String>,
 }
 
-pub async fn execute(_cfg: Config, args: &FindCommand, pool: &SqlitePool) -> Result<()> {
-    log::debug!("Find arguments:\n{:#?}", args);
-
-    // run sql queries to find resumes from db
-
-    if let Some(a) = &args.all {
-        // filter every field with a
-        // doesn't work with bools
-        let pattern = format!("%{}%", a);
-        let resumes = sqlx::query!(
-            "SELECT id FROM resumes 
-    JOIN metadata ON metadata.resume_id = resumes.id 
-    WHERE company LIKE ? 
-        OR \"group\" LIKE ? 
-        OR template LIKE ? 
-        OR position LIKE ? 
-        OR created_at LIKE ? 
-        OR applied_time LIKE ? 
-        OR length = ? 
-        OR location LIKE ? 
-        OR status LIKE ? 
-        OR file_path LIKE ? 
-        OR copied_from LIKE ?",
-            pattern,
-            pattern,
-            pattern,
-            pattern,
-            pattern,
-            pattern,
-            a,
-            pattern,
-            pattern,
-            pattern,
-            pattern
-        )
-        .fetch_all(pool)
-        .await?;
-        // output all ids
-        for r in resumes {
-            println!("{}", r.id);
+impl From<&FindCommand> for Filter {
+    fn from(cmd: &FindCommand) -> Self {
+        let mut filter = Filter {
+            company: cmd.company.clone(),
+            group: cmd.group.clone(),
+            template: cmd.template.clone(),
+            position: cmd.position.clone(),
+            has_cover_letter: cmd.has_cover_letter,
+            created_at: cmd.created_at.clone(),
+            applied_time: cmd.applied_time.clone(),
+            length: cmd.length,
+            location: cmd.location.clone(),
+            city: cmd.city.clone(),
+            region: cmd.region.clone(),
+            country: cmd.country.clone(),
+            work_mode: cmd.mode.clone(),
+            status: cmd.status.clone(),
+            file_path: cmd.file_path.clone(),
+            copied_from: cmd.copied_from.clone(),
+            tags: cmd.tags.clone(),
+            all: cmd.all.clone(),
+        };
+        // terms go after the flags, so they win
+        for (field, value) in &cmd.terms {
+            let value = Some(value.clone());
+            match field.as_str() {
+                "company" => filter.company = value,
+                "group" => filter.group = value,
+                "template" => filter.template = value,
+                "position" => filter.position = value,
+                "location" => filter.location = value,
+                "status" => filter.status = value,
+                "city" => filter.city = value,
+                "region" => filter.region = value,
+                "country" => filter.country = value,
+                "mode" => filter.work_mode = value,
+                _ => filter.tags.extend(value), // tag
+            }
         }
-        return Ok(());
-    }
-
-    // each arg should be AND not OR
-
-    let mut query =
-        "SELECT id FROM resumes JOIN metadata ON metadata.resume_id = resumes.id WHERE 1 = 1"
-            .to_string();
-
-    if let Some(c) = &args.company {
-        query.push_str(&format!(" AND company LIKE '%{}%'", c));
-    }
-
-    if let Some(g) = &args.group {
-        query.push_str(&format!(" AND \"group\" LIKE '%{}%'", g));
-    }
-
-    if let Some(t) = &args.template {
-        query.push_str(&format!(" AND template LIKE '%{}%'", t));
-    }
-
-    if let Some(p) = &args.position {
-        query.push_str(&format!(" AND position LIKE '%{}%'", p));
-    }
-
-    if let Some(h) = &args.has_cover_letter {
-        query.push_str(&format!(" AND has_cover_letter = {}", h));
-    }
-
-    if let Some(c) = &args.created_at {
-        query.push_str(&format!(" AND created_at LIKE '%{}%'", c));
-    }
-
-    if let Some(a) = &args.applied_time {
-        query.push_str(&format!(" AND applied_time LIKE '%{}%'", a));
-    }
-
-    if let Some(l) = &args.length {
-        query.push_str(&format!(" AND length = {}", l));
-    }
-
-    if let Some(l) = &args.location {
-        query.push_str(&format!(" AND location LIKE '%{}%'", l));
-    }
-
-    if let Some(s) = &args.status {
-        query.push_str(&format!(" AND status LIKE '%{}%'", s));
-    }
-
-    if let Some(f) = &args.file_path {
-        query.push_str(&format!(" AND file_path LIKE '%{}%'", f));
+        filter
     }
+}
 
-    if let Some(c) = &args.copied_from {
-        query.push_str(&format!(" AND copied_from LIKE '%{}%'", c));
-    }
+pub async fn execute(cfg: Config, args: &FindCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Find arguments:\n{:#?}", args);
 
-    let resumes = sqlx::query_as::<_, (i32,)>(query.as_str())
-        .fetch_all(pool)
+    // run sql queries to find resumes from db
+    let ids = Resuman::from_pool(cfg, pool.clone())
+        .find_ids(&Filter::from(args))
         .await?;
 
-    for r in resumes {
-        println!("{}", r.0);
+    // output all ids
+    for id in ids {
+        println!("{}", id);
     }
 
     // below is if each arg should be OR not AND
@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{config::Config, git, manager::Resuman, Error};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "log",
+    about = "List the versions of a resume kept in git, newest first",
+    long_about = "List the versions of a resume kept in git, newest first. Prints the revision, the time, the tags and what changed, the version that was sent is tagged resume-<id>-applied",
+    visible_aliases = ["history"],
+    arg_required_else_help = true
+)]
+pub struct LogCommand {
+    #[arg(index = 1, help = "ID of the resume")]
+    pub id: i64,
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "checkout",
+    about = "Restore the files of a resume to an earlier version and commit that",
+    long_about = "Restore the files of a resume to an earlier version and commit that. The metadata file is kept since it has to match the db, and files added after the version are left alone",
+    arg_required_else_help = true
+)]
+pub struct CheckoutCommand {
+    #[arg(index = 1, help = "ID of the resume")]
+    pub id: i64,
+
+    #[arg(
+        long,
+        value_name = "REV",
+        help = "Revision to restore, see `resuman log <id>`, or applied for the version that was sent"
+    )]
+    pub at: String,
+}
+
+/// The history lives in root_dir, without a repository there is nothing to read
+fn check_repo(cfg: &Config) -> Result<()> {
+    if !git::is_repo(cfg) {
+        return Err(Error::Invalid(format!(
+            "{} is not a git repository, turn on git with `resuman config set git true`",
+            cfg.root_dir.display()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+pub async fn log(cfg: Config, args: &LogCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Log with arguments:\n{:#?}", args);
+    check_repo(&cfg)?;
+
+    let resuman = Resuman::from_pool(cfg, pool.clone());
+    let resume = resuman.get(args.id).await?;
+    let path = PathBuf::from(&resume.file_path);
+    let commits = git::log(resuman.config(), path.parent().unwrap_or(&path))?;
+
+    println!("rev\ttime\ttags\tsubject");
+    for c in commits {
+        let tags = if c.tags.is_empty() {
+            "null".to_string()
+        } else {
+            c.tags.join(",")
+        };
+        println!("{}\t{}\t{}\t{}", c.rev, c.time, tags, c.subject);
+    }
+    Ok(())
+}
+
+pub async fn checkout(cfg: Config, args: &CheckoutCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Checkout with arguments:\n{:#?}", args);
+    check_repo(&cfg)?;
+
+    let resuman = Resuman::from_pool(cfg, pool.clone());
+    let resume = resuman.get(args.id).await?;
+    let rev = match args.at.as_str() {
+        "applied" => git::applied_tag(args.id),
+        rev => rev.to_string(),
+    };
+    let path = PathBuf::from(&resume.file_path);
+    let folder = path.parent().unwrap_or(&path);
+    let keep = [PathBuf::from(&resume.metadata.metadata_file_path)];
+    let message = format!(
+        "Restore resume {} ({} {}) to {}",
+        args.id, resume.company, resume.position, args.at
+    );
+    match git::restore(resuman.config(), folder, &rev, &keep, &message)? {
+        Some(new) => println!("{}", new),
+        None => eprintln!("Resume {} is already the same as {}", args.id, args.at),
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::{
+    fs,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use clap::Parser;
+use dialoguer::{theme::ColorfulTheme, Input};
+
+use crate::{git, letter, manager, utils, Error, Resuman};
+
+const CONFIG: &str = include_str!("../../assets/config.toml");
+const TEMPLATE: &str = include_str!("../../assets/default.tex");
+const LETTER: &str = include_str!("../../assets/cover_letter.txt");
+
+const DEFAULT_ROOT_DIR: &str = "~/resumes";
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "init",
+    about = "Set up resuman: config file, directories, starter templates and db",
+    long_about = "Set up resuman: config file, directories, starter templates and db. Safe to run again, nothing that already exists is overwritten"
+)]
+pub struct InitCommand {
+    #[arg(
+        short,
+        long,
+        value_name = "DIR",
+        help = "Root directory for a new config, prompted for if missing (default ~/resumes)"
+    )]
+    pub root_dir: Option<PathBuf>,
+}
+
+/// Prints whether `path` was created now or was already there
+fn report(created: bool, path: &Path) {
+    let state = if created { "created" } else { "exists" };
+    println!("{}\t{}", state, path.display());
+}
+
+/// Writes `contents` to `path` unless it exists, returns whether it was written
+fn seed(path: &Path, contents: &str) -> Result<bool> {
+    if path.exists() {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+    Ok(true)
+}
+
+fn ask_root_dir() -> Result<PathBuf> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(PathBuf::from(DEFAULT_ROOT_DIR));
+    }
+    let root_dir: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Where should resumes be kept?")
+        .default(DEFAULT_ROOT_DIR.to_string())
+        .interact_text()?;
+    Ok(PathBuf::from(root_dir))
+}
+
+// runs before the config is loaded, since creating it is part of the job
+pub async fn execute(
+    config_path: Option<PathBuf>,
+    profile: Option<&str>,
+    set: &[String],
+    args: &InitCommand,
+) -> Result<()> {
+    log::debug!("Init with arguments:\n{:#?}", args);
+
+    // an existing config is never touched, a new one goes where --config points
+    // or to the preferred location
+    let (path, created) = match utils::locate_config(config_path.clone()) {
+        Ok(location) => (location.path, false),
+        Err(Error::ConfigNotFound(_)) => {
+            let path = match config_path {
+                Some(path) => path,
+                None => utils::home_dir()?.join(".config/resuman/config.toml"),
+            };
+            let mut root_dir = match &args.root_dir {
+                Some(root_dir) => root_dir.clone(),
+                None => ask_root_dir()?,
+            };
+            // the config wants absolute paths, so a relative one is taken from here
+            if root_dir.is_relative() && !root_dir.starts_with("~") {
+                root_dir = std::env::current_dir()?.join(root_dir);
+            }
+            let root_dir = toml_edit::Value::from(root_dir.to_string_lossy().as_ref());
+            seed(&path, &CONFIG.replace("{root_dir}", &root_dir.to_string()))?;
+            (path, true)
+        }
+        Err(e) => return Err(e.into()),
+    };
+    report(created, &path);
+
+    let cfg = utils::load_config(Some(path.clone()), set)?.profile(profile)?;
+    if !created && args.root_dir.is_some() {
+        eprintln!(
+            "Kept the root_dir of the existing config, change it with `resuman config set root_dir <DIR>`"
+        );
+    }
+
+    // checked before opening, which creates whatever is missing
+    let full = cfg.with_defaults();
+    let root_dir = cfg.root_dir.clone();
+    let template_dir = manager::template_dir(&cfg);
+    let letter_dir = letter::letter_template_dir(&cfg);
+    let db_path = root_dir.join(full.db_path.clone().unwrap_or_default());
+    let existed = [&root_dir, &template_dir, &letter_dir, &db_path].map(|p| p.exists());
+
+    Resuman::open(cfg.clone()).await?;
+
+    report(!existed[0], &root_dir);
+    report(!existed[1], &template_dir);
+    let template = template_dir
+        .join(full.default_template.clone().unwrap_or_default())
+        .with_extension("tex");
+    report(seed(&template, TEMPLATE)?, &template);
+    report(!existed[2], &letter_dir);
+    // any of the letter extensions counts as the default letter template
+    let letter_name = full.default_letter_template.clone().unwrap_or_default();
+    match letter::find_template(&cfg, &letter_name) {
+        Some(letter) => report(false, &letter),
+        None => {
+            let letter = letter_dir.join(letter_name).with_extension("txt");
+            report(seed(&letter, LETTER)?, &letter);
+        }
+    }
+    report(!existed[3], &db_path);
+    if git::enabled(&cfg) {
+        report(git::init(&cfg)?, &root_dir.join(".git"));
+    }
+
+    println!(
+        "Resuman is ready, edit your templates at {}",
+        template_dir.display()
+    );
+    Ok(())
+}
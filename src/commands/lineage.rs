@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{config::Config, manager::Resuman, Error, Resume};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "lineage",
+    about = "Show which resumes were copied from which, as a tree or Graphviz DOT",
+    long_about = "Show which resumes were copied from which, as a tree or Graphviz DOT. With an id only its family is shown, from the first resume it descends from down to every copy of a copy, without an id every family of more than one resume",
+    visible_aliases = ["tree"]
+)]
+pub struct LineageCommand {
+    #[arg(index = 1, help = "ID of the resume, every family without it")]
+    pub id: Option<i64>,
+
+    #[arg(long, help = "Print Graphviz DOT instead, e.g. for `dot -Tsvg`")]
+    pub dot: bool,
+}
+
+/// The resumes by id and the copies of each one, sorted
+struct Families<'a> {
+    resumes: BTreeMap<i64, &'a Resume>,
+    copies: BTreeMap<i64, Vec<i64>>,
+}
+
+impl<'a> Families<'a> {
+    fn new(resumes: &'a [Resume]) -> Self {
+        let mut copies: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+        for r in resumes {
+            if let Some(from) = r.metadata.copied_from_id {
+                copies.entry(from).or_default().push(r.id);
+            }
+        }
+        Families {
+            resumes: resumes.iter().map(|r| (r.id, r)).collect(),
+            copies,
+        }
+    }
+
+    fn parent(&self, id: i64) -> Option<i64> {
+        self.resumes
+            .get(&id)
+            .and_then(|r| r.metadata.copied_from_id)
+            .filter(|from| self.resumes.contains_key(from))
+    }
+
+    /// The first resume `id` descends from, copies made in a circle by hand stop there
+    fn root(&self, id: i64) -> i64 {
+        let mut seen = HashSet::from([id]);
+        let mut current = id;
+        while let Some(parent) = self.parent(current) {
+            if !seen.insert(parent) {
+                break;
+            }
+            current = parent;
+        }
+        current
+    }
+
+    /// The roots of every family of more than one resume
+    fn roots(&self) -> Vec<i64> {
+        let roots: HashSet<i64> = self.copies.keys().map(|id| self.root(*id)).collect();
+        let mut roots: Vec<i64> = roots.into_iter().collect();
+        roots.sort();
+        roots
+    }
+
+    /// `root` and everything copied from it, depth first with the depth of each
+    fn walk(&self, root: i64) -> Vec<(usize, i64, bool)> {
+        let mut out = Vec::new();
+        let mut seen = HashSet::new();
+        let mut stack = vec![(0, root, true)];
+        while let Some((depth, id, last)) = stack.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            out.push((depth, id, last));
+            let copies = self.copies.get(&id).cloned().unwrap_or_default();
+            for (i, copy) in copies.iter().enumerate().rev() {
+                stack.push((depth + 1, *copy, i == copies.len() - 1));
+            }
+        }
+        out
+    }
+}
+
+fn describe(resume: &Resume) -> String {
+    format!(
+        "{}\t{}\t{}\t{}",
+        resume.id,
+        resume.company,
+        resume.position,
+        resume.created_at.format("%Y-%m-%d")
+    )
+}
+
+/// The family as an indented tree, `marked` gets a * at the end
+fn tree(families: &Families, root: i64, marked: Option<i64>) -> String {
+    let mut out = String::new();
+    // whether the ancestor at each depth was the last copy, which ends its line
+    let mut open: Vec<bool> = Vec::new();
+    for (depth, id, last) in families.walk(root) {
+        open.truncate(depth);
+        let mut prefix = String::new();
+        for ended in open.iter().skip(1) {
+            prefix.push_str(if *ended { "    " } else { "│   " });
+        }
+        if depth > 0 {
+            prefix.push_str(if last { "└── " } else { "├── " });
+        }
+        open.push(last);
+
+        let resume = families.resumes[&id];
+        let mark = if marked == Some(id) { "\t*" } else { "" };
+        out.push_str(&format!("{}{}{}\n", prefix, describe(resume), mark));
+    }
+    out
+}
+
+fn dot(families: &Families, roots: &[i64], marked: Option<i64>) -> String {
+    let quote = |s: String| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut out = String::from("digraph lineage {\n    rankdir=LR;\n    node [shape=box];\n");
+    let mut edges = String::new();
+    for root in roots {
+        for (_, id, _) in families.walk(*root) {
+            let r = families.resumes[&id];
+            // \n is a line break in a DOT label
+            let label = format!(
+                "{} {}\\n{} {}",
+                r.id,
+                quote(r.company.clone()),
+                quote(r.position.clone()),
+                r.created_at.format("%Y-%m-%d")
+            );
+            let style = if marked == Some(id) {
+                ", style=bold"
+            } else {
+                ""
+            };
+            out.push_str(&format!("    {} [label=\"{}\"{}];\n", id, label, style));
+            if let Some(parent) = families.parent(id).filter(|_| id != *root) {
+                edges.push_str(&format!("    {} -> {};\n", parent, id));
+            }
+        }
+    }
+    out.push_str(&edges);
+    out.push_str("}\n");
+    out
+}
+
+pub async fn execute(cfg: Config, args: &LineageCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Lineage with arguments:\n{:#?}", args);
+
+    let resumes = Resuman::from_pool(cfg, pool.clone()).list().await?;
+    let families = Families::new(&resumes);
+    let roots = match args.id {
+        Some(id) if !families.resumes.contains_key(&id) => {
+            return Err(Error::ResumeNotFound(id).into())
+        }
+        Some(id) => vec![families.root(id)],
+        None => families.roots(),
+    };
+
+    if args.dot {
+        print!("{}", dot(&families, &roots, args.id));
+        return Ok(());
+    }
+    if roots.is_empty() {
+        eprintln!("No resume was copied from another one, see `resuman create --from <id>`");
+    }
+    for (i, root) in roots.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        print!("{}", tree(&families, *root, args.id));
+    }
+    Ok(())
+}
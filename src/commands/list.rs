@@ -92,6 +92,9 @@ pub struct AllCommand {
     #[arg(short, long, help = "File path of the resume")]
     pub file_path: bool,
 
+    #[arg(long, help = "File path of the cover letter", visible_aliases = ["letter-path"])]
+    pub cover_letter_path: bool,
+
     #[arg(long, help = "Date created of the resume")]
     pub created_at: bool,
 
@@ -118,6 +121,17 @@ pub struct AllCommand {
 
     #[arg(short, long, help = "Notes of the resume")]
     pub notes: bool,
+
+    #[arg(long, help = "Tags of the resume")]
+    pub tags: bool,
+
+    // filters
+    #[arg(
+        long = "tag",
+        help = "Only list resumes with this tag, can be repeated (all must match)",
+        value_parser = crate::tag::normalize_tag
+    )]
+    pub filter_tags: Vec<String>,
 }
 
 pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Result<()> {
@@ -125,33 +139,25 @@ pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Resu
         Some(ListCommands::Group(args)) => {
             if args.fs {
                 // find all directories in the root directory
-                let entries = read_dir(cfg.root_dir.clone()).unwrap();
-                entries
-                    .filter_map(|entry| {
-                        let entry = entry.unwrap();
-                        let path = entry.path();
-                        if path.is_dir() {
-                            Some(path)
-                        } else {
-                            None
-                        }
-                    })
-                    .for_each(|path| {
-                        if args.verbose {
-                            println!("{}", path.display())
-                        } else {
-                            // don't output full path
-                            println!("{}", path.file_name().unwrap().to_str().unwrap())
-                        }
-                    });
+                for entry in read_dir(cfg.root_dir.clone())? {
+                    let path = entry?.path();
+                    if !path.is_dir() {
+                        continue;
+                    }
+                    if args.verbose {
+                        println!("{}", path.display())
+                    } else {
+                        // don't output full path
+                        println!("{}", path.file_name().unwrap_or_default().to_string_lossy())
+                    }
+                }
                 return Ok(());
             }
 
             // sql query to find all groups in the database with "resume" table, "group" column
             let records = sqlx::query!("SELECT DISTINCT \"group\" FROM resumes")
                 .fetch_all(pool)
-                .await
-                .unwrap();
+                .await?;
             records.iter().for_each(|r| println!("{}", r.group));
             Ok(())
         }
@@ -165,33 +171,25 @@ pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Resu
                         .unwrap_or(PathBuf::from("templates")),
                 );
                 log::debug!("Template path: {:?}", template_path);
-                let entries = read_dir(template_path).unwrap();
-                entries
-                    .filter_map(|entry| {
-                        let entry = entry.unwrap();
-                        let path = entry.path();
-                        if path.is_file() {
-                            Some(path)
-                        } else {
-                            None
-                        }
-                    })
-                    .for_each(|path| {
-                        if args.verbose {
-                            println!("{}", path.display())
-                        } else {
-                            // don't output full path or the extension
-                            println!("{}", path.file_stem().unwrap().to_str().unwrap())
-                        }
-                    });
+                for entry in read_dir(template_path)? {
+                    let path = entry?.path();
+                    if !path.is_file() {
+                        continue;
+                    }
+                    if args.verbose {
+                        println!("{}", path.display())
+                    } else {
+                        // don't output full path or the extension
+                        println!("{}", path.file_stem().unwrap_or_default().to_string_lossy())
+                    }
+                }
                 return Ok(());
             }
 
             // sql query to find all templates in the database with "resume" table, "template" column
             let records = sqlx::query!("SELECT DISTINCT template FROM resumes")
                 .fetch_all(pool)
-                .await
-                .unwrap();
+                .await?;
             records.iter().for_each(|r| println!("{}", r.template));
             Ok(())
         }
@@ -221,7 +219,7 @@ pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Resu
                             println!("{}", path.display())
                         } else {
                             // don't output full path or the extension
-                            println!("{}", path.file_stem().unwrap().to_str().unwrap())
+                            println!("{}", path.file_stem().unwrap_or_default().to_string_lossy())
                         }
                     });
                 return Ok(());
@@ -232,8 +230,7 @@ pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Resu
             // unfortunately this will always be verbose (full path)
             let records = sqlx::query!("SELECT file_path FROM resumes")
                 .fetch_all(pool)
-                .await
-                .unwrap();
+                .await?;
             records.iter().for_each(|r| println!("{}", r.file_path));
             Ok(())
         }
@@ -246,7 +243,7 @@ pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Resu
                 fields.push("id".to_string());
             }
             if args.group {
-                fields.push("\"group\"".to_string());
+                fields.push("group".to_string());
             }
             if args.template {
                 fields.push("template".to_string());
@@ -260,6 +257,9 @@ pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Resu
             if args.file_path {
                 fields.push("file_path".to_string());
             }
+            if args.cover_letter_path {
+                fields.push("cover_letter_path".to_string());
+            }
             if args.created_at {
                 fields.push("created_at".to_string());
             }
@@ -287,24 +287,45 @@ pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Resu
             if args.notes {
                 fields.push("notes".to_string());
             }
+            if args.tags {
+                fields.push("tags".to_string());
+            }
 
+            // urls live in their own table, show them as space separated kind=url pairs
             let records = sqlx::query!(
-                "SELECT * FROM resumes JOIN metadata ON resumes.id = metadata.resume_id"
+                r#"
+SELECT resumes.*, metadata.*,
+    (SELECT group_concat(kind || '=' || url, ' ') FROM resume_urls WHERE resume_urls.resume_id = resumes.id) AS urls,
+    (SELECT group_concat(name, ',') FROM (
+        SELECT tags.name FROM tags JOIN resume_tags ON resume_tags.tag_id = tags.id
+        WHERE resume_tags.resume_id = resumes.id ORDER BY tags.name
+    )) AS tags
+FROM resumes JOIN metadata ON resumes.id = metadata.resume_id
+                "#
             )
             .fetch_all(pool)
-            .await
-            .unwrap();
+            .await?;
 
             // display a row of fields first separated by tabs
             // then display each record separated by tabs
             if args.verbose {
-                println!("id\tgroup\ttemplate\tcompany\thas_cover_letter\tfile_path\tcreated_at\tapplied_time\tcopied_from\tmetadata_file_path\tlength\tlocation\tstatus\turls\tnotes");
+                println!("id\tgroup\ttemplate\tcompany\thas_cover_letter\tfile_path\tcreated_at\tapplied_time\tcopied_from\tmetadata_file_path\tlength\tlocation\tstatus\turls\tnotes\ttags\tcover_letter_path");
             } else {
                 let header = fields.join("\t");
                 println!("{}", header);
             }
 
             for record in records {
+                let record_tags = record.tags.clone().unwrap_or_default();
+                let record_tags: Vec<&str> = record_tags.split(',').collect();
+                if !args
+                    .filter_tags
+                    .iter()
+                    .all(|t| record_tags.contains(&t.as_str()))
+                {
+                    continue;
+                }
+
                 let mut row = Vec::new();
                 if args.verbose {
                     row.push(record.id.to_string());
@@ -328,6 +349,13 @@ pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Resu
                     row.push(record.status.clone());
                     row.push(record.urls.clone().unwrap_or("null".to_string()));
                     row.push(record.notes.clone());
+                    row.push(record.tags.clone().unwrap_or("null".to_string()));
+                    row.push(
+                        record
+                            .cover_letter_path
+                            .clone()
+                            .unwrap_or("null".to_string()),
+                    );
                     println!("{}", row.join("\t"));
                     continue;
                 }
@@ -349,6 +377,14 @@ pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Resu
                 if args.file_path {
                     row.push(record.file_path.clone());
                 }
+                if args.cover_letter_path {
+                    row.push(
+                        record
+                            .cover_letter_path
+                            .clone()
+                            .unwrap_or("null".to_string()),
+                    );
+                }
                 if args.created_at {
                     row.push(record.created_at.to_string());
                 }
@@ -381,6 +417,9 @@ pub async fn execute(cfg: Config, args: &ListCommand, pool: &SqlitePool) -> Resu
                 if args.notes {
                     row.push(record.notes.clone());
                 }
+                if args.tags {
+                    row.push(record.tags.clone().unwrap_or("null".to_string()));
+                }
                 println!("{}", row.join("\t"));
             }
 
@@ -0,0 +1,157 @@
+use std::{cmp::Ordering, path::Path};
+
+use anyhow::Result;
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{config::Config, metadata, Error};
+
+#[derive(Parser)]
+#[command(
+    name = "metadata",
+    about = "Check the metadata files and upgrade old ones",
+    arg_required_else_help = true
+)]
+pub struct MetadataCommand {
+    #[command(subcommand)]
+    pub command: Option<MetadataCommands>,
+}
+
+#[derive(Parser)]
+pub enum MetadataCommands {
+    Validate(ValidateCommand),
+    Migrate(MigrateCommand),
+    Schema(SchemaCommand),
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "validate",
+    about = "Check the metadata files against the schema, every resume without ids",
+    long_about = "Check the metadata files against the schema, every resume without ids. Prints ok, outdated or error with the id and the file, and fails if any file has an error"
+)]
+pub struct ValidateCommand {
+    #[arg(index = 1, help = "IDs of the resumes to check")]
+    pub ids: Vec<i64>,
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "migrate",
+    about = "Upgrade old metadata files to the current schema in place, every resume without ids"
+)]
+pub struct MigrateCommand {
+    #[arg(index = 1, help = "IDs of the resumes to upgrade")]
+    pub ids: Vec<i64>,
+
+    #[arg(short = 'n', long, help = "Only print what would be upgraded")]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "schema", about = "Print the JSON Schema of the metadata files")]
+pub struct SchemaCommand;
+
+/// The id and metadata file of the resumes with `ids`, or of every resume
+async fn files(pool: &SqlitePool, ids: &[i64]) -> Result<Vec<(i64, String)>> {
+    let files =
+        sqlx::query!("SELECT resume_id, metadata_file_path FROM metadata ORDER BY resume_id")
+            .fetch_all(pool)
+            .await?;
+    for id in ids {
+        if !files.iter().any(|f| f.resume_id == *id) {
+            return Err(Error::ResumeNotFound(*id).into());
+        }
+    }
+    Ok(files
+        .into_iter()
+        .filter(|f| ids.is_empty() || ids.contains(&f.resume_id))
+        .map(|f| (f.resume_id, f.metadata_file_path))
+        .collect())
+}
+
+pub async fn execute(_cfg: Config, args: &MetadataCommand, pool: &SqlitePool) -> Result<()> {
+    match &args.command {
+        Some(MetadataCommands::Validate(args)) => {
+            let mut errors = 0;
+            for (id, path) in files(pool, &args.ids).await? {
+                let value = match metadata::read_value(Path::new(&path)) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        errors += 1;
+                        println!("error\t{}\t{}\t{}", id, path, e);
+                        continue;
+                    }
+                };
+                let problems = metadata::problems(&value);
+                for problem in &problems {
+                    println!("error\t{}\t{}\t{}", id, path, problem);
+                }
+                errors += problems.len();
+
+                if !problems.is_empty() {
+                    continue;
+                }
+                let version = metadata::version(&value);
+                match version.cmp(&metadata::SCHEMA_VERSION) {
+                    Ordering::Less => println!(
+                        "outdated\t{}\t{}\tschema_version {}, upgrade it with `resuman metadata migrate`",
+                        id, path, version
+                    ),
+                    Ordering::Greater => println!(
+                        "ok\t{}\t{}\tschema_version {} is from a newer resuman",
+                        id, path, version
+                    ),
+                    Ordering::Equal => println!("ok\t{}\t{}", id, path),
+                }
+            }
+            if errors > 0 {
+                return Err(
+                    Error::Invalid(format!("The metadata files have {} error(s)", errors)).into(),
+                );
+            }
+            Ok(())
+        }
+        Some(MetadataCommands::Migrate(args)) => {
+            let mut failed = 0;
+            for (id, path) in files(pool, &args.ids).await? {
+                let file = Path::new(&path);
+                // checked as it is, an upgrade must never hide a broken file
+                let upgraded = metadata::read_value(file).and_then(|mut value| {
+                    let problems = metadata::problems(&value);
+                    if !problems.is_empty() {
+                        return Err(Error::InvalidMetadata(file.to_path_buf(), problems));
+                    }
+                    if !metadata::upgrade(&mut value) {
+                        return Ok(false);
+                    }
+                    if !args.dry_run {
+                        std::fs::write(file, serde_json::to_string_pretty(&value)?)?;
+                    }
+                    Ok(true)
+                });
+                match upgraded {
+                    Ok(true) => println!("migrated\t{}\t{}", id, path),
+                    Ok(false) => {}
+                    Err(e) => {
+                        failed += 1;
+                        eprintln!("Could not migrate {}: {}", id, e);
+                    }
+                }
+            }
+            if failed > 0 {
+                return Err(Error::Invalid(format!(
+                    "{} metadata file(s) could not be migrated, see `resuman metadata validate`",
+                    failed
+                ))
+                .into());
+            }
+            Ok(())
+        }
+        Some(MetadataCommands::Schema(_)) => {
+            print!("{}", metadata::SCHEMA);
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
@@ -2,11 +2,35 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 
+pub mod backup;
+pub mod build;
+pub mod company;
+pub mod config;
 pub mod create;
+pub mod db;
 pub mod delete;
+pub mod diff;
+pub mod dupes;
+pub mod edit;
 pub mod find;
+pub mod history;
+pub mod init;
+pub mod lineage;
 pub mod list;
+pub mod metadata;
+pub mod offer;
+pub mod open;
+pub mod profile;
+pub mod restore;
+pub mod serve;
+pub mod stats;
+pub mod submit;
+pub mod tag;
+pub mod tui;
 pub mod update;
+pub mod url;
+pub mod watch;
+pub mod wizard;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const NAME: &str = env!("CARGO_PKG_NAME");
@@ -24,6 +48,27 @@ pub struct Cli {
     )]
     pub config: Option<PathBuf>,
 
+    #[arg(
+        long,
+        env = "RESUMAN_PROFILE",
+        help = "Profile of the config to use, [main] without it or default_profile"
+    )]
+    pub profile: Option<String>,
+
+    #[arg(
+        long = "set",
+        value_name = "KEY=VALUE",
+        help = "Override a config value, wins over the file and RESUMAN_* env vars, can be repeated"
+    )]
+    pub set: Vec<String>,
+
+    #[arg(
+        long,
+        env = "RESUMAN_NO_AUTO_MIGRATE",
+        help = "Use the db as it is instead of applying pending migrations first, see `resuman db`"
+    )]
+    pub no_auto_migrate: bool,
+
     #[arg(long, hide = true)]
     pub markdown_help: bool,
 
@@ -31,10 +76,6 @@ pub struct Cli {
     pub command: Option<Commands>,
 }
 
-#[derive(Parser)]
-#[command(name = "init", about = "Initialize resuman")]
-pub struct InitCommand;
-
 // if the user creates a resume without deleting it properly,
 // then the resume will be orphaned in the db...
 // but if the user has created another resume with the same file path
@@ -49,9 +90,47 @@ pub enum Commands {
     /// A subcommand for creating something
     Create(create::CreateCommand),
     List(list::ListCommand),
-    Init(InitCommand),
+    Init(init::InitCommand),
     Update(update::UpdateCommand),
     Delete(delete::DeleteCommand),
     Find(find::FindCommand),
     Clean(CleanCommand),
+    Url(url::UrlCommand),
+    Open(open::OpenCommand),
+    Tag(tag::TagCommand),
+    Offer(offer::OfferCommand),
+    Tui(tui::TuiCommand),
+    Serve(serve::ServeCommand),
+    Config(config::ConfigCommand),
+    Profile(profile::ProfileCommand),
+    Stats(stats::StatsCommand),
+    Backup(backup::BackupCommand),
+    Restore(restore::RestoreCommand),
+    Db(db::DbCommand),
+    Metadata(metadata::MetadataCommand),
+    Edit(edit::EditCommand),
+    Watch(watch::WatchCommand),
+    Log(history::LogCommand),
+    Checkout(history::CheckoutCommand),
+    Build(build::BuildCommand),
+    Submit(submit::SubmitCommand),
+    Diff(diff::DiffCommand),
+    Lineage(lineage::LineageCommand),
+    Dupes(dupes::DupesCommand),
+    Company(company::CompanyCommand),
+}
+
+impl Commands {
+    /// The resumes a command works on by id, their frozen files are checked before it runs
+    pub fn resume_ids(&self) -> Vec<i64> {
+        match self {
+            Commands::Update(args) => vec![args.id.into()],
+            Commands::Open(args) => vec![args.id.into()],
+            Commands::Edit(args) => vec![args.id],
+            Commands::Log(args) => vec![args.id],
+            Commands::Checkout(args) => vec![args.id],
+            Commands::Build(args) => args.ids.clone(),
+            _ => Vec::new(),
+        }
+    }
 }
@@ -0,0 +1,430 @@
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDate, Utc};
+use clap::{Parser, ValueEnum};
+use sqlx::SqlitePool;
+
+use crate::{config::Config, Error};
+
+// fixed-term offers (internships, contracts) get a total prorated over their term, the
+// length of the resume unless the offer says otherwise
+const WEEKS_PER_YEAR: i64 = 52;
+const HOURS_PER_WEEK: f64 = 40.0;
+
+#[derive(Parser)]
+#[command(
+    name = "offer",
+    about = "Track and compare offers",
+    visible_aliases = ["offers"],
+    arg_required_else_help = true
+)]
+pub struct OfferCommand {
+    #[command(subcommand)]
+    pub command: Option<OfferCommands>,
+}
+
+#[derive(Parser)]
+pub enum OfferCommands {
+    Add(AddCommand),
+    Rm(RmCommand),
+    List(ListOfferCommand),
+    Compare(CompareCommand),
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "add", about = "Record an offer for a resume")]
+pub struct AddCommand {
+    #[arg(index = 1, help = "ID of the resume the offer is for")]
+    pub id: i32,
+
+    #[arg(short, long, help = "Base pay per period")]
+    pub base: f64,
+
+    #[arg(long, help = "Pay period of the base pay", value_enum, default_value_t = PayPeriod::Year)]
+    pub per: PayPeriod,
+
+    #[arg(short, long, help = "Currency code, defaults to default_currency")]
+    pub currency: Option<String>,
+
+    #[arg(long, help = "Signing or yearly bonus")]
+    pub bonus: Option<f64>,
+
+    #[arg(short, long, help = "Total equity grant value")]
+    pub equity: Option<f64>,
+
+    #[arg(long, help = "Equity vesting period in years (default 4)")]
+    pub equity_years: Option<u16>,
+
+    #[arg(short, long, help = "Relocation package")]
+    pub relocation: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "WEEKS",
+        help = "Length of the job in weeks if it differs from the length of the resume, its total is prorated over it"
+    )]
+    pub term_weeks: Option<u16>,
+
+    #[arg(
+        long,
+        conflicts_with = "term_weeks",
+        help = "An open-ended job, its total is not prorated over the length of the resume"
+    )]
+    pub full_time: bool,
+
+    #[arg(long, help = "Start date (YYYY-MM-DD)", visible_aliases = ["start"])]
+    pub start_date: Option<NaiveDate>,
+
+    #[arg(short, long, help = "Decision deadline (YYYY-MM-DD)")]
+    pub deadline: Option<NaiveDate>,
+
+    #[arg(short, long, help = "Location of the offer")]
+    pub location: Option<String>,
+
+    #[arg(short, long, help = "Other details")]
+    pub notes: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "rm", about = "Remove an offer by its offer id", visible_aliases = ["remove", "delete"])]
+pub struct RmCommand {
+    #[arg(index = 1, help = "ID of the offer (see `offer list`)")]
+    pub offer_id: i64,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "list", about = "List recorded offers", visible_aliases = ["ls"])]
+pub struct ListOfferCommand {
+    #[arg(index = 1, help = "ID of the resume, lists every offer if omitted")]
+    pub id: Option<i32>,
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "compare",
+    about = "Compare offers side by side",
+    long_about = "Compare offers side by side, normalized to annualized totals. Offers also get a total prorated over their term, the length of their resume or the --term-weeks they were added with, except those added with --full-time."
+)]
+pub struct CompareCommand {
+    #[arg(
+        index = 1,
+        help = "IDs of the offers to compare, compares every offer if omitted"
+    )]
+    pub offer_ids: Vec<i64>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayPeriod {
+    Hour,
+    Week,
+    Month,
+    Year,
+}
+
+impl PayPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayPeriod::Hour => "hour",
+            PayPeriod::Week => "week",
+            PayPeriod::Month => "month",
+            PayPeriod::Year => "year",
+        }
+    }
+
+    /// How many of this period fit in a year, assuming a 40 hour week
+    pub fn per_year(&self) -> f64 {
+        match self {
+            PayPeriod::Hour => HOURS_PER_WEEK * WEEKS_PER_YEAR as f64,
+            PayPeriod::Week => WEEKS_PER_YEAR as f64,
+            PayPeriod::Month => 12.0,
+            PayPeriod::Year => 1.0,
+        }
+    }
+}
+
+impl std::str::FromStr for PayPeriod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        <PayPeriod as ValueEnum>::from_str(s, true)
+            .map_err(|_| anyhow!("Unknown pay period: {}", s))
+    }
+}
+
+/// The weeks an offer is prorated over, `stored` is its own term_weeks, 0 for open-ended
+fn term_weeks(stored: Option<i64>, resume_length: Option<i64>) -> Option<i64> {
+    match stored {
+        Some(0) => None,
+        Some(weeks) => Some(weeks),
+        None => resume_length.filter(|weeks| *weeks > 0),
+    }
+}
+
+/// Offer totals, all in the offer's own currency
+struct Totals {
+    base: f64,
+    equity: f64,
+    annualized: f64,
+    term: Option<f64>, // only for fixed-term offers
+}
+
+fn totals(
+    base: f64,
+    period: PayPeriod,
+    bonus: f64,
+    equity: f64,
+    equity_years: i64,
+    relocation: f64,
+    term_weeks: Option<i64>,
+) -> Totals {
+    let base = base * period.per_year();
+    let equity = equity / equity_years.max(1) as f64;
+    let annualized = base + bonus + equity;
+
+    // the term total is what the job pays over its length, one-time payments included
+    let term = term_weeks.map(|weeks| {
+        let years = weeks as f64 / WEEKS_PER_YEAR as f64;
+        (base + equity) * years + bonus + relocation
+    });
+
+    Totals {
+        base,
+        equity,
+        annualized,
+        term,
+    }
+}
+
+/// Prints a reminder to stderr for every offer whose decision deadline is within `days` days
+pub async fn remind_deadlines(pool: &SqlitePool, days: u16) -> Result<()> {
+    let today = Utc::now().date_naive();
+    let until = today + chrono::Days::new(days.into());
+    let offers = sqlx::query!(
+        r#"
+SELECT offers.id, offers.deadline AS "deadline!: NaiveDate", resumes.company, resumes.position
+FROM offers JOIN resumes ON resumes.id = offers.resume_id
+WHERE offers.deadline IS NOT NULL AND offers.deadline >= ?1 AND offers.deadline <= ?2
+ORDER BY offers.deadline
+        "#,
+        today,
+        until,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for o in offers {
+        let left = (o.deadline - today).num_days();
+        eprintln!(
+            "Reminder: offer {} from {} ({}) must be decided by {} ({} day{} left)",
+            o.id,
+            o.company,
+            o.position,
+            o.deadline,
+            left,
+            if left == 1 { "" } else { "s" }
+        );
+    }
+    Ok(())
+}
+
+pub async fn execute(cfg: Config, args: &OfferCommand, pool: &SqlitePool) -> Result<()> {
+    match &args.command {
+        Some(OfferCommands::Add(args)) => {
+            log::debug!("Adding offer with arguments:\n{:#?}", args);
+
+            let exists = sqlx::query!("SELECT id FROM resumes WHERE id = ?", args.id)
+                .fetch_optional(pool)
+                .await?;
+            if exists.is_none() {
+                return Err(Error::ResumeNotFound(args.id.into()).into());
+            }
+
+            let period = args.per.as_str();
+            let currency = args
+                .currency
+                .clone()
+                .unwrap_or(cfg.default_currency.unwrap_or("USD".to_string()))
+                .to_uppercase();
+            let bonus = args.bonus.unwrap_or(0.0);
+            let equity = args.equity.unwrap_or(0.0);
+            let equity_years = args.equity_years.unwrap_or(4);
+            let relocation = args.relocation.unwrap_or(0.0);
+            let notes = args.notes.clone().unwrap_or("".to_string());
+            let term_weeks = if args.full_time {
+                Some(0)
+            } else {
+                args.term_weeks.map(i64::from)
+            };
+
+            let offer_id = sqlx::query!(
+                r#"
+INSERT INTO offers (resume_id, base, period, currency, bonus, equity, equity_years, relocation, term_weeks, start_date, deadline, location, notes)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+RETURNING id
+                "#,
+                args.id,
+                args.base,
+                period,
+                currency,
+                bonus,
+                equity,
+                equity_years,
+                relocation,
+                term_weeks,
+                args.start_date,
+                args.deadline,
+                args.location,
+                notes,
+            )
+            .fetch_one(pool)
+            .await?
+            .id;
+
+            println!("{}", offer_id);
+            Ok(())
+        }
+        Some(OfferCommands::Rm(args)) => {
+            let removed = sqlx::query!("DELETE FROM offers WHERE id = ?", args.offer_id)
+                .execute(pool)
+                .await?;
+            if removed.rows_affected() == 0 {
+                return Err(
+                    Error::Invalid(format!("No offer found with id {}", args.offer_id)).into(),
+                );
+            }
+            Ok(())
+        }
+        Some(OfferCommands::List(args)) => {
+            let records = sqlx::query!(
+                r#"
+SELECT offers.*, resumes.company, metadata.length AS "resume_length?: i64" FROM offers
+JOIN resumes ON resumes.id = offers.resume_id
+LEFT JOIN metadata ON metadata.resume_id = offers.resume_id
+WHERE ?1 IS NULL OR offers.resume_id = ?1
+ORDER BY offers.id
+                "#,
+                args.id
+            )
+            .fetch_all(pool)
+            .await?;
+
+            println!("id\tresume_id\tcompany\tbase\tperiod\tcurrency\tbonus\tequity\tequity_years\trelocation\tterm_weeks\tstart_date\tdeadline\tlocation\tnotes");
+            for r in records {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                    r.id,
+                    r.resume_id,
+                    r.company,
+                    r.base,
+                    r.period,
+                    r.currency,
+                    r.bonus,
+                    r.equity,
+                    r.equity_years,
+                    r.relocation,
+                    term_weeks(r.term_weeks, r.resume_length)
+                        .map(|w| w.to_string())
+                        .unwrap_or("null".to_string()),
+                    r.start_date
+                        .map(|d| d.to_string())
+                        .unwrap_or("null".to_string()),
+                    r.deadline
+                        .map(|d| d.to_string())
+                        .unwrap_or("null".to_string()),
+                    r.location.unwrap_or("null".to_string()),
+                    r.notes
+                );
+            }
+            Ok(())
+        }
+        Some(OfferCommands::Compare(args)) => {
+            let records = sqlx::query!(
+                r#"
+SELECT offers.*, resumes.company, resumes.position, metadata.length AS "resume_length?: i64"
+FROM offers
+JOIN resumes ON resumes.id = offers.resume_id
+LEFT JOIN metadata ON metadata.resume_id = offers.resume_id
+ORDER BY offers.id
+                "#
+            )
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .filter(|r| args.offer_ids.is_empty() || args.offer_ids.contains(&r.id))
+            .collect::<Vec<_>>();
+            if let Some(id) = args
+                .offer_ids
+                .iter()
+                .find(|id| !records.iter().any(|r| r.id == **id))
+            {
+                return Err(Error::Invalid(format!("No offer found with id {}", id)).into());
+            }
+
+            if records.is_empty() {
+                return Err(anyhow!("No offers to compare"));
+            }
+
+            // one column per offer, one row per field
+            let mut rows: Vec<(&str, Vec<String>)> = vec![
+                ("offer", vec![]),
+                ("company", vec![]),
+                ("position", vec![]),
+                ("location", vec![]),
+                ("currency", vec![]),
+                ("base/year", vec![]),
+                ("bonus", vec![]),
+                ("equity/year", vec![]),
+                ("relocation", vec![]),
+                ("annualized", vec![]),
+                ("term (weeks)", vec![]),
+                ("term total", vec![]),
+                ("start_date", vec![]),
+                ("deadline", vec![]),
+            ];
+
+            for r in &records {
+                let period = r.period.parse::<PayPeriod>()?;
+                let weeks = term_weeks(r.term_weeks, r.resume_length);
+                let t = totals(
+                    r.base,
+                    period,
+                    r.bonus,
+                    r.equity,
+                    r.equity_years,
+                    r.relocation,
+                    weeks,
+                );
+                let values = [
+                    r.id.to_string(),
+                    r.company.clone(),
+                    r.position.clone(),
+                    r.location.clone().unwrap_or("null".to_string()),
+                    r.currency.clone(),
+                    format!("{:.0}", t.base),
+                    format!("{:.0}", r.bonus),
+                    format!("{:.0}", t.equity),
+                    format!("{:.0}", r.relocation),
+                    format!("{:.0}", t.annualized),
+                    weeks.map(|w| w.to_string()).unwrap_or("null".to_string()),
+                    t.term
+                        .map(|t| format!("{:.0}", t))
+                        .unwrap_or("null".to_string()),
+                    r.start_date
+                        .map(|d| d.to_string())
+                        .unwrap_or("null".to_string()),
+                    r.deadline
+                        .map(|d| d.to_string())
+                        .unwrap_or("null".to_string()),
+                ];
+                for (row, value) in rows.iter_mut().zip(values) {
+                    row.1.push(value);
+                }
+            }
+
+            for (field, values) in rows {
+                println!("{}\t{}", field, values.join("\t"));
+            }
+            Ok(())
+        }
+        None => Err(anyhow!("No subcommand provided")),
+    }
+}
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{config::Config, url::UrlKind, utils, Error};
+
+#[derive(Parser, Debug)]
+#[command(name = "open", about = "Open a resume folder or one of its URLs")]
+pub struct OpenCommand {
+    #[arg(index = 1, help = "ID of the resume to open")]
+    pub id: i32,
+
+    #[arg(
+        short,
+        long,
+        help = "Open the first URL of this kind instead of the folder",
+        value_enum
+    )]
+    pub url: Option<UrlKind>,
+}
+
+pub async fn execute(_cfg: Config, args: &OpenCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Opening with arguments:\n{:#?}", args);
+
+    let file_path = sqlx::query!("SELECT file_path FROM resumes WHERE id = ?", args.id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::ResumeNotFound(args.id.into()))?
+        .file_path;
+
+    let target = match args.url {
+        Some(kind) => {
+            let kind = kind.as_str();
+            sqlx::query!(
+                "SELECT url FROM resume_urls WHERE resume_id = ? AND kind = ? ORDER BY id LIMIT 1",
+                args.id,
+                kind
+            )
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| anyhow!("Resume {} has no {} URL", args.id, kind))?
+            .url
+        }
+        None => {
+            // file_path points at the .tex, open the folder around it
+            let path = PathBuf::from(&file_path);
+            path.parent().unwrap_or(&path).display().to_string()
+        }
+    };
+
+    utils::open_with_system(&target)?;
+
+    println!("{}", target);
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use clap::Parser;
+
+use crate::{commands::config::edit_config, config::MAIN_PROFILE, utils, Error};
+
+#[derive(Parser)]
+#[command(
+    name = "profile",
+    about = "List the profiles of the config and pick the default one",
+    visible_aliases = ["profiles"],
+    arg_required_else_help = true
+)]
+pub struct ProfileCommand {
+    #[command(subcommand)]
+    pub command: Option<ProfileCommands>,
+}
+
+#[derive(Parser)]
+pub enum ProfileCommands {
+    List(ListProfileCommand),
+    Use(UseCommand),
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "list",
+    about = "List the profiles and their root directories, * marks the active one"
+)]
+pub struct ListProfileCommand;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "use",
+    about = "Use a profile when neither --profile nor RESUMAN_PROFILE is given"
+)]
+pub struct UseCommand {
+    #[arg(index = 1, help = "Name of the profile, main for the [main] config")]
+    pub name: String,
+}
+
+// like config, this only needs the file
+pub fn execute(
+    config_path: Option<PathBuf>,
+    profile: Option<&str>,
+    args: &ProfileCommand,
+) -> Result<()> {
+    let location = utils::locate_config(config_path)?;
+    let data = utils::parse_config(&location.path)?;
+
+    match &args.command {
+        Some(ProfileCommands::List(_)) => {
+            let active = data.active_profile(profile);
+            for name in data.profile_names() {
+                let marker = if name == active { "*" } else { " " };
+                let root_dir = match data.profile(Some(&name)) {
+                    Ok(cfg) => cfg.root_dir.display().to_string(),
+                    Err(e) => format!("invalid: {}", e),
+                };
+                println!("{} {}\t{}", marker, name, root_dir);
+            }
+            Ok(())
+        }
+        Some(ProfileCommands::Use(args)) => {
+            if !data.profile_names().contains(&args.name) {
+                return Err(Error::Invalid(format!(
+                    "Unknown profile {}, the profiles are {}",
+                    args.name,
+                    data.profile_names().join(", ")
+                ))
+                .into());
+            }
+            // main is what is used without a default, so it does not need one
+            let value = (args.name != MAIN_PROFILE).then(|| args.name.as_str().into());
+            let contents = edit_config(&location.path, &[], "default_profile", value)?;
+            fs::write(&location.path, contents)?;
+
+            if let Some(profile) = profile.filter(|p| *p != args.name) {
+                eprintln!(
+                    "Profile {} is still used while --profile or RESUMAN_PROFILE says so",
+                    profile
+                );
+            }
+            println!("{}", args.name);
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+
+use crate::{backup, config::Config, Resuman};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "restore",
+    about = "Replace the db with a backup, after backing up the current one",
+    long_about = "Replace the db with a backup, after backing up the current one. The backup is checked first, one from a newer resuman is refused and one from an older resuman is migrated",
+    arg_required_else_help = true
+)]
+pub struct RestoreCommand {
+    #[arg(index = 1, value_name = "FILE", help = "The backup to restore")]
+    pub file: PathBuf,
+}
+
+// runs without an open db, since the one there may be the reason for restoring
+pub async fn execute(cfg: Config, args: &RestoreCommand, migrate: bool) -> Result<()> {
+    log::debug!("Restore with arguments:\n{:#?}", args);
+
+    let (version, previous) = backup::restore(&cfg, &args.file).await?;
+    if let Some(previous) = previous {
+        println!("backup\t{}", previous.display());
+    }
+    println!("restored\t{}\t{}", args.file.display(), version);
+
+    // migrates a backup from an older resuman right away
+    if migrate {
+        Resuman::open(cfg).await?.pool().close().await;
+    }
+    Ok(())
+}
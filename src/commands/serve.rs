@@ -0,0 +1,217 @@
+// local http api and dashboard, every route goes through the same code as the matching command
+
+use std::net::IpAddr;
+
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use clap::Parser;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::SqlitePool;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    commands::{find::FindCommand, stats},
+    config::Config,
+    error::Error,
+    manager::Resuman,
+    resume::{Filter, NewResume, Patch, Resume},
+};
+
+const DASHBOARD: &str = include_str!("../../assets/dashboard.html");
+
+#[derive(Parser, Debug)]
+#[command(name = "serve", about = "Serve a local JSON API and web dashboard", visible_aliases = ["server"])]
+pub struct ServeCommand {
+    #[arg(short, long, help = "Port to listen on", default_value_t = 7878)]
+    pub port: u16,
+
+    #[arg(
+        long,
+        help = "Address to bind, only reachable from this machine by default",
+        default_value = "127.0.0.1"
+    )]
+    pub host: IpAddr,
+
+    #[arg(
+        long,
+        env = "RESUMAN_TOKEN",
+        hide_env_values = true,
+        help = "Require this bearer token on every /api request"
+    )]
+    pub token: Option<String>,
+}
+
+#[derive(Clone)]
+struct AppState {
+    resuman: Resuman,
+    token: Option<String>,
+}
+
+/// Error body of every failed request, `{"error": "..."}`
+struct ApiError(StatusCode, String);
+
+impl From<Error> for ApiError {
+    fn from(e: Error) -> Self {
+        let status = match e {
+            Error::ResumeNotFound(_) => StatusCode::NOT_FOUND,
+            Error::DuplicateResume(_) | Error::LikelyDuplicate(_) => StatusCode::CONFLICT,
+            // a metadata file on disk that was edited into something resuman cannot read
+            Error::MetadataParse(_) | Error::InvalidMetadata(..) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            Error::Invalid(_)
+            | Error::InvalidPath(..)
+            | Error::TemplateNotFound(_)
+            | Error::Other(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(status, e.to_string())
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+#[derive(Deserialize, Debug)]
+struct DeleteParams {
+    #[serde(default)]
+    files: bool,
+}
+
+async fn require_token(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    if let Some(token) = &state.token {
+        let given = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        // compared in constant time, so response times do not give the token away
+        let valid = given.is_some_and(|g| bool::from(g.as_bytes().ct_eq(token.as_bytes())));
+        if !valid {
+            return ApiError(
+                StatusCode::UNAUTHORIZED,
+                "Missing or invalid bearer token".to_string(),
+            )
+            .into_response();
+        }
+    }
+    next.run(req).await
+}
+
+async fn dashboard() -> Html<&'static str> {
+    Html(DASHBOARD)
+}
+
+async fn list_resumes(State(state): State<AppState>) -> ApiResult<Json<Vec<Resume>>> {
+    Ok(Json(state.resuman.list().await?))
+}
+
+async fn get_resume(State(state): State<AppState>, Path(id): Path<i64>) -> ApiResult<Json<Resume>> {
+    Ok(Json(state.resuman.get(id).await?))
+}
+
+/// The body is a [`NewResume`], urls as "[kind=]url" strings like --url
+async fn create_resume(
+    State(state): State<AppState>,
+    Json(body): Json<NewResume>,
+) -> ApiResult<(StatusCode, Json<Resume>)> {
+    let resume = state.resuman.create(body).await?;
+    Ok((StatusCode::CREATED, Json(resume)))
+}
+
+/// The body is a [`Patch`], tags as "+tag" or "-tag" strings like --tag
+async fn update_resume(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(body): Json<Patch>,
+) -> ApiResult<Json<Resume>> {
+    Ok(Json(state.resuman.update(id, body).await?))
+}
+
+async fn delete_resume(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(params): Query<DeleteParams>,
+) -> ApiResult<StatusCode> {
+    state.resuman.delete(id, params.files).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Takes the same filters as `find`, e.g. `?company=acme&tag=remote&tag=rust&letter=true`
+async fn find_resumes(
+    State(state): State<AppState>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> ApiResult<Json<Vec<i64>>> {
+    // parse the filters like the command line would, so both validate the same way
+    let args = std::iter::once("find".to_string()).chain(
+        params
+            .into_iter()
+            .flat_map(|(k, v)| [format!("--{}", k.replace('_', "-")), v]),
+    );
+    let args = FindCommand::try_parse_from(args)
+        .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.render().to_string()))?;
+    Ok(Json(state.resuman.find_ids(&Filter::from(&args)).await?))
+}
+
+async fn get_stats(State(state): State<AppState>) -> ApiResult<Json<stats::Stats>> {
+    Ok(Json(stats::collect(state.resuman.pool()).await?))
+}
+
+/// The whole app, without binding anything, so it can be driven by an in-process client
+pub fn router(resuman: Resuman, token: Option<String>) -> Router {
+    let state = AppState { resuman, token };
+
+    let api = Router::new()
+        .route("/resumes", get(list_resumes).post(create_resume))
+        .route(
+            "/resumes/{id}",
+            get(get_resume).patch(update_resume).delete(delete_resume),
+        )
+        .route("/find", get(find_resumes))
+        .route("/stats", get(get_stats))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token));
+
+    Router::new()
+        .route("/", get(dashboard))
+        .nest("/api", api)
+        .with_state(state)
+}
+
+pub async fn execute(cfg: Config, args: &ServeCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Serving with arguments:\n{:#?}", args);
+
+    if !args.host.is_loopback() && args.token.is_none() {
+        eprintln!(
+            "Warning: binding to {} without --token, anyone who can reach it can edit your resumes",
+            args.host
+        );
+    }
+
+    let listener = tokio::net::TcpListener::bind((args.host, args.port)).await?;
+    println!("Serving on http://{}", listener.local_addr()?);
+    axum::serve(
+        listener,
+        router(Resuman::from_pool(cfg, pool.clone()), args.token.clone()),
+    )
+    .await?;
+    Ok(())
+}
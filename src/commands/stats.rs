@@ -0,0 +1,258 @@
+use anyhow::Result;
+use clap::Parser;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::{
+    config::{Config, Data},
+    resume::KNOWN_STATUSES,
+    Resuman,
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "stats",
+    about = "Show counts of applications by status, group, company, region and month"
+)]
+pub struct StatsCommand {
+    #[arg(long, help = "Print the stats as JSON")]
+    pub json: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Add up the stats of every profile that has a db",
+        visible_aliases = ["all"]
+    )]
+    pub all_profiles: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Count {
+    pub key: String,
+    pub count: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Stats {
+    pub total: i64,
+    pub applied: i64,
+    pub by_status: Vec<Count>,
+    pub by_group: Vec<Count>,
+    pub by_company: Vec<Count>, // canonical names, most applied to first
+    pub by_region: Vec<Count>,  // e.g. "ON, CA", or the country alone, most applied to first
+    pub by_month: Vec<Count>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub by_profile: Vec<Count>, // only with --all-profiles
+}
+
+/// Adds the counts of `other` to `counts`, keys only in `other` go at the end
+fn add_counts(counts: &mut Vec<Count>, other: Vec<Count>) {
+    for c in other {
+        match counts.iter_mut().find(|existing| existing.key == c.key) {
+            Some(existing) => existing.count += c.count,
+            None => counts.push(c),
+        }
+    }
+}
+
+impl Stats {
+    fn add(&mut self, other: Stats) {
+        self.total += other.total;
+        self.applied += other.applied;
+        add_counts(&mut self.by_status, other.by_status);
+        add_counts(&mut self.by_group, other.by_group);
+        add_counts(&mut self.by_company, other.by_company);
+        add_counts(&mut self.by_region, other.by_region);
+        add_counts(&mut self.by_month, other.by_month);
+        sort_statuses(&mut self.by_status);
+        self.by_group.sort_by(|a, b| a.key.cmp(&b.key));
+        for counts in [&mut self.by_company, &mut self.by_region] {
+            counts.sort_by(|a, b| b.count.cmp(&a.count).then(a.key.cmp(&b.key)));
+        }
+        self.by_month.sort_by(|a, b| a.key.cmp(&b.key));
+    }
+}
+
+// lifecycle order first, anything custom after
+fn sort_statuses(counts: &mut [Count]) {
+    counts.sort_by_key(|c| {
+        (
+            KNOWN_STATUSES
+                .iter()
+                .position(|s| *s == c.key)
+                .unwrap_or(KNOWN_STATUSES.len()),
+            c.key.clone(),
+        )
+    });
+}
+
+/// The stats of every profile with a db added up, profiles without one are skipped
+pub async fn collect_all(data: &Data) -> Result<Stats> {
+    let mut stats = Stats {
+        total: 0,
+        applied: 0,
+        by_status: Vec::new(),
+        by_group: Vec::new(),
+        by_company: Vec::new(),
+        by_region: Vec::new(),
+        by_month: Vec::new(),
+        by_profile: Vec::new(),
+    };
+    for name in data.profile_names() {
+        let cfg = data.profile(Some(&name))?;
+        // opening creates a db, which stats should not do
+        let db_path = cfg
+            .root_dir
+            .join(cfg.with_defaults().db_path.unwrap_or_default());
+        if !db_path.exists() {
+            log::debug!("Skipping profile {}, {:?} does not exist", name, db_path);
+            continue;
+        }
+        let resuman = Resuman::open(cfg).await?;
+        let profile = collect(resuman.pool()).await?;
+        stats.by_profile.push(Count {
+            key: name,
+            count: profile.total,
+        });
+        stats.add(profile);
+    }
+    Ok(stats)
+}
+
+pub async fn collect(pool: &SqlitePool) -> Result<Stats> {
+    let totals = sqlx::query!(
+        r#"
+SELECT COUNT(*) AS total, COUNT(applied_time) AS applied
+FROM resumes JOIN metadata ON metadata.resume_id = resumes.id
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let mut by_status: Vec<Count> = sqlx::query!(
+        r#"
+SELECT status, COUNT(*) AS "count!: i64" FROM metadata GROUP BY status
+        "#
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| Count {
+        key: r.status,
+        count: r.count,
+    })
+    .collect();
+    sort_statuses(&mut by_status);
+
+    let by_group = sqlx::query!(
+        r#"
+SELECT "group", COUNT(*) AS "count!: i64" FROM resumes GROUP BY "group" ORDER BY "group"
+        "#
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| Count {
+        key: r.group,
+        count: r.count,
+    })
+    .collect();
+
+    let by_company = sqlx::query!(
+        r#"
+SELECT COALESCE(companies.name, resumes.company) AS "company!: String", COUNT(*) AS "count!: i64"
+FROM resumes LEFT JOIN companies ON companies.id = resumes.company_id
+GROUP BY 1 ORDER BY 2 DESC, 1
+        "#
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| Count {
+        key: r.company,
+        count: r.count,
+    })
+    .collect();
+
+    let by_region = sqlx::query!(
+        r#"
+SELECT COALESCE(region || ', ' || country, country, 'unknown') AS "region!: String", COUNT(*) AS "count!: i64"
+FROM metadata GROUP BY 1 ORDER BY 2 DESC, 1
+        "#
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| Count {
+        key: r.region,
+        count: r.count,
+    })
+    .collect();
+
+    let by_month = sqlx::query!(
+        r#"
+SELECT strftime('%Y-%m', created_at) AS "month!: String", COUNT(*) AS "count!: i64"
+FROM resumes GROUP BY 1 ORDER BY 1
+        "#
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| Count {
+        key: r.month,
+        count: r.count,
+    })
+    .collect();
+
+    Ok(Stats {
+        total: totals.total,
+        applied: totals.applied,
+        by_status,
+        by_group,
+        by_company,
+        by_region,
+        by_month,
+        by_profile: Vec::new(),
+    })
+}
+
+pub async fn execute(
+    _cfg: Config,
+    args: &StatsCommand,
+    pool: &SqlitePool,
+    data: &Data,
+) -> Result<()> {
+    log::debug!("Stats with arguments:\n{:#?}", args);
+
+    let stats = if args.all_profiles {
+        collect_all(data).await?
+    } else {
+        collect(pool).await?
+    };
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    println!("total\t{}", stats.total);
+    println!("applied\t{}", stats.applied);
+    for (name, counts) in [
+        ("status", &stats.by_status),
+        ("group", &stats.by_group),
+        ("company", &stats.by_company),
+        ("region", &stats.by_region),
+        ("month", &stats.by_month),
+        ("profile", &stats.by_profile),
+    ] {
+        if counts.is_empty() && name == "profile" {
+            continue;
+        }
+        println!();
+        println!("{}\tcount", name);
+        for c in counts {
+            println!("{}\t{}", c.key, c.count);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+
+use crate::{config::Config, manager::Resuman, resume::Patch, Error, Resume};
+
+// the folder next to the resume that keeps what was sent
+pub const SUBMITTED_DIR: &str = "submitted";
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "submit",
+    about = "Mark a resume as applied and freeze what was sent",
+    long_about = "Mark a resume as applied and freeze what was sent. The .tex, the PDF `resuman build` made from it and the cover letter are copied read-only to a submitted/ folder next to them and their SHA-256 is kept in the db, commands on the resume warn when a frozen file changes and `resuman diff` shows what changed since",
+    arg_required_else_help = true
+)]
+pub struct SubmitCommand {
+    #[arg(index = 1, help = "ID of the resume that was sent")]
+    pub id: i64,
+}
+
+/// A file frozen by submit
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub resume_id: i64,
+    pub kind: String,
+    pub name: String,
+    pub sha256: String,
+    pub submitted_at: DateTime<Utc>,
+}
+
+impl Submission {
+    /// Where the frozen copy is, `name` is relative to the folder of the resume
+    pub fn path(&self, resume: &Resume) -> PathBuf {
+        folder(resume).join(&self.name)
+    }
+
+    /// The file the copy was made from, where it is now
+    pub fn source(&self, resume: &Resume) -> Option<PathBuf> {
+        match self.kind.as_str() {
+            "resume" => Some(PathBuf::from(&resume.file_path)),
+            "pdf" => Some(PathBuf::from(&resume.file_path).with_extension("pdf")),
+            "cover_letter" => resume.cover_letter_path.as_ref().map(PathBuf::from),
+            _ => None,
+        }
+    }
+}
+
+fn folder(resume: &Resume) -> PathBuf {
+    let path = PathBuf::from(&resume.file_path);
+    path.parent().map(Path::to_path_buf).unwrap_or(path)
+}
+
+pub fn sha256(path: &Path) -> Result<String> {
+    Ok(format!("{:x}", Sha256::digest(fs::read(path)?)))
+}
+
+/// The frozen files of a resume, or of every resume without an id
+pub async fn submissions(pool: &SqlitePool, id: Option<i64>) -> Result<Vec<Submission>> {
+    Ok(sqlx::query_as!(
+        Submission,
+        r#"
+SELECT resume_id, kind, name, sha256, submitted_at AS "submitted_at: DateTime<Utc>"
+FROM submissions WHERE ?1 IS NULL OR resume_id = ?1 ORDER BY resume_id, id
+        "#,
+        id
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Prints a warning to stderr for every frozen file of the resumes that was changed
+/// or removed
+pub async fn warn_modified(cfg: &Config, pool: &SqlitePool, ids: &[i64]) -> Result<()> {
+    let resuman = Resuman::from_pool(cfg.clone(), pool.clone());
+    for &id in ids {
+        let submissions = submissions(pool, Some(id)).await?;
+        if submissions.is_empty() {
+            continue;
+        }
+        let resume = resuman.get(id).await?;
+        for s in submissions {
+            let path = s.path(&resume);
+            let problem = match sha256(&path) {
+                Ok(sum) if sum == s.sha256 => continue,
+                Ok(_) => "was modified",
+                Err(_) => "is gone",
+            };
+            eprintln!(
+                "Warning: {} {} since resume {} was submitted on {}",
+                path.display(),
+                problem,
+                s.resume_id,
+                s.submitted_at.format("%Y-%m-%d")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Copies the files to `staging`, keeps their sums and moves `staging` to `dir`, the
+/// rows are only committed once the folder is in place
+async fn freeze(
+    pool: &SqlitePool,
+    id: i64,
+    files: &[(&'static str, PathBuf)],
+    staging: &Path,
+    dir: &Path,
+    now: DateTime<Utc>,
+) -> Result<Vec<(&'static str, PathBuf, String)>> {
+    // left behind by a submit that was killed halfway
+    if staging.exists() {
+        fs::remove_dir_all(staging)?;
+    }
+    fs::create_dir(staging)?;
+    let mut tx = pool.begin().await?;
+    let mut frozen = Vec::new();
+    for (kind, source) in files {
+        let file_name = source.file_name().unwrap_or_default();
+        let staged = staging.join(file_name);
+        fs::copy(source, &staged)?;
+        let sum = sha256(&staged)?;
+        let copy = dir.join(file_name);
+        let name = Path::new(SUBMITTED_DIR)
+            .join(file_name)
+            .display()
+            .to_string();
+        sqlx::query!(
+            "INSERT INTO submissions (resume_id, kind, name, sha256, submitted_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            id,
+            kind,
+            name,
+            sum,
+            now,
+        )
+        .execute(&mut *tx)
+        .await?;
+        frozen.push((*kind, copy, sum));
+    }
+    fs::rename(staging, dir)?;
+    tx.commit().await?;
+    Ok(frozen)
+}
+
+pub async fn execute(cfg: Config, args: &SubmitCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Submit with arguments:\n{:#?}", args);
+
+    let resuman = Resuman::from_pool(cfg, pool.clone());
+    let resume = resuman.get(args.id).await?;
+    if let Some(s) = submissions(pool, Some(args.id)).await?.first() {
+        return Err(Error::Invalid(format!(
+            "Resume {} was submitted on {}, see `resuman diff {}` for what changed since",
+            args.id,
+            s.submitted_at.format("%Y-%m-%d"),
+            args.id
+        ))
+        .into());
+    }
+    let dir = folder(&resume).join(SUBMITTED_DIR);
+    if dir.exists() {
+        return Err(Error::InvalidPath(
+            dir,
+            "already exists, move it away to submit again".to_string(),
+        )
+        .into());
+    }
+
+    let tex = PathBuf::from(&resume.file_path);
+    let pdf = tex.with_extension("pdf");
+    let mut files = vec![("resume", tex)];
+    if pdf.exists() {
+        files.push(("pdf", pdf));
+    } else {
+        eprintln!(
+            "No PDF at {}, only the sources are frozen, run `resuman build` before submitting to keep it too",
+            pdf.display()
+        );
+    }
+    if let Some(letter) = resume.cover_letter_path.as_ref().map(PathBuf::from) {
+        if letter.exists() {
+            files.push(("cover_letter", letter));
+        }
+    }
+
+    // the copies go to a staging folder renamed into place once they are all there,
+    // so a failed copy does not leave a half-written submitted/ that blocks the next try
+    let now = Utc::now();
+    let staging = folder(&resume).join(format!(".{}.partial", SUBMITTED_DIR));
+    let frozen = match freeze(pool, args.id, &files, &staging, &dir, now).await {
+        Ok(frozen) => frozen,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            let _ = fs::remove_dir_all(&dir);
+            return Err(e);
+        }
+    };
+    for (_, copy, _) in &frozen {
+        let mut permissions = fs::metadata(copy)?.permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(copy, permissions)?;
+    }
+
+    // after the copies, so the git commit of the update has them too
+    let patch = Patch {
+        status: Some("applied".to_string()),
+        applied_time: Some(now),
+        ..Default::default()
+    };
+    resuman.update(args.id, patch).await?;
+    for (kind, copy, sum) in frozen {
+        println!("{}\t{}\t{}", kind, copy.display(), sum);
+    }
+    Ok(())
+}
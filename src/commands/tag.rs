@@ -0,0 +1,148 @@
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{
+    config::Config,
+    tag::{add_tag, normalize_tag, sync_metadata_tags},
+};
+
+#[derive(Parser)]
+#[command(
+    name = "tag",
+    about = "Manage tags shared between resumes",
+    visible_aliases = ["tags"],
+    arg_required_else_help = true
+)]
+pub struct TagCommand {
+    #[command(subcommand)]
+    pub command: Option<TagCommands>,
+}
+
+#[derive(Parser)]
+pub enum TagCommands {
+    List(ListTagCommand),
+    Rename(RenameCommand),
+    Merge(MergeCommand),
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "list", about = "List all tags and how many resumes use them", visible_aliases = ["ls"])]
+pub struct ListTagCommand;
+
+#[derive(Parser, Debug)]
+#[command(name = "rename", about = "Rename a tag", visible_aliases = ["mv"])]
+pub struct RenameCommand {
+    #[arg(index = 1, help = "Current tag name", value_parser = normalize_tag)]
+    pub from: String,
+
+    #[arg(index = 2, help = "New tag name", value_parser = normalize_tag)]
+    pub to: String,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "merge", about = "Merge tags into another tag")]
+pub struct MergeCommand {
+    #[arg(index = 1, required = true, help = "Tags to merge away", value_parser = normalize_tag)]
+    pub from: Vec<String>,
+
+    #[arg(long, help = "Tag to merge into, created if missing", value_parser = normalize_tag)]
+    pub into: String,
+}
+
+async fn resumes_with_tag(pool: &SqlitePool, name: &str) -> Result<Vec<i64>> {
+    let ids = sqlx::query!(
+        r#"
+SELECT resume_tags.resume_id FROM resume_tags
+JOIN tags ON tags.id = resume_tags.tag_id
+WHERE tags.name = ?
+        "#,
+        name
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| r.resume_id)
+    .collect();
+    Ok(ids)
+}
+
+pub async fn execute(_cfg: Config, args: &TagCommand, pool: &SqlitePool) -> Result<()> {
+    match &args.command {
+        Some(TagCommands::List(_)) => {
+            let records = sqlx::query!(
+                r#"
+SELECT tags.name, COUNT(resume_tags.resume_id) AS "count: i64" FROM tags
+LEFT JOIN resume_tags ON resume_tags.tag_id = tags.id
+GROUP BY tags.id
+ORDER BY tags.name
+                "#
+            )
+            .fetch_all(pool)
+            .await?;
+            records
+                .iter()
+                .for_each(|r| println!("{}\t{}", r.name, r.count));
+            Ok(())
+        }
+        Some(TagCommands::Rename(args)) => {
+            log::debug!("Renaming tag with arguments:\n{:#?}", args);
+
+            let existing = sqlx::query!("SELECT id FROM tags WHERE name = ?", args.to)
+                .fetch_optional(pool)
+                .await?;
+            if existing.is_some() {
+                return Err(anyhow!(
+                    "Tag {} already exists, use `tag merge {} --into {}` instead",
+                    args.to,
+                    args.from,
+                    args.to
+                ));
+            }
+
+            let renamed = sqlx::query!(
+                "UPDATE tags SET name = ?1 WHERE name = ?2",
+                args.to,
+                args.from
+            )
+            .execute(pool)
+            .await?;
+            if renamed.rows_affected() == 0 {
+                return Err(anyhow!("No tag named {}", args.from));
+            }
+
+            for resume_id in resumes_with_tag(pool, &args.to).await? {
+                sync_metadata_tags(pool, resume_id).await?;
+            }
+            Ok(())
+        }
+        Some(TagCommands::Merge(args)) => {
+            log::debug!("Merging tags with arguments:\n{:#?}", args);
+
+            sqlx::query!("INSERT OR IGNORE INTO tags (name) VALUES (?)", args.into)
+                .execute(pool)
+                .await?;
+
+            let mut affected = Vec::new();
+            for from in args.from.iter().filter(|from| **from != args.into) {
+                let resume_ids = resumes_with_tag(pool, from).await?;
+                for resume_id in &resume_ids {
+                    add_tag(pool, *resume_id, &args.into).await?;
+                }
+                // removing the tag also removes its resume_tags rows
+                sqlx::query!("DELETE FROM tags WHERE name = ?", from)
+                    .execute(pool)
+                    .await?;
+                affected.extend(resume_ids);
+            }
+
+            affected.sort();
+            affected.dedup();
+            for resume_id in affected {
+                sync_metadata_tags(pool, resume_id).await?;
+            }
+            Ok(())
+        }
+        None => Err(anyhow!("No subcommand provided")),
+    }
+}
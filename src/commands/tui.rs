@@ -0,0 +1,596 @@
+// full screen browser for applications
+// every edit goes through Resuman::update so the metadata files stay in sync
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use clap::Parser;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Text},
+    widgets::{
+        Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, TableState,
+        Wrap,
+    },
+    DefaultTerminal, Frame,
+};
+use sqlx::SqlitePool;
+
+use crate::{
+    config::Config,
+    manager::Resuman,
+    resume::{Patch, Resume, KNOWN_STATUSES},
+    utils,
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "tui",
+    about = "Browse and edit applications in a full screen interface",
+    visible_aliases = ["ui", "browse"]
+)]
+pub struct TuiCommand {
+    #[arg(short, long, help = "Start in the kanban view")]
+    pub kanban: bool,
+
+    #[arg(short, long, help = "Start with this filter")]
+    pub filter: Option<String>,
+}
+
+/// One application as shown, dates and lists already formatted
+struct Application {
+    id: i64,
+    company: String,
+    position: String,
+    group: String,
+    template: String,
+    status: String,
+    created_at: String,
+    applied_time: Option<String>,
+    length: i64,
+    location: Option<String>,
+    notes: String,
+    file_path: String,
+    tags: String,
+    urls: Vec<String>,
+}
+
+impl Application {
+    fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        [
+            self.company.as_str(),
+            &self.position,
+            &self.group,
+            &self.status,
+            self.location.as_deref().unwrap_or(""),
+            &self.tags,
+            &self.notes,
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(&filter))
+    }
+}
+
+enum View {
+    Table,
+    Kanban,
+}
+
+enum Mode {
+    Normal,
+    Filter,
+    StatusPicker(ListState),
+}
+
+struct App {
+    applications: Vec<Application>,
+    statuses: Vec<String>,
+    filter: String,
+    view: View,
+    mode: Mode,
+    table_state: TableState,
+    // kanban selection, (column, card)
+    column: usize,
+    card: usize,
+    message: String,
+}
+
+impl App {
+    fn visible(&self) -> Vec<&Application> {
+        self.applications
+            .iter()
+            .filter(|a| self.filter.is_empty() || a.matches(&self.filter))
+            .collect()
+    }
+
+    /// Visible applications grouped by status, known statuses first in lifecycle order
+    fn columns(&self) -> Vec<(String, Vec<&Application>)> {
+        let visible = self.visible();
+        self.statuses
+            .iter()
+            .map(|status| {
+                let cards = visible
+                    .iter()
+                    .filter(|a| &a.status == status)
+                    .copied()
+                    .collect();
+                (status.clone(), cards)
+            })
+            .collect()
+    }
+
+    fn selected(&self) -> Option<&Application> {
+        match self.view {
+            View::Table => self
+                .table_state
+                .selected()
+                .and_then(|i| self.visible().get(i).copied()),
+            View::Kanban => self
+                .columns()
+                .get(self.column)
+                .and_then(|(_, cards)| cards.get(self.card).copied()),
+        }
+    }
+
+    fn select_id(&mut self, id: i64) {
+        if let Some(i) = self.visible().iter().position(|a| a.id == id) {
+            self.table_state.select(Some(i));
+        }
+        let position = self
+            .columns()
+            .iter()
+            .enumerate()
+            .find_map(|(c, (_, cards))| {
+                cards.iter().position(|a| a.id == id).map(|card| (c, card))
+            });
+        if let Some((column, card)) = position {
+            self.column = column;
+            self.card = card;
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        match self.view {
+            View::Table => {
+                let len = self.visible().len();
+                if len == 0 {
+                    self.table_state.select(None);
+                    return;
+                }
+                let current = self.table_state.selected().unwrap_or(0) as isize;
+                let next = (current + delta).clamp(0, len as isize - 1);
+                self.table_state.select(Some(next as usize));
+            }
+            View::Kanban => {
+                let len = self
+                    .columns()
+                    .get(self.column)
+                    .map(|(_, cards)| cards.len())
+                    .unwrap_or(0);
+                let next = (self.card as isize + delta).clamp(0, len.max(1) as isize - 1);
+                self.card = next as usize;
+            }
+        }
+    }
+
+    fn move_column(&mut self, delta: isize) {
+        let len = self.statuses.len();
+        if len == 0 {
+            return;
+        }
+        self.column = (self.column as isize + delta).clamp(0, len as isize - 1) as usize;
+        let cards = self.columns()[self.column].1.len();
+        self.card = self.card.min(cards.saturating_sub(1));
+    }
+
+    fn clamp_selection(&mut self) {
+        let len = self.visible().len();
+        match self.table_state.selected() {
+            _ if len == 0 => self.table_state.select(None),
+            Some(i) if i >= len => self.table_state.select(Some(len - 1)),
+            None => self.table_state.select(Some(0)),
+            _ => {}
+        }
+        self.move_column(0);
+    }
+}
+
+impl From<Resume> for Application {
+    fn from(r: Resume) -> Self {
+        Application {
+            id: r.id,
+            company: r.company,
+            position: r.position,
+            group: r.group,
+            template: r.template,
+            status: r.metadata.status,
+            created_at: r.created_at.format("%Y-%m-%d").to_string(),
+            applied_time: r
+                .metadata
+                .applied_time
+                .map(|t| t.format("%Y-%m-%d").to_string()),
+            length: r.metadata.length,
+            location: r.metadata.location,
+            notes: r.metadata.notes,
+            file_path: r.file_path,
+            tags: r.tags.join(", "),
+            urls: r
+                .metadata
+                .urls
+                .into_iter()
+                .map(|u| format!("{}={}", u.kind, u.url))
+                .collect(),
+        }
+    }
+}
+
+async fn load(resuman: &Resuman) -> Result<(Vec<Application>, Vec<String>)> {
+    // newest first
+    let applications: Vec<Application> = resuman
+        .list()
+        .await?
+        .into_iter()
+        .rev()
+        .map(Application::from)
+        .collect();
+
+    let mut statuses: Vec<String> = KNOWN_STATUSES.iter().map(|s| s.to_string()).collect();
+    let mut others: Vec<String> = applications
+        .iter()
+        .map(|a| a.status.clone())
+        .filter(|s| !statuses.contains(s))
+        .collect();
+    others.sort();
+    others.dedup();
+    statuses.extend(others);
+
+    Ok((applications, statuses))
+}
+
+pub async fn execute(cfg: Config, args: &TuiCommand, pool: &SqlitePool) -> Result<()> {
+    let resuman = Resuman::from_pool(cfg, pool.clone());
+    let (applications, statuses) = load(&resuman).await?;
+    let mut app = App {
+        applications,
+        statuses,
+        filter: args.filter.clone().unwrap_or_default(),
+        view: if args.kanban {
+            View::Kanban
+        } else {
+            View::Table
+        },
+        mode: Mode::Normal,
+        table_state: TableState::default(),
+        column: 0,
+        card: 0,
+        message: String::new(),
+    };
+    app.clamp_selection();
+
+    let mut terminal = ratatui::try_init()?;
+    let result = run(&mut terminal, &mut app, &resuman).await;
+    ratatui::restore();
+    result
+}
+
+async fn run(terminal: &mut DefaultTerminal, app: &mut App, resuman: &Resuman) -> Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, app))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match &mut app.mode {
+            Mode::Filter => match key.code {
+                KeyCode::Enter => app.mode = Mode::Normal,
+                KeyCode::Esc => {
+                    app.filter.clear();
+                    app.mode = Mode::Normal;
+                }
+                KeyCode::Backspace => {
+                    app.filter.pop();
+                }
+                KeyCode::Char(c) => app.filter.push(c),
+                _ => {}
+            },
+            Mode::StatusPicker(state) => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => app.mode = Mode::Normal,
+                KeyCode::Down | KeyCode::Char('j') => state.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => state.select_previous(),
+                KeyCode::Enter => {
+                    let status = state.selected().and_then(|i| app.statuses.get(i)).cloned();
+                    let id = app.selected().map(|a| a.id);
+                    app.mode = Mode::Normal;
+                    if let (Some(status), Some(id)) = (status, id) {
+                        set_status(app, resuman, id, status).await?;
+                    }
+                }
+                _ => {}
+            },
+            Mode::Normal => match key.code {
+                KeyCode::Char('q') => return Ok(()),
+                KeyCode::Esc if app.filter.is_empty() => return Ok(()),
+                KeyCode::Esc => app.filter.clear(),
+                KeyCode::Down | KeyCode::Char('j') => app.move_by(1),
+                KeyCode::Up | KeyCode::Char('k') => app.move_by(-1),
+                KeyCode::PageDown => app.move_by(10),
+                KeyCode::PageUp => app.move_by(-10),
+                KeyCode::Home | KeyCode::Char('g') => app.move_by(isize::MIN / 2),
+                KeyCode::End | KeyCode::Char('G') => app.move_by(isize::MAX / 2),
+                KeyCode::Left | KeyCode::Char('h') => app.move_column(-1),
+                KeyCode::Right | KeyCode::Char('l') => app.move_column(1),
+                KeyCode::Char('/') => app.mode = Mode::Filter,
+                KeyCode::Char('v') | KeyCode::Tab => {
+                    let id = app.selected().map(|a| a.id);
+                    app.view = match app.view {
+                        View::Table => View::Kanban,
+                        View::Kanban => View::Table,
+                    };
+                    if let Some(id) = id {
+                        app.select_id(id);
+                    }
+                }
+                KeyCode::Char('s') => {
+                    if let Some(current) = app.selected().map(|a| a.status.clone()) {
+                        let mut state = ListState::default();
+                        state.select(app.statuses.iter().position(|s| *s == current));
+                        app.mode = Mode::StatusPicker(state);
+                    }
+                }
+                KeyCode::Char('o') => {
+                    if let Some(path) = app.selected().map(|a| PathBuf::from(&a.file_path)) {
+                        let folder = path.parent().unwrap_or(&path).display().to_string();
+                        app.message = match utils::open_with_system(&folder) {
+                            Ok(_) => format!("Opened {}", folder),
+                            Err(e) => e.to_string(),
+                        };
+                    }
+                }
+                KeyCode::Char('e') => {
+                    if let Some(file_path) = app.selected().map(|a| a.file_path.clone()) {
+                        // hand the terminal over to the editor, then take it back
+                        ratatui::restore();
+                        app.message = edit_file(&utils::editor(), &file_path);
+                        *terminal = ratatui::try_init()?;
+                    }
+                }
+                KeyCode::Char('r') => {
+                    let id = app.selected().map(|a| a.id);
+                    (app.applications, app.statuses) = load(resuman).await?;
+                    app.clamp_selection();
+                    if let Some(id) = id {
+                        app.select_id(id);
+                    }
+                    app.message = "Reloaded".to_string();
+                }
+                _ => {}
+            },
+        }
+
+        if let Mode::Filter = app.mode {
+            app.clamp_selection();
+        }
+    }
+}
+
+/// Opens a file in the editor, the message says how it went
+fn edit_file(editor: &str, file_path: &str) -> String {
+    match utils::open_in_editor(editor, Path::new(file_path)) {
+        Ok(()) => format!("Edited {}", file_path),
+        Err(e) => format!("{}, set $VISUAL or $EDITOR to change the editor", e),
+    }
+}
+
+async fn set_status(app: &mut App, resuman: &Resuman, id: i64, status: String) -> Result<()> {
+    let patch = Patch {
+        status: Some(status.clone()),
+        ..Default::default()
+    };
+    app.message = match resuman.update(id, patch).await {
+        Ok(_) => format!("Set status of {} to {}", id, status),
+        Err(e) => format!("Could not update {}: {}", id, e),
+    };
+
+    (app.applications, app.statuses) = load(resuman).await?;
+    app.clamp_selection();
+    app.select_id(id);
+    Ok(())
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, bar] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+
+    match app.view {
+        View::Table => {
+            let [table, detail] =
+                Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .areas(main);
+            draw_table(frame, app, table);
+            draw_detail(frame, app, detail);
+        }
+        View::Kanban => {
+            let [board, detail] =
+                Layout::vertical([Constraint::Percentage(65), Constraint::Percentage(35)])
+                    .areas(main);
+            draw_kanban(frame, app, board);
+            draw_detail(frame, app, detail);
+        }
+    }
+
+    let bar_text = match &app.mode {
+        Mode::Filter => format!("/{}", app.filter),
+        Mode::StatusPicker(_) => "j/k move  enter set status  esc cancel".to_string(),
+        Mode::Normal if !app.message.is_empty() => app.message.clone(),
+        Mode::Normal => {
+            let filter = if app.filter.is_empty() {
+                String::new()
+            } else {
+                format!("[/{}]  ", app.filter)
+            };
+            format!(
+                "{}q quit  / filter  v view  s status  o open folder  e edit tex  r reload",
+                filter
+            )
+        }
+    };
+    frame.render_widget(Paragraph::new(bar_text).reversed(), bar);
+
+    if let Mode::StatusPicker(state) = &mut app.mode {
+        let area = centered(main, 30, app.statuses.len() as u16 + 2);
+        let items: Vec<ListItem> = app
+            .statuses
+            .iter()
+            .map(|s| ListItem::new(s.as_str()))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Status"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, state);
+    }
+}
+
+fn draw_table(frame: &mut Frame, app: &mut App, area: Rect) {
+    let rows: Vec<Row> = app
+        .visible()
+        .iter()
+        .map(|a| {
+            Row::new(vec![
+                Cell::from(a.id.to_string()),
+                Cell::from(a.company.clone()),
+                Cell::from(a.position.clone()),
+                Cell::from(a.group.clone()),
+                Cell::from(a.status.clone()),
+                Cell::from(a.created_at.clone()),
+            ])
+        })
+        .collect();
+    let title = format!("Applications ({})", rows.len());
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(5),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "id", "company", "position", "group", "status", "created",
+        ])
+        .bold(),
+    )
+    .block(Block::default().borders(Borders::ALL).title(title))
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, area, &mut app.table_state);
+}
+
+fn draw_kanban(frame: &mut Frame, app: &App, area: Rect) {
+    let columns = app.columns();
+    let areas = Layout::horizontal(vec![Constraint::Fill(1); columns.len().max(1)]).split(area);
+
+    for (c, ((status, cards), area)) in columns.iter().zip(areas.iter()).enumerate() {
+        let items: Vec<ListItem> = cards
+            .iter()
+            .map(|a| ListItem::new(format!("{} {}\n  {}", a.id, a.company, a.position)))
+            .collect();
+        let mut state = ListState::default();
+        let mut block =
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} ({})", status, cards.len()));
+        if c == app.column {
+            state.select(Some(app.card));
+            block = block.border_style(Style::default().add_modifier(Modifier::BOLD));
+        }
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, *area, &mut state);
+    }
+}
+
+fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title("Details");
+    let Some(a) = app.selected() else {
+        frame.render_widget(Paragraph::new("Nothing selected").block(block), area);
+        return;
+    };
+
+    let field =
+        |name: &str, value: String| Line::from(vec![format!("{:<10}", name).bold(), value.into()]);
+    let mut lines = vec![
+        field("id", a.id.to_string()),
+        field("company", a.company.clone()),
+        field("position", a.position.clone()),
+        field("group", a.group.clone()),
+        field("template", a.template.clone()),
+        field("status", a.status.clone()),
+        field("created", a.created_at.clone()),
+        field(
+            "applied",
+            a.applied_time.clone().unwrap_or("never".to_string()),
+        ),
+        field("length", format!("{} weeks", a.length)),
+        field("location", a.location.clone().unwrap_or("none".to_string())),
+        field("tags", a.tags.clone()),
+        field("file", a.file_path.clone()),
+        Line::from(""),
+        Line::from("urls".bold()),
+    ];
+    lines.extend(a.urls.iter().map(|u| Line::from(format!("  {}", u))));
+    lines.push(Line::from(""));
+    lines.push(Line::from("notes".bold()));
+    lines.extend(Text::from(a.notes.clone()).lines);
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_file_takes_editors_with_flags_and_reports_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("my resume.tex");
+        let file = file.to_str().unwrap();
+
+        // flags come before the file, like `code --wait`
+        assert_eq!(edit_file("touch -m", file), format!("Edited {}", file));
+        assert!(Path::new(file).exists());
+        assert_eq!(
+            edit_file("false", file),
+            "false exited with exit status: 1, set $VISUAL or $EDITOR to change the editor"
+        );
+        assert!(edit_file("/no/such/editor", file)
+            .starts_with("/no/such/editor exited with exit status: 127"));
+    }
+}
@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use sqlx::SqlitePool;
+
+use crate::{
+    config::Config,
+    url::{insert_url, sync_metadata_urls, validate_url, UrlKind},
+    Error,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "url",
+    about = "Manage URLs attached to a resume",
+    visible_aliases = ["urls", "link"],
+    arg_required_else_help = true
+)]
+pub struct UrlCommand {
+    #[command(subcommand)]
+    pub command: Option<UrlCommands>,
+}
+
+#[derive(Parser)]
+pub enum UrlCommands {
+    Add(AddCommand),
+    Rm(RmCommand),
+    List(ListUrlCommand),
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "add", about = "Attach a URL to a resume")]
+pub struct AddCommand {
+    #[arg(index = 1, help = "ID of the resume")]
+    pub id: i32,
+
+    #[arg(index = 2, help = "URL to attach", value_parser = validate_url)]
+    pub url: String,
+
+    #[arg(short, long, help = "Kind of URL", value_enum, default_value_t = UrlKind::Posting)]
+    pub kind: UrlKind,
+
+    #[arg(short, long, help = "Optional label for the URL")]
+    pub label: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "rm", about = "Remove a URL by its URL id", visible_aliases = ["remove", "delete"])]
+pub struct RmCommand {
+    #[arg(index = 1, help = "ID of the URL (see `url list`)")]
+    pub url_id: i64,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "list", about = "List URLs attached to resumes", visible_aliases = ["ls"])]
+pub struct ListUrlCommand {
+    #[arg(index = 1, help = "ID of the resume, lists every URL if omitted")]
+    pub id: Option<i32>,
+
+    #[arg(short, long, help = "Only list URLs of this kind", value_enum)]
+    pub kind: Option<UrlKind>,
+}
+
+pub async fn execute(_cfg: Config, args: &UrlCommand, pool: &SqlitePool) -> Result<()> {
+    match &args.command {
+        Some(UrlCommands::Add(args)) => {
+            log::debug!("Adding url with arguments:\n{:#?}", args);
+
+            let resume_id = i64::from(args.id);
+            let exists = sqlx::query!("SELECT id FROM resumes WHERE id = ?", resume_id)
+                .fetch_optional(pool)
+                .await?;
+            if exists.is_none() {
+                return Err(Error::ResumeNotFound(args.id.into()).into());
+            }
+
+            let url_id =
+                insert_url(pool, resume_id, args.kind, args.label.as_deref(), &args.url).await?;
+            sync_metadata_urls(pool, resume_id).await?;
+
+            // print the id of the new url so it can be removed later
+            println!("{}", url_id);
+            Ok(())
+        }
+        Some(UrlCommands::Rm(args)) => {
+            log::debug!("Removing url with arguments:\n{:#?}", args);
+
+            let resume_id = sqlx::query!(
+                "DELETE FROM resume_urls WHERE id = ? RETURNING resume_id",
+                args.url_id
+            )
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| Error::Invalid(format!("No URL found with id {}", args.url_id)))?
+            .resume_id;
+
+            sync_metadata_urls(pool, resume_id).await?;
+            Ok(())
+        }
+        Some(UrlCommands::List(args)) => {
+            log::debug!("Listing urls with arguments:\n{:#?}", args);
+
+            let kind = args.kind.map(|k| k.as_str());
+            let records = sqlx::query!(
+                r#"
+SELECT id, resume_id, kind, label, url FROM resume_urls
+WHERE (?1 IS NULL OR resume_id = ?1) AND (?2 IS NULL OR kind = ?2)
+ORDER BY resume_id, id
+                "#,
+                args.id,
+                kind,
+            )
+            .fetch_all(pool)
+            .await?;
+
+            println!("id\tresume_id\tkind\tlabel\turl");
+            for r in records {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}",
+                    r.id,
+                    r.resume_id,
+                    r.kind,
+                    r.label.unwrap_or("null".to_string()),
+                    r.url
+                );
+            }
+            Ok(())
+        }
+        None => Err(anyhow!("No subcommand provided")),
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Local;
+use clap::Parser;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use sqlx::SqlitePool;
+
+use crate::{
+    config::Config,
+    manager::Resuman,
+    sync::{self, Change},
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "watch",
+    about = "Keep the db in sync with root_dir while folders are moved and metadata is edited",
+    long_about = "Keep the db in sync with root_dir while folders are moved and metadata is edited. Starts with a full scan, then applies new folders, moves and metadata edits as they happen and prints each of them. Rows whose files are gone are only reported, see clean"
+)]
+pub struct WatchCommand {
+    #[arg(
+        short,
+        long,
+        value_name = "MS",
+        default_value_t = 500,
+        help = "Wait for this many milliseconds without changes before syncing"
+    )]
+    pub debounce: u64,
+
+    #[arg(long, help = "Only do the full scan and exit")]
+    pub once: bool,
+}
+
+/// Prints the changes with the time, a missing or skipped resume only until it
+/// changes, instead of on every sync
+fn report(changes: Vec<Change>, lasting: &mut Vec<Change>) {
+    let now: Vec<Change> = changes
+        .iter()
+        .filter(|c| matches!(c, Change::Missing { .. } | Change::Skipped { .. }))
+        .cloned()
+        .collect();
+    for change in changes {
+        if lasting.contains(&change) {
+            continue;
+        }
+        log::info!("{}", change);
+        println!("{}\t{}", Local::now().format("%Y-%m-%dT%H:%M:%S"), change);
+    }
+    *lasting = now;
+}
+
+/// Whether an event can change what a sync finds, reads of the files (by the sync
+/// itself) and changes of the db and the backups cannot
+fn relevant(resuman: &Resuman, event: &notify::Result<Event>) -> bool {
+    match event {
+        Ok(event) => {
+            !matches!(event.kind, EventKind::Access(_))
+                && !event.paths.iter().all(|p| sync::is_internal(resuman, p))
+        }
+        Err(e) => {
+            log::warn!("Watch error: {}", e);
+            false
+        }
+    }
+}
+
+pub async fn execute(cfg: Config, args: &WatchCommand, pool: &SqlitePool) -> Result<()> {
+    log::debug!("Watch with arguments:\n{:#?}", args);
+
+    let resuman = Resuman::from_pool(cfg, pool.clone());
+    let root_dir = resuman.config().root_dir.clone();
+    let mut lasting = Vec::new();
+
+    // whatever happened while nothing was watching
+    report(sync::reconcile(&resuman).await?, &mut lasting);
+    if args.once {
+        return Ok(());
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })?;
+    watcher.watch(&root_dir, RecursiveMode::Recursive)?;
+    eprintln!("Watching {}, stop with Ctrl-C", root_dir.display());
+
+    let debounce = Duration::from_millis(args.debounce);
+    while let Some(event) = rx.recv().await {
+        if !relevant(&resuman, &event) {
+            continue;
+        }
+        // a move or a copy is many events, the sync waits until they stopped
+        while let Ok(Some(_)) = tokio::time::timeout(debounce, rx.recv()).await {}
+        // a failed sync is retried with the next change, the watcher keeps running
+        match sync::reconcile(&resuman).await {
+            Ok(changes) => report(changes, &mut lasting),
+            Err(e) => log::error!("Could not sync: {}", e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,339 @@
+// guided prompts for `create`, only used when stdin is a terminal
+
+use std::{fs::read_dir, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use dialoguer::{theme::ColorfulTheme, Completion, Confirm, Input};
+use sqlx::SqlitePool;
+
+use crate::{
+    commands::create::CreateCommand,
+    config::Config,
+    letter,
+    resume::KNOWN_STATUSES,
+    tag,
+    url::{self, UrlArg},
+};
+
+/// Tab completes to the first candidate starting with the input, ignoring case
+struct Candidates(Vec<String>);
+
+impl Candidates {
+    fn new(mut values: Vec<String>) -> Self {
+        values.sort();
+        values.dedup();
+        Candidates(values)
+    }
+}
+
+impl Completion for Candidates {
+    fn get(&self, input: &str) -> Option<String> {
+        let input = input.to_lowercase();
+        self.0
+            .iter()
+            .find(|c| c.to_lowercase().starts_with(&input) && c.to_lowercase() != input)
+            .cloned()
+    }
+}
+
+/// Completes only the last space separated word, for lists like tags
+struct LastWord(Candidates);
+
+impl Completion for LastWord {
+    fn get(&self, input: &str) -> Option<String> {
+        let (head, last) = input.rsplit_once(' ').unwrap_or(("", input));
+        let completed = self.0.get(last)?;
+        Some(if head.is_empty() {
+            completed
+        } else {
+            format!("{} {}", head, completed)
+        })
+    }
+}
+
+/// File stems of every file directly inside `dir`, empty if the directory is missing
+fn file_stems(dir: PathBuf) -> Vec<String> {
+    read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn prompt_text(
+    theme: &ColorfulTheme,
+    prompt: &str,
+    default: Option<String>,
+    candidates: &Candidates,
+) -> Result<String> {
+    let mut input = Input::<String>::with_theme(theme)
+        .with_prompt(prompt)
+        .completion_with(candidates)
+        .validate_with(|input: &String| -> Result<(), &str> {
+            if input.trim().is_empty() {
+                Err("A value is required")
+            } else {
+                Ok(())
+            }
+        });
+    if let Some(default) = default {
+        input = input.default(default);
+    }
+    Ok(input.interact_text()?.trim().to_string())
+}
+
+fn prompt_optional(
+    theme: &ColorfulTheme,
+    prompt: &str,
+    initial: Option<String>,
+) -> Result<Option<String>> {
+    let value = Input::<String>::with_theme(theme)
+        .with_prompt(prompt)
+        .with_initial_text(initial.unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()?;
+    let value = value.trim().to_string();
+    Ok(if value.is_empty() { None } else { Some(value) })
+}
+
+/// Asks for every field of `create` in turn, flags that were given become the defaults
+pub async fn prompt_create(
+    cfg: &Config,
+    args: &CreateCommand,
+    pool: &SqlitePool,
+) -> Result<CreateCommand> {
+    let theme = ColorfulTheme::default();
+
+    let companies = sqlx::query!("SELECT DISTINCT company FROM resumes")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.company)
+        .collect();
+    let groups = sqlx::query!("SELECT DISTINCT \"group\" FROM resumes")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.group)
+        .collect();
+    let mut templates: Vec<String> = sqlx::query!("SELECT DISTINCT template FROM resumes")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.template)
+        .collect();
+    templates.extend(file_stems(
+        cfg.root_dir.join(
+            cfg.template_dir
+                .clone()
+                .unwrap_or(PathBuf::from("templates")),
+        ),
+    ));
+    let mut statuses: Vec<String> = sqlx::query!("SELECT DISTINCT status FROM metadata")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.status)
+        .collect();
+    statuses.extend(KNOWN_STATUSES.iter().map(|s| s.to_string()));
+    let tags = sqlx::query!("SELECT name FROM tags")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.name)
+        .collect();
+
+    let company = prompt_text(
+        &theme,
+        "Company",
+        args.company.clone(),
+        &Candidates::new(companies),
+    )?;
+    let group = prompt_text(
+        &theme,
+        "Group",
+        Some(
+            args.group
+                .clone()
+                .unwrap_or(cfg.default_group.clone().unwrap_or("default".to_string())),
+        ),
+        &Candidates::new(groups),
+    )?;
+
+    // a copied file replaces the template, so only ask for a template without one
+    let template = if args.file.is_some() || args.from.is_some() {
+        None
+    } else {
+        Some(prompt_text(
+            &theme,
+            "Template",
+            Some(
+                args.template.clone().unwrap_or(
+                    cfg.default_template
+                        .clone()
+                        .unwrap_or("default".to_string()),
+                ),
+            ),
+            &Candidates::new(templates),
+        )?)
+    };
+    let position = prompt_text(
+        &theme,
+        "Position",
+        Some(
+            args.position
+                .clone()
+                .unwrap_or(cfg.default_position.clone().unwrap_or("swe".to_string())),
+        ),
+        &Candidates::new(vec![]),
+    )?;
+
+    let has_cover_letter = Confirm::with_theme(&theme)
+        .with_prompt("Cover letter?")
+        .default(args.has_cover_letter || args.letter_template.is_some())
+        .interact()?;
+    let letter_template = if has_cover_letter {
+        let letter_templates = file_stems(letter::letter_template_dir(cfg));
+        let default = args.letter_template.clone().or(cfg
+            .default_letter_template
+            .clone()
+            .filter(|name| letter::find_template(cfg, name).is_some()));
+        match default {
+            // without any template the letter is left empty, like --letter
+            None if letter_templates.is_empty() => None,
+            default => Some(prompt_text(
+                &theme,
+                "Cover letter template",
+                default,
+                &Candidates::new(letter_templates),
+            )?),
+        }
+    } else {
+        None
+    };
+
+    let length = Input::<u16>::with_theme(&theme)
+        .with_prompt("Length of job (weeks)")
+        .default(args.length.unwrap_or(cfg.default_length.unwrap_or(16)))
+        .interact_text()?;
+    let location = prompt_optional(&theme, "Location (optional)", args.location.clone())?;
+    let status = prompt_text(
+        &theme,
+        "Status",
+        Some(
+            args.status
+                .clone()
+                .unwrap_or(cfg.default_status.clone().unwrap_or("applying".to_string())),
+        ),
+        &Candidates::new(statuses),
+    )?;
+
+    let initial_urls = args
+        .urls
+        .iter()
+        .map(UrlArg::to_string)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let urls = Input::<String>::with_theme(&theme)
+        .with_prompt("URLs as [kind=[label=]]url, space separated (optional)")
+        .with_initial_text(initial_urls)
+        .allow_empty(true)
+        .validate_with(|input: &String| -> Result<(), String> {
+            input
+                .split_whitespace()
+                .try_for_each(|u| url::parse_url_arg(u).map(|_| ()))
+                .map_err(|e| e.to_string())
+        })
+        .interact_text()?
+        .split_whitespace()
+        .map(url::parse_url_arg)
+        .collect::<crate::Result<Vec<UrlArg>>>()?;
+
+    let tag_candidates = LastWord(Candidates::new(tags));
+    let tags = Input::<String>::with_theme(&theme)
+        .with_prompt("Tags, space separated (optional)")
+        .with_initial_text(args.tags.join(" "))
+        .allow_empty(true)
+        .completion_with(&tag_candidates)
+        .validate_with(|input: &String| -> Result<(), String> {
+            input
+                .split_whitespace()
+                .try_for_each(|t| tag::normalize_tag(t).map(|_| ()))
+                .map_err(|e| e.to_string())
+        })
+        .interact_text()?
+        .split_whitespace()
+        .map(tag::normalize_tag)
+        .collect::<crate::Result<Vec<String>>>()?;
+    let notes = prompt_optional(&theme, "Notes (optional)", args.notes.clone())?;
+
+    let filled = CreateCommand {
+        company: Some(company),
+        group: Some(group),
+        template,
+        file: args.file.clone(),
+        from: args.from,
+        position: Some(position),
+        has_cover_letter,
+        letter_template,
+        applied_time: args.applied_time,
+        length: Some(length),
+        location,
+        status: Some(status),
+        urls,
+        notes,
+        tags,
+        force: args.force,
+        interactive: false,
+    };
+
+    eprintln!();
+    eprintln!("{}", summary(&filled));
+    let confirmed = Confirm::with_theme(&theme)
+        .with_prompt("Create this resume?")
+        .default(true)
+        .interact()?;
+    if !confirmed {
+        return Err(anyhow!("Aborted, nothing was created"));
+    }
+
+    Ok(filled)
+}
+
+fn summary(args: &CreateCommand) -> String {
+    let or_none = |value: &Option<String>| value.clone().unwrap_or("none".to_string());
+    let mut lines = vec![
+        format!("Company:      {}", or_none(&args.company)),
+        format!("Group:        {}", or_none(&args.group)),
+        match (&args.file, args.from) {
+            (Some(file), _) => format!("Copied from:  {}", file.display()),
+            (None, Some(id)) => format!("Copied from:  resume {}", id),
+            (None, None) => format!("Template:     {}", or_none(&args.template)),
+        },
+        format!("Position:     {}", or_none(&args.position)),
+        format!(
+            "Cover letter: {}",
+            match (&args.letter_template, args.has_cover_letter) {
+                (Some(template), _) => template.clone(),
+                (None, true) => "empty".to_string(),
+                (None, false) => "no".to_string(),
+            }
+        ),
+        format!("Length:       {} weeks", args.length.unwrap_or_default()),
+        format!("Location:     {}", or_none(&args.location)),
+        format!("Status:       {}", or_none(&args.status)),
+    ];
+    for u in &args.urls {
+        lines.push(format!("URL:          {}", u));
+    }
+    if !args.tags.is_empty() {
+        lines.push(format!("Tags:         {}", args.tags.join(", ")));
+    }
+    lines.push(format!("Notes:        {}", or_none(&args.notes)));
+    lines.join("\n")
+}
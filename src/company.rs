@@ -0,0 +1,111 @@
+// the companies applied to, so one written as "Jane Street", "Jane-Street" and
+// "janestreet" is still one company in lists and stats
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::{
+    duplicate,
+    error::{Error, Result},
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Company {
+    pub id: i64,
+    pub name: String,
+    pub website: Option<String>,
+    pub industry: Option<String>,
+    pub size: Option<String>,
+    pub hq: Option<String>,
+    pub aliases: Vec<String>, // sorted
+}
+
+/// Every company, sorted by name
+pub async fn all(pool: &SqlitePool) -> Result<Vec<Company>> {
+    let records = sqlx::query!(
+        r#"
+SELECT id, name, website, industry, size, hq,
+    (SELECT json_group_array(name) FROM (
+        SELECT name FROM company_aliases WHERE company_id = companies.id ORDER BY name
+    )) AS "aliases!: String"
+FROM companies ORDER BY name COLLATE NOCASE
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    records
+        .into_iter()
+        .map(|r| {
+            Ok(Company {
+                id: r.id,
+                name: r.name,
+                website: r.website,
+                industry: r.industry,
+                size: r.size,
+                hq: r.hq,
+                aliases: serde_json::from_str(&r.aliases)?,
+            })
+        })
+        .collect()
+}
+
+/// The company `name` is the name or an alias of, compared like duplicates are
+pub async fn resolve(pool: &SqlitePool, name: &str) -> Result<Option<i64>> {
+    let key = duplicate::normalize_company(name);
+    let found = all(pool).await?.into_iter().find(|c| {
+        std::iter::once(&c.name)
+            .chain(&c.aliases)
+            .any(|n| duplicate::normalize_company(n) == key)
+    });
+    Ok(found.map(|c| c.id))
+}
+
+/// A company given on the command line, by id, name or alias
+pub async fn find(pool: &SqlitePool, arg: &str) -> Result<Company> {
+    let id = match arg.parse::<i64>() {
+        Ok(id) => Some(id),
+        Err(_) => resolve(pool, arg).await?,
+    };
+    all(pool)
+        .await?
+        .into_iter()
+        .find(|c| Some(c.id) == id)
+        .ok_or_else(|| {
+            Error::Invalid(format!(
+                "No company {}, see `resuman company list` for the companies",
+                arg
+            ))
+        })
+}
+
+/// Links a resume to the company its name belongs to, adding the company if it is new
+pub async fn link(pool: &SqlitePool, resume_id: i64, name: &str) -> Result<i64> {
+    let id = match resolve(pool, name).await? {
+        Some(id) => id,
+        None => {
+            sqlx::query!("INSERT INTO companies (name) VALUES (?) RETURNING id", name)
+                .fetch_one(pool)
+                .await?
+                .id
+        }
+    };
+    // a new way of writing the name is remembered
+    sqlx::query!(
+        r#"
+INSERT OR IGNORE INTO company_aliases (name, company_id)
+SELECT ?1, ?2 WHERE ?1 != (SELECT name FROM companies WHERE id = ?2)
+        "#,
+        name,
+        id
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query!(
+        "UPDATE resumes SET company_id = ? WHERE id = ?",
+        id,
+        resume_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
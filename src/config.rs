@@ -1,13 +1,28 @@
-use serde_derive::Deserialize;
-use std::path::PathBuf;
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+
+use crate::{build, utils, Error};
+
+// the name of the config under [main], which every profile builds on
+pub const MAIN_PROFILE: &str = "main";
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Data {
-    // all under [main] for now, I guess we can make subcategories later
     pub main: Config,
+    // [profiles.<name>] tables, any value they leave out comes from [main]
+    #[serde(default)]
+    pub profiles: BTreeMap<String, toml::Table>,
+    pub default_profile: Option<String>, // used without --profile, set by `profile use`
+
+    // from RESUMAN_* env vars and --set, they win over [main] and every profile
+    #[serde(skip)]
+    pub overrides: toml::Table,
+    // relative root_dirs are relative to this, the folder of the config file
+    #[serde(skip)]
+    pub base_dir: Option<PathBuf>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct Config {
     pub root_dir: PathBuf,
     pub template_dir: Option<PathBuf>,
@@ -26,9 +41,156 @@ pub struct Config {
     pub default_status: Option<String>, // default is "applying"
 
     pub folder_pattern: Option<String>, // default is "{company}_{position}_{date}"
-    pub file_pattern: Option<String>,   // default is "resume_{company}_{position}_{date}"
-    pub date_format: Option<String>,    // default is "%Y_%m_%d"
+    pub file_pattern: Option<String>,   // default is "resume_{company}_{position}_{date}.tex"
+    pub date_format: Option<String>,    // default is "%Y-%m-%d"
 
     pub cover_letter_name: Option<String>, // default is "cover_letter.txt"
+    pub letter_template_dir: Option<PathBuf>, // default is "letters", relative to template_dir
+    pub default_letter_template: Option<String>, // default is "default", empty letter if missing
     pub metadata_name: Option<String>,     // default is "metadata.json"
+
+    pub default_currency: Option<String>, // default is "USD"
+    pub offer_reminder_days: Option<u16>, // default is 3, remind about offer deadlines this close
+
+    pub journal_mode: Option<String>, // default is "wal", see backup::JOURNAL_MODES
+    pub backup_dir: Option<PathBuf>,  // default is "backups", relative to root_dir
+    pub backup_keep: Option<u16>,     // default is 10, 0 keeps every backup
+
+    pub git: Option<bool>, // default is false, commit root_dir to a local git repository
+
+    pub duplicate_window_days: Option<u16>, // default is 30, see duplicate::find
+
+    pub build_command: Option<String>, // default is build::DEFAULT_BUILD_COMMAND
+}
+
+/// The env var that overrides `key`, e.g. RESUMAN_ROOT_DIR for root_dir
+pub fn env_var(key: &str) -> String {
+    format!("RESUMAN_{}", key.to_uppercase())
+}
+
+impl Config {
+    /// Every key a config can have, sorted
+    pub fn keys() -> Vec<String> {
+        Config::defaults().keys().cloned().collect()
+    }
+
+    // with the defaults filled in, every key shows up
+    fn defaults() -> toml::Table {
+        toml::Table::try_from(Config::default().with_defaults()).unwrap_or_default()
+    }
+
+    /// Parses a value given as text, like in an env var, into the type `key` has
+    pub fn parse_value(key: &str, value: &str) -> crate::Result<toml::Value> {
+        match Config::defaults().get(key) {
+            Some(toml::Value::Integer(_)) => {
+                value.trim().parse().map(toml::Value::Integer).map_err(|_| {
+                    Error::Invalid(format!("{} must be a number, not {:?}", key, value))
+                })
+            }
+            Some(toml::Value::Boolean(_)) => match value.trim() {
+                "true" | "1" => Ok(toml::Value::Boolean(true)),
+                "false" | "0" => Ok(toml::Value::Boolean(false)),
+                _ => Err(Error::Invalid(format!(
+                    "{} must be true or false, not {:?}",
+                    key, value
+                ))),
+            },
+            Some(_) => Ok(toml::Value::String(value.to_string())),
+            None => Err(Error::Invalid(format!(
+                "Unknown config key {}, the keys are {}",
+                key,
+                Config::keys().join(", ")
+            ))),
+        }
+    }
+
+    /// The same config with every unset value replaced by the default the commands use
+    pub fn with_defaults(&self) -> Config {
+        let or = |value: &Option<String>, default: &str| {
+            Some(value.clone().unwrap_or(default.to_string()))
+        };
+        Config {
+            root_dir: self.root_dir.clone(),
+            template_dir: Some(
+                self.template_dir
+                    .clone()
+                    .unwrap_or(PathBuf::from("templates")),
+            ),
+            db_path: Some(
+                self.db_path
+                    .clone()
+                    .unwrap_or(PathBuf::from("resumandb.sqlite")),
+            ),
+            default_group: or(&self.default_group, "default"),
+            default_template: or(&self.default_template, "default"),
+            default_position: or(&self.default_position, "swe"),
+            default_length: Some(self.default_length.unwrap_or(16)),
+            default_status: or(&self.default_status, "applying"),
+            folder_pattern: or(&self.folder_pattern, "{company}_{position}_{date}"),
+            file_pattern: or(&self.file_pattern, "resume_{company}_{position}_{date}.tex"),
+            date_format: or(&self.date_format, "%Y-%m-%d"),
+            cover_letter_name: or(&self.cover_letter_name, "cover_letter.txt"),
+            letter_template_dir: Some(
+                self.letter_template_dir
+                    .clone()
+                    .unwrap_or(PathBuf::from("letters")),
+            ),
+            default_letter_template: or(&self.default_letter_template, "default"),
+            metadata_name: or(&self.metadata_name, "metadata.json"),
+            default_currency: or(&self.default_currency, "USD"),
+            offer_reminder_days: Some(self.offer_reminder_days.unwrap_or(3)),
+            journal_mode: or(&self.journal_mode, "wal"),
+            backup_dir: Some(self.backup_dir.clone().unwrap_or(PathBuf::from("backups"))),
+            backup_keep: Some(self.backup_keep.unwrap_or(10)),
+            git: Some(self.git.unwrap_or(false)),
+            duplicate_window_days: Some(self.duplicate_window_days.unwrap_or(30)),
+            build_command: or(&self.build_command, build::DEFAULT_BUILD_COMMAND),
+        }
+    }
+}
+
+impl Data {
+    /// Every profile name, main first
+    pub fn profile_names(&self) -> Vec<String> {
+        std::iter::once(MAIN_PROFILE.to_string())
+            .chain(self.profiles.keys().cloned())
+            .collect()
+    }
+
+    /// The profile that `name`, or default_profile without it, picks
+    pub fn active_profile<'a>(&'a self, name: Option<&'a str>) -> &'a str {
+        name.or(self.default_profile.as_deref())
+            .unwrap_or(MAIN_PROFILE)
+    }
+
+    /// The config of a profile, [main] with the values of the profile and then the
+    /// overrides on top, and root_dir resolved
+    ///
+    /// A profile without its own root_dir gets a folder named after it in the root_dir of
+    /// [main], so profiles never share a db by accident.
+    pub fn profile(&self, name: Option<&str>) -> crate::Result<Config> {
+        let name = self.active_profile(name);
+        let mut merged = toml::Table::try_from(&self.main).map_err(anyhow::Error::from)?;
+        if name != MAIN_PROFILE {
+            let profile = self.profiles.get(name).ok_or_else(|| {
+                Error::Invalid(format!(
+                    "Unknown profile {}, the profiles are {}",
+                    name,
+                    self.profile_names().join(", ")
+                ))
+            })?;
+            merged.insert(
+                "root_dir".to_string(),
+                toml::Value::String(self.main.root_dir.join(name).to_string_lossy().to_string()),
+            );
+            merged.extend(profile.clone());
+        }
+        merged.extend(self.overrides.clone());
+
+        let mut cfg: Config = merged.try_into().map_err(|e: toml::de::Error| {
+            Error::Invalid(format!("Invalid profile {}: {}", name, e.message()))
+        })?;
+        cfg.root_dir = utils::resolve_path(cfg.root_dir, self.base_dir.as_deref())?;
+        Ok(cfg)
+    }
 }
@@ -0,0 +1,152 @@
+// likely duplicate applications, the same company and position written differently
+// within a few days of each other, e.g. "Google" swe and "google" SWE
+
+use chrono::{DateTime, Utc};
+
+use crate::{config::Config, Resume};
+
+// legal forms that do not make a company a different one
+const SUFFIXES: [&str; 9] = [
+    "inc",
+    "llc",
+    "ltd",
+    "corp",
+    "corporation",
+    "co",
+    "gmbh",
+    "plc",
+    "company",
+];
+
+/// How many days apart two applications can be and still be the same one,
+/// `duplicate_window_days` in the config
+pub fn window_days(cfg: &Config) -> i64 {
+    cfg.duplicate_window_days.unwrap_or(30).into()
+}
+
+/// Lowercase letters and digits of each word, without punctuation
+fn words(s: &str) -> Vec<String> {
+    s.split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .map(|w| {
+            w.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// A company name without case, punctuation, spaces and legal forms like Inc.
+pub fn normalize_company(name: &str) -> String {
+    let mut words = words(name);
+    while words.len() > 1 && words.last().is_some_and(|w| SUFFIXES.contains(&w.as_str())) {
+        words.pop();
+    }
+    words.concat()
+}
+
+/// A position without case, punctuation and spaces
+pub fn normalize_position(name: &str) -> String {
+    words(name).concat()
+}
+
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(row[j + 1])
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// Whether two normalized names are the same up to a typo, which only counts for
+/// longer names since short ones like "hp" and "ibm" are easy to hit by chance
+fn similar(a: &str, b: &str) -> bool {
+    a == b || (a.chars().count().min(b.chars().count()) >= 5 && distance(a, b) <= 1)
+}
+
+/// Whether an application to `company` as `position` at `at` looks like `resume`
+pub fn matches(
+    resume: &Resume,
+    company: &str,
+    position: &str,
+    at: DateTime<Utc>,
+    days: i64,
+) -> bool {
+    (resume.created_at - at).num_days().abs() <= days
+        && similar(
+            &normalize_company(&resume.company),
+            &normalize_company(company),
+        )
+        && similar(
+            &normalize_position(&resume.position),
+            &normalize_position(position),
+        )
+}
+
+/// The resumes that look like an application to `company` as `position` at `at`
+pub fn find<'a>(
+    resumes: &'a [Resume],
+    company: &str,
+    position: &str,
+    at: DateTime<Utc>,
+    days: i64,
+) -> Vec<&'a Resume> {
+    resumes
+        .iter()
+        .filter(|r| matches(r, company, position, at, days))
+        .collect()
+}
+
+/// Groups of ids that look like the same application, a resume that matches any
+/// resume of a group is in it, sorted by their smallest id
+pub fn clusters(resumes: &[Resume], days: i64) -> Vec<Vec<i64>> {
+    // union-find over the indexes of `resumes`
+    let mut parent: Vec<usize> = (0..resumes.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..resumes.len() {
+        for j in i + 1..resumes.len() {
+            let b = &resumes[j];
+            if matches(&resumes[i], &b.company, &b.position, b.created_at, days) {
+                let (ri, rj) = (root(&mut parent, i), root(&mut parent, j));
+                parent[ri.max(rj)] = ri.min(rj);
+            }
+        }
+    }
+
+    let mut clusters: Vec<Vec<i64>> = Vec::new();
+    let mut index: Vec<Option<usize>> = vec![None; resumes.len()];
+    for (i, resume) in resumes.iter().enumerate() {
+        let r = root(&mut parent, i);
+        match index[r] {
+            Some(c) => clusters[c].push(resume.id),
+            None => {
+                index[r] = Some(clusters.len());
+                clusters.push(vec![resume.id]);
+            }
+        }
+    }
+    clusters.retain(|c| c.len() > 1);
+    for c in &mut clusters {
+        c.sort();
+    }
+    clusters.sort();
+    clusters
+}
@@ -1,20 +1,192 @@
-// #[derive(Debug)]
-// pub enum ResumeManagerError {
-//     IoError(std::io::Error),
-//     ParseError(String),
-// }
-//
-// impl std::fmt::Display for ResumeManagerError {
-//     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-//         match self {
-//             ResumeManagerError::IoError(err) => write!(f, "I/O Error: {}", err),
-//             ResumeManagerError::ParseError(msg) => write!(f, "Parse Error: {}", msg),
-//         }
-//     }
-// }
-//
-// impl From<std::io::Error> for ResumeManagerError {
-//     fn from(err: std::io::Error) -> Self {
-//         ResumeManagerError::IoError(err)
-//     }
-// }
+use std::path::PathBuf;
+
+/// Errors returned by the library, see [`crate::Resuman`]
+///
+/// Every kind has its own process exit code, see [`Error::exit_code`].
+#[derive(Debug)]
+pub enum Error {
+    ConfigNotFound(Vec<PathBuf>), // every path that was tried
+    ConfigParse(PathBuf, toml::de::Error),
+    InvalidPath(PathBuf, String), // the path and why it is invalid
+    TemplateNotFound(PathBuf),    // where the template was looked for
+    DuplicateResume(PathBuf),     // the folder that already exists
+    LikelyDuplicate(Vec<String>), // the resumes it looks like
+    ResumeNotFound(i64),
+    Db(sqlx::Error),
+    Io(std::io::Error),
+    MetadataParse(serde_json::Error),
+    InvalidMetadata(PathBuf, Vec<String>), // the file and what is wrong with it
+    Migration(sqlx::migrate::MigrateError),
+    Git(String),            // what git printed
+    Build(PathBuf, String), // the .tex and what went wrong
+    Invalid(String),        // any other bad input
+    Other(anyhow::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Exit code of the process when a command fails with this error
+    ///
+    /// 2 is shared with clap's usage errors, since both mean the input was wrong.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Other(_) => 1,
+            Error::Invalid(_) => 2,
+            Error::ConfigNotFound(_) => 3,
+            Error::ConfigParse(..) => 4,
+            Error::InvalidPath(..) => 5,
+            Error::TemplateNotFound(_) => 6,
+            Error::DuplicateResume(_) | Error::LikelyDuplicate(_) => 7,
+            Error::ResumeNotFound(_) => 8,
+            Error::Db(_) => 9,
+            Error::Io(_) => 10,
+            Error::MetadataParse(_) | Error::InvalidMetadata(..) => 11,
+            Error::Migration(_) => 12,
+            Error::Git(_) => 13,
+            Error::Build(..) => 14,
+        }
+    }
+
+    /// What the user can do about it, printed under the error
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Error::ConfigNotFound(_) => Some(
+                "create ~/.config/resuman/config.toml with a [main] section containing root_dir = \"~/resumes\", or pass --config <FILE>".to_string(),
+            ),
+            Error::ConfigParse(..) => {
+                Some("every setting goes under [main], check the TOML syntax".to_string())
+            }
+            Error::InvalidPath(..) => {
+                Some("paths must be absolute, start with ~/ or a $VAR, or be relative to the config file".to_string())
+            }
+            Error::TemplateNotFound(_) => Some(
+                "see `resuman list template --fs` for the resume templates, or copy an existing .tex with --file".to_string(),
+            ),
+            Error::DuplicateResume(_) => Some(
+                "a resume with the same company, position and date already exists, change it with `resuman update <id>` or pick another --position".to_string(),
+            ),
+            Error::LikelyDuplicate(_) => Some(
+                "pass --force to create it anyway, `resuman dupes` lists and merges likely duplicates".to_string(),
+            ),
+            Error::ResumeNotFound(_) => {
+                Some("see `resuman list all -i -c` for the ids".to_string())
+            }
+            Error::MetadataParse(_) => Some(
+                "the metadata file is probably edited by hand, fix its JSON and try again".to_string(),
+            ),
+            Error::InvalidMetadata(..) => Some(
+                "fix the file by hand, `resuman metadata validate` lists the problems of every metadata file".to_string(),
+            ),
+            Error::Db(_) => Some("run with RUST_LOG=debug for details".to_string()),
+            Error::Migration(_) => Some(
+                "nothing else ran, see `resuman db status` for the migrations and `resuman backup --list` for the backup taken before migrating".to_string(),
+            ),
+            Error::Git(_) => Some(
+                "check that git is installed and `git status` works in root_dir, or turn it off with `resuman config set git false`".to_string(),
+            ),
+            Error::Build(..) => Some(
+                "see the .log next to the .tex, or set build_command to the tool that builds your resumes".to_string(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::ConfigNotFound(paths) => write!(
+                f,
+                "Cannot find a config file, tried {}",
+                paths
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Error::ConfigParse(path, err) => {
+                write!(f, "Could not parse config {}: {}", path.display(), err)
+            }
+            Error::InvalidPath(path, why) => write!(f, "Invalid path {}: {}", path.display(), why),
+            Error::TemplateNotFound(path) => write!(f, "Template not found: {}", path.display()),
+            Error::DuplicateResume(path) => {
+                write!(f, "Resume already exists: {}", path.display())
+            }
+            Error::LikelyDuplicate(resumes) => write!(
+                f,
+                "Looks like an application that exists already: {}",
+                resumes.join(", ")
+            ),
+            Error::ResumeNotFound(id) => write!(f, "No resume found with id {}", id),
+            Error::Db(err) => write!(f, "Database error: {}", err),
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::MetadataParse(err) => write!(f, "Could not parse metadata: {}", err),
+            Error::InvalidMetadata(path, problems) => write!(
+                f,
+                "Invalid metadata {}: {}",
+                path.display(),
+                problems.join(", ")
+            ),
+            Error::Migration(err) => write!(f, "Could not migrate the db: {}", err),
+            Error::Git(msg) => write!(f, "Git error: {}", msg),
+            Error::Build(path, why) => write!(f, "Could not build {}: {}", path.display(), why),
+            Error::Invalid(msg) => write!(f, "{}", msg),
+            Error::Other(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ConfigParse(_, err) => Some(err),
+            Error::Db(err) => Some(err),
+            Error::Io(err) => Some(err),
+            Error::MetadataParse(err) => Some(err),
+            Error::Migration(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(err: sqlx::Error) -> Self {
+        Error::Db(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::MetadataParse(err)
+    }
+}
+
+// the commands return anyhow errors, which may carry one of the errors above, so
+// recover its kind before giving up
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<Error>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<sqlx::Error>() {
+            Ok(err) => return Error::Db(err),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<std::io::Error>() {
+            Ok(err) => return Error::Io(err),
+            Err(err) => err,
+        };
+        match err.downcast::<serde_json::Error>() {
+            Ok(err) => Error::MetadataParse(err),
+            Err(err) => Error::Other(err),
+        }
+    }
+}
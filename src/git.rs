@@ -0,0 +1,200 @@
+// an optional history of root_dir in a local git repository, kept through the git CLI
+// so the repository stays usable with any other git tool
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    backup, build,
+    config::Config,
+    error::{Error, Result},
+    manager,
+};
+
+/// One commit that touched a resume folder, see [`log`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub rev: String,
+    pub time: String,
+    pub subject: String,
+    pub tags: Vec<String>,
+}
+
+/// Whether create, update and build commit their changes, `git` in the config
+pub fn enabled(cfg: &Config) -> bool {
+    cfg.git.unwrap_or(false)
+}
+
+pub fn is_repo(cfg: &Config) -> bool {
+    cfg.root_dir.join(".git").exists()
+}
+
+/// The tag of the version of a resume that was sent, set when its status becomes applied
+pub fn applied_tag(id: i64) -> String {
+    format!("resume-{}-applied", id)
+}
+
+/// Runs git in root_dir and returns its stdout
+fn git(cfg: &Config, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&cfg.root_dir)
+        .args(args)
+        .output()
+        .map_err(|e| Error::Git(format!("could not run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Commits what is staged, as resuman when git has no user set up
+fn commit_staged(cfg: &Config, message: &str, paths: &[&Path]) -> Result<()> {
+    let mut args = Vec::new();
+    if git(cfg, &["config", "user.email"]).is_err() {
+        args.extend([
+            "-c",
+            "user.name=resuman",
+            "-c",
+            "user.email=resuman@localhost",
+        ]);
+    }
+    args.extend(["commit", "-q", "-m", message, "--"]);
+    let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    args.extend(paths.iter().map(String::as_str));
+    git(cfg, &args)?;
+    Ok(())
+}
+
+/// Makes root_dir a repository with a first commit of what is in it, unless it is one
+/// already, and returns whether it did
+///
+/// The db and the backups are ignored, they change on every command, and so is what a
+/// build leaves next to the PDF.
+pub fn init(cfg: &Config) -> Result<bool> {
+    start(cfg, None)
+}
+
+// `except` is left for its own commit, like the folder of a resume that is being created
+fn start(cfg: &Config, except: Option<&Path>) -> Result<bool> {
+    if is_repo(cfg) {
+        return Ok(false);
+    }
+    git(cfg, &["init", "-q"])?;
+
+    let ignore = cfg.root_dir.join(".gitignore");
+    if !ignore.exists() {
+        let mut lines = Vec::new();
+        for (path, suffix) in [(manager::db_path(cfg), "*"), (backup::backup_dir(cfg), "/")] {
+            if let Ok(rest) = path.strip_prefix(&cfg.root_dir) {
+                lines.push(format!("/{}{}\n", rest.display(), suffix));
+            }
+        }
+        lines.extend(build::BYPRODUCTS.iter().map(|p| format!("{}\n", p)));
+        fs::write(&ignore, lines.concat())?;
+    }
+    let except = except.map(|p| format!(":(exclude){}", p.display()));
+    let mut args = vec!["add", "-A", "--", "."];
+    args.extend(except.as_deref());
+    git(cfg, &args)?;
+    commit_staged(
+        cfg,
+        &format!("Start the history of {}", cfg.root_dir.display()),
+        &[],
+    )?;
+    Ok(true)
+}
+
+/// Commits the changes in `folder` with `message`, creating the repository first if
+/// needed, and returns the new revision, none if nothing changed
+pub fn commit(cfg: &Config, folder: &Path, message: &str) -> Result<Option<String>> {
+    start(cfg, Some(folder))?;
+    let folder = folder.display().to_string();
+    git(cfg, &["add", "-A", "--", &folder])?;
+    if git(cfg, &["diff", "--cached", "--quiet", "--", &folder]).is_ok() {
+        return Ok(None);
+    }
+    commit_staged(cfg, message, &[Path::new(&folder)])?;
+    Ok(Some(
+        git(cfg, &["rev-parse", "--short", "HEAD"])?
+            .trim()
+            .to_string(),
+    ))
+}
+
+/// Points `name` at the last commit, moving it if it exists
+pub fn tag(cfg: &Config, name: &str) -> Result<()> {
+    git(cfg, &["tag", "-f", name, "HEAD"])?;
+    Ok(())
+}
+
+/// Every commit that touched `folder`, newest first
+pub fn log(cfg: &Config, folder: &Path) -> Result<Vec<Commit>> {
+    let folder = folder.display().to_string();
+    let out = git(
+        cfg,
+        &["log", "--format=%h%x09%aI%x09%D%x09%s", "--", &folder],
+    )?;
+    Ok(out
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, '\t');
+            let (rev, time, refs, subject) =
+                (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+            let tags = refs
+                .split(", ")
+                .filter_map(|r| r.strip_prefix("tag: "))
+                .map(String::from)
+                .collect();
+            Some(Commit {
+                rev: rev.to_string(),
+                time: time.to_string(),
+                subject: subject.to_string(),
+                tags,
+            })
+        })
+        .collect())
+}
+
+/// Puts the files of `folder` back the way they were at `rev` and commits that, except
+/// the `keep` files, like the metadata file which has to match the db
+///
+/// Files added to the folder after `rev` are left alone.
+pub fn restore(
+    cfg: &Config,
+    folder: &Path,
+    rev: &str,
+    keep: &[PathBuf],
+    message: &str,
+) -> Result<Option<String>> {
+    let verify = format!("{}^{{commit}}", rev);
+    if git(cfg, &["rev-parse", "--verify", "--quiet", &verify]).is_err() {
+        return Err(Error::Invalid(format!("Unknown revision {}", rev)));
+    }
+    let folder = folder.display().to_string();
+    if git(cfg, &["ls-tree", "-r", "--name-only", rev, &folder])?
+        .trim()
+        .is_empty()
+    {
+        return Err(Error::Invalid(format!(
+            "{} has no files of {}",
+            rev, folder
+        )));
+    }
+
+    let excluded: Vec<String> = keep
+        .iter()
+        .map(|p| format!(":(exclude){}", p.display()))
+        .collect();
+    let mut args = vec!["checkout", rev, "--", &folder];
+    args.extend(excluded.iter().map(String::as_str));
+    git(cfg, &args)?;
+    commit(cfg, Path::new(&folder), message)
+}
@@ -0,0 +1,111 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::Config,
+    error::{Error, Result},
+};
+
+// checked in this order when looking up a template by name
+pub const LETTER_EXTENSIONS: [&str; 3] = ["tex", "md", "txt"];
+
+/// Values substituted into a cover letter template, `{company}` etc
+pub struct LetterFields<'a> {
+    pub company: &'a str,
+    pub position: &'a str,
+    pub group: &'a str,
+    pub template: &'a str,
+    pub date: &'a str,
+    pub location: Option<&'a str>,
+}
+
+pub fn letter_template_dir(cfg: &Config) -> PathBuf {
+    cfg.root_dir
+        .join(
+            cfg.template_dir
+                .clone()
+                .unwrap_or(PathBuf::from("templates")),
+        )
+        .join(
+            cfg.letter_template_dir
+                .clone()
+                .unwrap_or(PathBuf::from("letters")),
+        )
+}
+
+/// Finds `<name>.tex`, `<name>.md` or `<name>.txt` in the letter template directory
+pub fn find_template(cfg: &Config, name: &str) -> Option<PathBuf> {
+    let dir = letter_template_dir(cfg);
+    LETTER_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(name).with_extension(ext))
+        .find(|path| path.is_file())
+}
+
+pub fn render(contents: &str, fields: &LetterFields) -> String {
+    contents
+        .replace("{company}", fields.company)
+        .replace("{position}", fields.position)
+        .replace("{group}", fields.group)
+        .replace("{template}", fields.template)
+        .replace("{date}", fields.date)
+        .replace("{location}", fields.location.unwrap_or(""))
+}
+
+/// Writes a cover letter into `folder_path` and returns its path
+///
+/// An explicitly requested template must exist. Without one, the default template is used
+/// if there is one, otherwise the letter is left empty. The letter takes the extension of
+/// its template.
+pub fn write_letter(
+    cfg: &Config,
+    folder_path: &Path,
+    file_name: &str,
+    template: Option<&str>,
+    fields: &LetterFields,
+) -> Result<PathBuf> {
+    let template_path = match template {
+        Some(name) => Some(
+            find_template(cfg, name)
+                .ok_or_else(|| Error::TemplateNotFound(letter_template_dir(cfg).join(name)))?,
+        ),
+        None => find_template(
+            cfg,
+            &cfg.default_letter_template
+                .clone()
+                .unwrap_or("default".to_string()),
+        ),
+    };
+    log::debug!("Cover letter template: {:?}", template_path);
+
+    let mut letter_path = folder_path.join(file_name);
+    let contents = match &template_path {
+        Some(path) => {
+            if let Some(ext) = path.extension() {
+                letter_path.set_extension(ext);
+            }
+            render(&fs::read_to_string(path)?, fields)
+        }
+        None => String::new(),
+    };
+
+    if letter_path.exists() {
+        return Err(Error::Invalid(format!(
+            "Cover letter already exists: {:?}",
+            letter_path
+        )));
+    }
+    fs::write(&letter_path, contents)?;
+    Ok(letter_path)
+}
+
+pub fn letter_file_name(cfg: &Config, company: &str, position: &str, date: &str) -> String {
+    cfg.cover_letter_name
+        .clone()
+        .unwrap_or("cover_letter.txt".to_string())
+        .replace("{company}", company)
+        .replace("{position}", position)
+        .replace("{date}", date)
+}
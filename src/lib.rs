@@ -0,0 +1,31 @@
+//! Resuman manages resumes written in LaTeX, one folder per application, with
+//! a metadata file next to each resume and an SQLite db indexing all of them.
+//!
+//! [`Resuman`] is the entry point, the `resuman` binary is a thin layer over it.
+
+pub mod backup;
+pub mod build;
+pub mod commands;
+pub mod company;
+pub mod config;
+pub mod duplicate;
+pub mod error;
+pub mod git;
+pub mod letter;
+pub mod location;
+pub mod manager;
+pub mod metadata;
+pub mod resume;
+pub mod sync;
+pub mod tag;
+pub mod url;
+pub mod utils;
+
+use sqlx::migrate::Migrator;
+
+pub use error::{Error, Result};
+pub use manager::Resuman;
+pub use resume::{Filter, MetaData, NewResume, Patch, Resume, ResumeUrl};
+
+// use CARGO_MANIFEST_DIR if relative path doesn't work
+pub static MIGRATOR: Migrator = sqlx::migrate!();
@@ -0,0 +1,583 @@
+// structured locations parsed from the free text one, e.g. "Remote - Canada" or
+// "NYC area (hybrid)", with offline tables of common cities, regions and countries
+
+use std::{fmt, str::FromStr};
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkMode {
+    Onsite,
+    Hybrid,
+    Remote,
+}
+
+impl WorkMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkMode::Onsite => "onsite",
+            WorkMode::Hybrid => "hybrid",
+            WorkMode::Remote => "remote",
+        }
+    }
+}
+
+impl fmt::Display for WorkMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WorkMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "onsite" | "on-site" | "office" | "in-office" => Ok(WorkMode::Onsite),
+            "hybrid" => Ok(WorkMode::Hybrid),
+            "remote" | "wfh" => Ok(WorkMode::Remote),
+            _ => Err(Error::Invalid(format!(
+                "Unknown work mode {}, use onsite, hybrid or remote",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub city: Option<String>,
+    pub region: Option<String>,  // state or province code, e.g. NY or ON
+    pub country: Option<String>, // ISO 3166 code, e.g. US or CA
+    pub work_mode: Option<WorkMode>,
+}
+
+// ISO code, then the names it goes by, lowercase
+const COUNTRIES: &[(&str, &[&str])] = &[
+    (
+        "US",
+        &[
+            "us",
+            "usa",
+            "u.s.",
+            "u.s.a.",
+            "united states",
+            "united states of america",
+            "america",
+        ],
+    ),
+    ("CA", &["canada"]),
+    ("MX", &["mexico"]),
+    ("BR", &["brazil", "brasil"]),
+    (
+        "GB",
+        &[
+            "uk",
+            "u.k.",
+            "united kingdom",
+            "great britain",
+            "britain",
+            "england",
+            "scotland",
+            "wales",
+        ],
+    ),
+    ("IE", &["ireland"]),
+    ("DE", &["germany", "deutschland"]),
+    ("FR", &["france"]),
+    ("NL", &["netherlands", "the netherlands", "holland"]),
+    ("BE", &["belgium"]),
+    ("CH", &["switzerland"]),
+    ("AT", &["austria"]),
+    ("ES", &["spain"]),
+    ("PT", &["portugal"]),
+    ("IT", &["italy"]),
+    ("PL", &["poland"]),
+    ("SE", &["sweden"]),
+    ("NO", &["norway"]),
+    ("DK", &["denmark"]),
+    ("FI", &["finland"]),
+    ("IL", &["israel"]),
+    ("AE", &["uae", "united arab emirates"]),
+    ("IN", &["india"]),
+    ("CN", &["china"]),
+    ("HK", &["hong kong"]),
+    ("TW", &["taiwan"]),
+    ("JP", &["japan"]),
+    ("KR", &["korea", "south korea"]),
+    ("SG", &["singapore"]),
+    ("AU", &["australia"]),
+    ("NZ", &["new zealand"]),
+];
+
+// code, country, name
+const REGIONS: &[(&str, &str, &str)] = &[
+    ("AL", "US", "alabama"),
+    ("AK", "US", "alaska"),
+    ("AZ", "US", "arizona"),
+    ("AR", "US", "arkansas"),
+    ("CA", "US", "california"),
+    ("CO", "US", "colorado"),
+    ("CT", "US", "connecticut"),
+    ("DE", "US", "delaware"),
+    ("DC", "US", "district of columbia"),
+    ("FL", "US", "florida"),
+    ("GA", "US", "georgia"),
+    ("HI", "US", "hawaii"),
+    ("ID", "US", "idaho"),
+    ("IL", "US", "illinois"),
+    ("IN", "US", "indiana"),
+    ("IA", "US", "iowa"),
+    ("KS", "US", "kansas"),
+    ("KY", "US", "kentucky"),
+    ("LA", "US", "louisiana"),
+    ("ME", "US", "maine"),
+    ("MD", "US", "maryland"),
+    ("MA", "US", "massachusetts"),
+    ("MI", "US", "michigan"),
+    ("MN", "US", "minnesota"),
+    ("MS", "US", "mississippi"),
+    ("MO", "US", "missouri"),
+    ("MT", "US", "montana"),
+    ("NE", "US", "nebraska"),
+    ("NV", "US", "nevada"),
+    ("NH", "US", "new hampshire"),
+    ("NJ", "US", "new jersey"),
+    ("NM", "US", "new mexico"),
+    ("NY", "US", "new york state"),
+    ("NC", "US", "north carolina"),
+    ("ND", "US", "north dakota"),
+    ("OH", "US", "ohio"),
+    ("OK", "US", "oklahoma"),
+    ("OR", "US", "oregon"),
+    ("PA", "US", "pennsylvania"),
+    ("RI", "US", "rhode island"),
+    ("SC", "US", "south carolina"),
+    ("SD", "US", "south dakota"),
+    ("TN", "US", "tennessee"),
+    ("TX", "US", "texas"),
+    ("UT", "US", "utah"),
+    ("VT", "US", "vermont"),
+    ("VA", "US", "virginia"),
+    ("WA", "US", "washington state"),
+    ("WV", "US", "west virginia"),
+    ("WI", "US", "wisconsin"),
+    ("WY", "US", "wyoming"),
+    ("AB", "CA", "alberta"),
+    ("BC", "CA", "british columbia"),
+    ("MB", "CA", "manitoba"),
+    ("NB", "CA", "new brunswick"),
+    ("NL", "CA", "newfoundland and labrador"),
+    ("NS", "CA", "nova scotia"),
+    ("ON", "CA", "ontario"),
+    ("PE", "CA", "prince edward island"),
+    ("QC", "CA", "quebec"),
+    ("SK", "CA", "saskatchewan"),
+];
+
+// name, region, country, then other names it goes by, lowercase
+// the first of several cities with a name wins unless a region or country says otherwise
+const CITIES: &[(&str, Option<&str>, &str, &[&str])] = &[
+    (
+        "New York",
+        Some("NY"),
+        "US",
+        &["nyc", "new york city", "manhattan", "brooklyn"],
+    ),
+    (
+        "San Francisco",
+        Some("CA"),
+        "US",
+        &["sf", "bay area", "san francisco bay area", "sf bay area"],
+    ),
+    ("Los Angeles", Some("CA"), "US", &["la"]),
+    ("San Jose", Some("CA"), "US", &[]),
+    ("San Diego", Some("CA"), "US", &[]),
+    ("Mountain View", Some("CA"), "US", &[]),
+    ("Palo Alto", Some("CA"), "US", &[]),
+    ("Menlo Park", Some("CA"), "US", &[]),
+    ("Sunnyvale", Some("CA"), "US", &[]),
+    ("Cupertino", Some("CA"), "US", &[]),
+    ("Seattle", Some("WA"), "US", &[]),
+    ("Redmond", Some("WA"), "US", &[]),
+    ("Bellevue", Some("WA"), "US", &[]),
+    ("Portland", Some("OR"), "US", &[]),
+    ("Boston", Some("MA"), "US", &[]),
+    ("Cambridge", Some("MA"), "US", &[]),
+    (
+        "Washington",
+        Some("DC"),
+        "US",
+        &["dc", "washington dc", "washington d.c."],
+    ),
+    ("Philadelphia", Some("PA"), "US", &["philly"]),
+    ("Pittsburgh", Some("PA"), "US", &[]),
+    ("Chicago", Some("IL"), "US", &[]),
+    ("Austin", Some("TX"), "US", &[]),
+    ("Dallas", Some("TX"), "US", &[]),
+    ("Houston", Some("TX"), "US", &[]),
+    ("Denver", Some("CO"), "US", &[]),
+    ("Boulder", Some("CO"), "US", &[]),
+    ("Atlanta", Some("GA"), "US", &[]),
+    ("Miami", Some("FL"), "US", &[]),
+    ("Salt Lake City", Some("UT"), "US", &["slc"]),
+    (
+        "Toronto",
+        Some("ON"),
+        "CA",
+        &["gta", "greater toronto area"],
+    ),
+    ("Waterloo", Some("ON"), "CA", &["kitchener-waterloo", "kw"]),
+    ("Ottawa", Some("ON"), "CA", &[]),
+    ("Montreal", Some("QC"), "CA", &["montréal"]),
+    ("Vancouver", Some("BC"), "CA", &[]),
+    ("Calgary", Some("AB"), "CA", &[]),
+    ("Edmonton", Some("AB"), "CA", &[]),
+    ("London", None, "GB", &[]),
+    ("Cambridge", None, "GB", &[]),
+    ("Edinburgh", None, "GB", &[]),
+    ("Dublin", None, "IE", &[]),
+    ("Berlin", None, "DE", &[]),
+    ("Munich", None, "DE", &["münchen", "muenchen"]),
+    ("Paris", None, "FR", &[]),
+    ("Amsterdam", None, "NL", &[]),
+    ("Zurich", None, "CH", &["zürich"]),
+    ("Stockholm", None, "SE", &[]),
+    ("Madrid", None, "ES", &[]),
+    ("Barcelona", None, "ES", &[]),
+    ("Tel Aviv", None, "IL", &[]),
+    ("Bangalore", None, "IN", &["bengaluru"]),
+    ("Hyderabad", None, "IN", &[]),
+    ("Tokyo", None, "JP", &[]),
+    ("Seoul", None, "KR", &[]),
+    ("Singapore", None, "SG", &[]),
+    ("Hong Kong", None, "HK", &[]),
+    ("Sydney", None, "AU", &[]),
+    ("Melbourne", None, "AU", &[]),
+    ("Sao Paulo", None, "BR", &["são paulo"]),
+];
+
+// words around the place that say nothing about it
+const FILLER: &[&str] = &[
+    "in", "based", "only", "area", "greater", "metro", "region", "or", "and",
+];
+
+/// The ISO code of a country code or name
+pub fn country(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_lowercase();
+    COUNTRIES
+        .iter()
+        .find(|(code, names)| code.eq_ignore_ascii_case(&name) || names.contains(&name.as_str()))
+        .map(|(code, _)| *code)
+}
+
+/// The code and country of a state or province code or name
+pub fn region(name: &str) -> Option<(&'static str, &'static str)> {
+    let name = name.trim().to_lowercase();
+    REGIONS
+        .iter()
+        .find(|(code, _, full)| {
+            code.eq_ignore_ascii_case(&name)
+                || *full == name
+                || full.strip_suffix(" state") == Some(name.as_str())
+        })
+        .map(|(code, country, _)| (*code, *country))
+}
+
+/// The canonical name of a known city, or `name` itself
+pub fn city(name: &str) -> String {
+    cities(name)
+        .first()
+        .map_or(name.trim().to_string(), |c| c.0.to_string())
+}
+
+fn cities(
+    name: &str,
+) -> Vec<&'static (
+    &'static str,
+    Option<&'static str>,
+    &'static str,
+    &'static [&'static str],
+)> {
+    let name = name.trim().to_lowercase();
+    CITIES
+        .iter()
+        .filter(|(city, _, _, aliases)| {
+            city.to_lowercase() == name || aliases.contains(&name.as_str())
+        })
+        .collect()
+}
+
+/// Removes `phrase` wherever it stands as whole words, returns whether it was there
+fn take_phrase(text: &mut String, phrase: &str) -> bool {
+    let boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric());
+    let mut found = false;
+    let mut from = 0;
+    while let Some(i) = text[from..].find(phrase).map(|i| i + from) {
+        let end = i + phrase.len();
+        if boundary(text[..i].chars().last()) && boundary(text[end..].chars().next()) {
+            text.replace_range(i..end, " ");
+            found = true;
+            from = i + 1;
+        } else {
+            from = end;
+        }
+    }
+    found
+}
+
+/// Parses a free text location, what is not recognized is left out
+///
+/// Two letter codes are regions when they follow a city, and countries on their
+/// own, so "San Jose, CA" is in California and "Remote, CA" in Canada.
+pub fn parse(input: &str) -> Location {
+    let mut text = input.to_lowercase();
+    let mut work_mode = None;
+    // later ones win, "remote or hybrid" is hybrid
+    for (mode, phrases) in [
+        (
+            WorkMode::Onsite,
+            &["on-site", "onsite", "on site", "in-office", "in office"][..],
+        ),
+        (
+            WorkMode::Remote,
+            &["remote", "wfh", "work from home", "anywhere", "worldwide"][..],
+        ),
+        (WorkMode::Hybrid, &["hybrid"][..]),
+    ] {
+        for phrase in phrases {
+            if take_phrase(&mut text, phrase) {
+                work_mode = Some(mode);
+            }
+        }
+    }
+
+    let mut parts: Vec<String> = Vec::new();
+    for part in text
+        .split([',', '/', '(', ')', '|', ';', ':'])
+        .flat_map(|p| p.split(" - "))
+    {
+        let all: Vec<&str> = part
+            .trim_matches(|c: char| c == '-' || c.is_whitespace())
+            .split_whitespace()
+            .collect();
+        let words: Vec<&str> = all
+            .iter()
+            .copied()
+            .filter(|w| !FILLER.contains(w))
+            .collect();
+        let known = |p: &str| !cities(p).is_empty() || region(p).is_some() || country(p).is_some();
+        match words.split_last() {
+            // names with filler in them, like "Bay Area"
+            _ if all.len() > 1 && known(&all.join(" ")) => parts.push(all.join(" ")),
+            None => continue,
+            // "Vancouver BC" without a comma
+            Some((last, rest)) if !rest.is_empty() && !known(&words.join(" ")) && known(last) => {
+                parts.push(rest.join(" "));
+                parts.push(last.to_string());
+            }
+            Some(_) => parts.push(words.join(" ")),
+        }
+    }
+    // "IN" alone is India, not filler
+    if parts.is_empty() && work_mode.is_none() {
+        parts.extend(
+            text.split(|c: char| !c.is_alphanumeric())
+                .filter(|w| !w.is_empty())
+                .map(str::to_string)
+                .take(1),
+        );
+    }
+
+    let mut location = Location {
+        work_mode,
+        ..Default::default()
+    };
+    let mut unknown = None;
+    let mut city_part = None;
+    for (i, part) in parts.iter().enumerate() {
+        // a city leads, so "LA" on its own is Los Angeles and not Louisiana
+        if i == 0 && !cities(part).is_empty() {
+            city_part = Some(part);
+        } else if let Some((code, country)) =
+            region(part).filter(|_| i > 0 || country(part).is_none())
+        {
+            location.region.get_or_insert(code.to_string());
+            location.country.get_or_insert(country.to_string());
+        } else if let Some(code) = country(part) {
+            location.country = Some(code.to_string());
+        } else if !cities(part).is_empty() {
+            city_part.get_or_insert(part);
+        } else if i == 0 {
+            unknown = Some(part);
+        }
+    }
+
+    if let Some(part) = city_part {
+        let candidates = cities(part);
+        let (name, region, country, _) = candidates
+            .iter()
+            .find(|(_, region, country, _)| match &location.region {
+                Some(r) => Some(r.as_str()) == *region,
+                None => location.country.as_deref().is_none_or(|c| c == *country),
+            })
+            .unwrap_or(&candidates[0]);
+        location.city = Some(name.to_string());
+        location.region = location.region.take().or(region.map(|r| r.to_string()));
+        location.country = Some(location.country.take().unwrap_or(country.to_string()));
+    } else if let Some(part) =
+        unknown.filter(|_| location.region.is_some() || location.country.is_some())
+    {
+        // an unknown city is kept as written when the rest places it
+        let start = input.to_lowercase().find(part.as_str());
+        location.city = Some(
+            start
+                .and_then(|i| input.get(i..i + part.len()))
+                .unwrap_or(part)
+                .to_string(),
+        );
+    }
+    location
+}
+
+/// The migration that added the structured location columns
+pub const LOCATION_MIGRATION: i64 = 20250222120000;
+
+/// Fills the structured location of resumes from before the columns existed, migrate
+/// runs it once right after [`LOCATION_MIGRATION`]
+pub async fn backfill(pool: &SqlitePool) -> Result<()> {
+    let rows = sqlx::query!(
+        r#"
+SELECT resume_id, location AS "location!: String" FROM metadata
+WHERE location IS NOT NULL AND location != ''
+    AND city IS NULL AND region IS NULL AND country IS NULL AND work_mode IS NULL
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    for row in rows {
+        set(pool, row.resume_id, Some(&row.location)).await?;
+    }
+    Ok(())
+}
+
+/// Stores the structured fields parsed from the location of a resume
+pub async fn set(pool: &SqlitePool, resume_id: i64, location: Option<&str>) -> Result<Location> {
+    let parsed = location.map(parse).unwrap_or_default();
+    let work_mode = parsed.work_mode.map(|m| m.as_str());
+    sqlx::query!(
+        "UPDATE metadata SET city = ?, region = ?, country = ?, work_mode = ? WHERE resume_id = ?",
+        parsed.city,
+        parsed.region,
+        parsed.country,
+        work_mode,
+        resume_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(
+        city: Option<&str>,
+        region: Option<&str>,
+        country: Option<&str>,
+        work_mode: Option<WorkMode>,
+    ) -> Location {
+        Location {
+            city: city.map(str::to_string),
+            region: region.map(str::to_string),
+            country: country.map(str::to_string),
+            work_mode,
+        }
+    }
+
+    #[test]
+    fn two_letter_codes_are_countries_alone_and_regions_after_a_city() {
+        assert_eq!(parse("CA"), location(None, None, Some("CA"), None));
+        assert_eq!(
+            parse("San Jose, CA"),
+            location(Some("San Jose"), Some("CA"), Some("US"), None)
+        );
+        assert_eq!(
+            parse("Remote, CA"),
+            location(None, None, Some("CA"), Some(WorkMode::Remote))
+        );
+        assert_eq!(
+            parse("LA"),
+            location(Some("Los Angeles"), Some("CA"), Some("US"), None)
+        );
+        assert_eq!(parse("IN"), location(None, None, Some("IN"), None));
+    }
+
+    #[test]
+    fn cities_take_their_region_from_the_table_or_the_input() {
+        let waterloo = location(Some("Waterloo"), Some("ON"), Some("CA"), None);
+        assert_eq!(parse("Waterloo"), waterloo);
+        assert_eq!(parse("Waterloo, ON"), waterloo);
+        assert_eq!(parse("waterloo ontario"), waterloo);
+        assert_eq!(
+            parse("Cambridge, UK"),
+            location(Some("Cambridge"), None, Some("GB"), None)
+        );
+        assert_eq!(
+            parse("Cambridge"),
+            location(Some("Cambridge"), Some("MA"), Some("US"), None)
+        );
+        assert_eq!(
+            parse("Vancouver BC"),
+            location(Some("Vancouver"), Some("BC"), Some("CA"), None)
+        );
+    }
+
+    #[test]
+    fn work_modes_are_taken_out_of_the_place() {
+        assert_eq!(
+            parse("Remote - Canada"),
+            location(None, None, Some("CA"), Some(WorkMode::Remote))
+        );
+        let nyc = location(
+            Some("New York"),
+            Some("NY"),
+            Some("US"),
+            Some(WorkMode::Hybrid),
+        );
+        assert_eq!(parse("NYC area/hybrid"), nyc);
+        assert_eq!(parse("NYC area (hybrid)"), nyc);
+        assert_eq!(
+            parse("Bay Area, remote or hybrid"),
+            location(
+                Some("San Francisco"),
+                Some("CA"),
+                Some("US"),
+                Some(WorkMode::Hybrid)
+            )
+        );
+        assert_eq!(
+            parse("On-site"),
+            location(None, None, None, Some(WorkMode::Onsite))
+        );
+    }
+
+    #[test]
+    fn unknown_places_are_left_out_or_kept_as_written() {
+        assert_eq!(parse("Somewhere odd"), Location::default());
+        assert_eq!(parse(""), Location::default());
+        assert_eq!(
+            parse("Smallville, Ontario"),
+            location(Some("Smallville"), Some("ON"), Some("CA"), None)
+        );
+        assert_eq!(
+            parse("Kleinstadt, Germany (hybrid)"),
+            location(Some("Kleinstadt"), None, Some("DE"), Some(WorkMode::Hybrid))
+        );
+    }
+}
@@ -1,36 +1,87 @@
-mod commands;
-mod config;
-mod error;
-mod resume;
-mod utils;
+use std::ffi::OsString;
 
-use std::{fs, path::PathBuf, str::FromStr};
+use clap::{CommandFactory, Parser};
 
-use clap::Parser;
-
-use commands::{create, delete, find, list, update, Cli, Commands};
+use resuman::{
+    commands::{
+        backup, build, company, config, create, db, delete, diff, dupes, edit, find, history, init,
+        lineage, list, metadata, offer, open, profile, restore, serve, stats, submit, tag, tui,
+        update, url, watch, Cli, Commands,
+    },
+    utils, Error, Resuman,
+};
 
 use dotenv::dotenv;
 use env_logger::Env;
 
-use sqlx::{
-    migrate::Migrator,
-    sqlite::{SqliteConnectOptions, SqliteJournalMode},
-    Pool,
-};
-
-// use CARGO_MANIFEST_DIR if relative path doesn't work
-static MIGRATOR: Migrator = sqlx::migrate!();
-
 #[tokio::main(flavor = "current_thread")]
-async fn main() -> anyhow::Result<()> {
+async fn main() {
     dotenv().ok();
 
     let env = Env::default();
     env_logger::init_from_env(env);
 
-    let cli = Cli::parse();
+    let cli = parse_cli();
+
+    // every kind of error exits with its own code, so scripts can branch on it
+    if let Err(e) = run(cli).await {
+        let e = Error::from(e);
+        eprintln!("Error: {}", e);
+        if let Some(hint) = e.hint() {
+            eprintln!("Hint: {}", hint);
+        }
+        std::process::exit(e.exit_code());
+    }
+}
+
+/// `edit` used to be an alias of `update`, so arguments only `update` takes still go there
+fn parse_cli() -> Cli {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let err = match Cli::try_parse_from(&args) {
+        Ok(cli) => return cli,
+        Err(e) => e,
+    };
+    if let Some(i) = subcommand_index(&args).filter(|i| args[*i] == "edit") {
+        let mut forwarded = args.clone();
+        forwarded[i] = "update".into();
+        if let Ok(cli) = Cli::try_parse_from(&forwarded) {
+            eprintln!(
+                "Warning: `resuman edit` now opens the metadata in $EDITOR, use `resuman update` to change fields with flags"
+            );
+            return cli;
+        }
+    }
+    err.exit()
+}
+
+/// Where the subcommand is in `args`, the first one after the global options and their
+/// values, so `--profile edit` is not taken for it
+fn subcommand_index(args: &[OsString]) -> Option<usize> {
+    let cli = Cli::command();
+    // `--set k=v` and `-c FILE` take the next argument, `--set=k=v` and `-cFILE` do not
+    let takes_next = |a: &str| {
+        cli.get_arguments()
+            .filter(|arg| arg.get_action().takes_values())
+            .any(|arg| match a.strip_prefix("--") {
+                Some(long) => arg.get_long() == Some(long),
+                None => a.len() == 2 && arg.get_short() == a.chars().nth(1),
+            })
+    };
+    let mut i = 1;
+    while i < args.len() {
+        let a = args[i].to_string_lossy();
+        if !a.starts_with('-') {
+            return Some(i);
+        }
+        if takes_next(&a) {
+            i += 1;
+        }
+        i += 1;
+    }
+    None
+}
 
+async fn run(cli: Cli) -> anyhow::Result<()> {
     if cli.markdown_help {
         clap_markdown::print_help_markdown::<Cli>();
         return Ok(());
@@ -38,114 +89,104 @@ async fn main() -> anyhow::Result<()> {
 
     // switch to .clone if lifetime issues
     let config_path = cli.config;
+    let profile = cli.profile.as_deref();
 
-    let mut config = utils::load_config(config_path.clone()).unwrap();
+    // these work on the file itself, which may not even parse, and init creates it
+    match &cli.command {
+        Some(Commands::Config(args)) => {
+            return config::execute(config_path, profile, &cli.set, args)
+        }
+        Some(Commands::Profile(args)) => return profile::execute(config_path, profile, args),
+        Some(Commands::Init(args)) => {
+            return init::execute(config_path, profile, &cli.set, args).await
+        }
+        _ => {}
+    }
 
-    log::debug!("Config: {:?}", config);
+    let data = utils::load_config(config_path.clone(), &cli.set)?;
 
-    let root_dir = utils::resolve_path(config.main.root_dir.clone()).unwrap();
-    log::debug!("Root dir: {:?}", root_dir);
+    log::debug!("Config: {:?}", data);
 
-    // since it needs to be passed to the commands
-    config.main.root_dir = root_dir.clone();
-
-    let binding = root_dir.join(
-        config
-            .main
-            .db_path
-            .clone()
-            .unwrap_or(PathBuf::from("resumandb.sqlite")),
-    );
-    let db_path = binding.to_str().unwrap();
-
-    fs::create_dir_all(&root_dir).unwrap();
-
-    log::debug!("Db path: {:?}", db_path);
-
-    // if !Sqlite::database_exists(db_path).await.unwrap_or(false) {
-    //     log::debug!("Creating database {}", db_path);
-    //     match Sqlite::create_database(db_path).await {
-    //         Ok(_) => log::debug!("Database created at {}", db_path),
-    //         Err(e) => log::error!("Error creating database: {}", e),
-    //     }
-    // } else {
-    //     log::debug!("Database exists at {}, connecting.", db_path);
-    // }
-
-    // use sqlite options and create_if_missing
-    let pool = Pool::connect_with(
-        SqliteConnectOptions::from_str(db_path)?
-            .journal_mode(SqliteJournalMode::Off)
-            .create_if_missing(true),
-    )
-    .await?;
-
-    // let crate_dir = env!("CARGO_MANIFEST_DIR");
-    // let migrations = Path::new(&crate_dir).join("./migrations");
-    // let migration_results = Migrator::new(migrations).await.unwrap().run(&pool).await;
-    let migration_results = MIGRATOR.run(&pool).await;
-
-    match migration_results {
-        Ok(_) => log::debug!("Migrations ran successfully"),
-        Err(ref e) => log::error!("Error running migrations: {}", e),
+    // the db there may be damaged, so restore must not open it
+    if let Some(Commands::Restore(args)) = &cli.command {
+        let cfg = data.profile(profile)?;
+        return restore::execute(cfg, args, !cli.no_auto_migrate).await;
     }
 
-    log::debug!("migration results: {:?}", migration_results);
+    // resolves root_dir, creates the db and template directories and runs migrations,
+    // db takes care of the migrations itself
+    let cfg = data.profile(profile)?;
+    let resuman = if cli.no_auto_migrate || matches!(cli.command, Some(Commands::Db(_))) {
+        Resuman::connect(cfg).await?
+    } else {
+        Resuman::open(cfg).await?
+    };
 
-    // initialize template directory
-    let template_dir = root_dir.join(
-        config
-            .main
-            .template_dir
-            .clone()
-            .unwrap_or(PathBuf::from("templates")),
-    );
-    fs::create_dir_all(&template_dir).unwrap();
+    // since it needs to be passed to the commands
+    let config = resuman.config().clone();
+    let pool = resuman.pool().clone();
+
+    if cli.no_auto_migrate {
+        let pending = resuman::backup::pending_versions(&pool).await?;
+        if !pending.is_empty() {
+            eprintln!(
+                "The db misses {} migration(s), apply them with `resuman db migrate`",
+                pending.len()
+            );
+        }
+    }
+
+    // a reminder should never stop the actual command from running
+    if let Err(e) = offer::remind_deadlines(&pool, config.offer_reminder_days.unwrap_or(3)).await {
+        log::warn!("Could not check offer deadlines: {}", e);
+    }
 
-    // let file_path = format!("{}/test.txt", root_dir.display());
-    // utils::save_to_file(&file_path, "Hello, world!", Some(true)).unwrap();
+    // only the resumes the command touches, hashing every frozen file each time is slow
+    let ids = cli
+        .command
+        .as_ref()
+        .map(Commands::resume_ids)
+        .unwrap_or_default();
+    if let Err(e) = submit::warn_modified(&config, &pool, &ids).await {
+        log::warn!("Could not check the submitted files: {}", e);
+    }
 
     match cli.command {
-        Some(Commands::Create(args)) => create::execute(config.main.clone(), &args, &pool)
-            .await
-            .map_err(|e| e.into()),
-        Some(Commands::Update(args)) => update::execute(config.main.clone(), &args, &pool)
-            .await
-            .map_err(|e| e.into()),
-        Some(Commands::List(args)) => list::execute(config.main.clone(), &args, &pool)
-            .await
-            .map_err(|e| e.into()),
-        Some(Commands::Init(_)) => {
-            println!(
-                "Successfully initialized resuman!\nEdit your templates at {:?}",
-                template_dir
-            );
-            return Ok(()); // exit early
-        }
-        Some(Commands::Delete(args)) => delete::execute(config.main.clone(), &args, &pool)
-            .await
-            .map_err(|e| e.into()),
-        Some(Commands::Find(args)) => find::execute(config.main.clone(), &args, &pool)
-            .await
-            .map_err(|e| e.into()),
+        Some(Commands::Create(args)) => create::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Update(args)) => update::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Edit(args)) => edit::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Watch(args)) => watch::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Log(args)) => history::log(config.clone(), &args, &pool).await,
+        Some(Commands::Checkout(args)) => history::checkout(config.clone(), &args, &pool).await,
+        Some(Commands::Build(args)) => build::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Submit(args)) => submit::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Diff(args)) => diff::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Lineage(args)) => lineage::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Dupes(args)) => dupes::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Company(args)) => company::execute(config.clone(), &args, &pool).await,
+        Some(Commands::List(args)) => list::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Delete(args)) => delete::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Find(args)) => find::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Url(args)) => url::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Open(args)) => open::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Tag(args)) => tag::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Offer(args)) => offer::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Tui(args)) => tui::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Serve(args)) => serve::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Stats(args)) => stats::execute(config.clone(), &args, &pool, &data).await,
+        Some(Commands::Backup(args)) => backup::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Db(args)) => db::execute(config.clone(), &args, &pool).await,
+        Some(Commands::Metadata(args)) => metadata::execute(config.clone(), &args, &pool).await,
         Some(Commands::Clean(_)) => {
-            // go through every entry in the db and check if the file exists
-            // if it doesn't, remove the entry
-            let resumes = sqlx::query!("SELECT * FROM resumes")
-                .fetch_all(&pool)
-                .await?;
-
-            for r in resumes {
-                let path = r.file_path.clone();
-                if !PathBuf::from(&path).exists() {
-                    sqlx::query!("DELETE FROM resumes WHERE id = ?", r.id)
-                        .execute(&pool)
-                        .await?;
-                }
+            for id in resuman.clean().await? {
+                log::info!("Removed resume {}, its file is gone", id);
             }
             Ok(())
         }
         // unreachable because of arg_required_else_help = true
-        _ => Ok(eprintln!("Invalid subcommand or arguments")),
+        _ => {
+            eprintln!("Invalid subcommand or arguments");
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,1195 @@
+// the library entry point, every command that touches resumes goes through here
+// so the files, the metadata json and the db stay in sync
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::{
+    backup, build, company,
+    config::Config,
+    duplicate,
+    error::{Error, Result},
+    git,
+    letter::{self, LetterFields},
+    location,
+    metadata::{self, Metadata},
+    resume::{Filter, MetaData, NewResume, Patch, Resume},
+    tag, url, utils, MIGRATOR,
+};
+
+/// A handle on a resuman root directory and its db
+///
+/// ```no_run
+/// # async fn run(config: resuman::config::Config) -> resuman::Result<()> {
+/// use resuman::{NewResume, Patch, Resuman};
+///
+/// let resuman = Resuman::open(config).await?;
+/// let resume = resuman
+///     .create(NewResume {
+///         company: "Acme".to_string(),
+///         ..Default::default()
+///     })
+///     .await?;
+/// resuman
+///     .update(
+///         resume.id,
+///         Patch {
+///             status: Some("applied".to_string()),
+///             ..Default::default()
+///         },
+///     )
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Resuman {
+    cfg: Config,
+    pool: SqlitePool,
+}
+
+impl From<&Patch> for Metadata {
+    fn from(patch: &Patch) -> Self {
+        Metadata {
+            company: patch.company.clone(),
+            group: patch.group.clone(),
+            template: patch.template.clone(),
+            copied_from: patch
+                .copied_from
+                .as_ref()
+                .map(|p| p.to_string_lossy().into_owned()),
+            position: patch.position.clone(),
+            has_cover_letter: patch.has_cover_letter,
+            cover_letter_path: None, // only set when a letter is generated
+            created_at: patch.created_at,
+            applied_time: patch.applied_time,
+            length: patch.length,
+            location: patch.location.clone(),
+            status: patch.status.clone(),
+            urls: None,
+            tags: None,
+            notes: patch.notes.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+enum SqlParam<'a> {
+    String(&'a Option<String>),
+    Bool(&'a Option<bool>),
+    DateTime(&'a Option<DateTime<Utc>>),
+    U16(&'a Option<u16>),
+    I64(&'a Option<i64>),
+}
+
+impl<'a> SqlParam<'a> {
+    fn bind_to_query(
+        self,
+        query: sqlx::query::Query<'a, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'a>>,
+    ) -> sqlx::query::Query<'a, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'a>> {
+        match self {
+            SqlParam::String(val) => query.bind(val.as_deref()),
+            SqlParam::Bool(val) => query.bind(val),
+            SqlParam::DateTime(val) => query.bind(val.map(|dt| dt.naive_utc())),
+            SqlParam::U16(val) => query.bind(val.map(|v| v as i64)), // SQLite expects i64
+            SqlParam::I64(val) => query.bind(val),
+        }
+    }
+}
+
+/// A cover letter written by an update that has not reached the db yet
+struct NewLetter(Option<PathBuf>);
+
+impl NewLetter {
+    fn keep(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for NewLetter {
+    fn drop(&mut self) {
+        if let Some(path) = self.0.take() {
+            log::debug!("Removing {:?}, the update failed", path);
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl Resuman {
+    /// Opens the db under `root_dir`, creating the directories and the db if missing,
+    /// and runs the migrations
+    pub async fn open(cfg: Config) -> Result<Self> {
+        let resuman = Resuman::connect(cfg).await?;
+        resuman.migrate().await?;
+        Ok(resuman)
+    }
+
+    /// Like [`Resuman::open`], but leaves the schema of the db as it is
+    pub async fn connect(mut cfg: Config) -> Result<Self> {
+        cfg.root_dir = utils::resolve_path(cfg.root_dir, None)?;
+        log::debug!("Root dir: {:?}", cfg.root_dir);
+
+        let db_path = db_path(&cfg);
+        fs::create_dir_all(&cfg.root_dir)?;
+        log::debug!("Db path: {:?}", db_path);
+
+        let pool = backup::connect(&cfg, &db_path).await?;
+
+        // initialize template directories
+        fs::create_dir_all(template_dir(&cfg))?;
+        fs::create_dir_all(letter::letter_template_dir(&cfg))?;
+
+        Ok(Resuman { cfg, pool })
+    }
+
+    /// Runs the pending migrations, after backing up a db that has any
+    ///
+    /// Each migration runs in a transaction, so a failed one leaves the db at the
+    /// last one that worked.
+    pub async fn migrate(&self) -> Result<()> {
+        // a migration that goes wrong should never take the only copy with it
+        if backup::needs_migration(&self.pool).await? {
+            let path = backup::backup(&self.pool, &self.cfg, Some("pre-migration")).await?;
+            log::info!("Backed up the db to {} before migrating", path.display());
+        }
+
+        let pending = backup::pending_versions(&self.pool).await?;
+        MIGRATOR.run(&self.pool).await.map_err(Error::Migration)?;
+        log::debug!("Migrations ran successfully");
+        // the locations from before the columns are parsed once, one that yields
+        // nothing would otherwise be parsed again on every open
+        if pending.contains(&location::LOCATION_MIGRATION) {
+            location::backfill(&self.pool).await?;
+        }
+        // the urls moved out of the metadata table, the files still hold the old string
+        if pending.contains(&url::URLS_MIGRATION) {
+            url::backfill_metadata(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// Wraps an already open and migrated pool, `cfg.root_dir` must already be resolved
+    pub fn from_pool(cfg: Config, pool: SqlitePool) -> Self {
+        Resuman { cfg, pool }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.cfg
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Every resume, ordered by id
+    pub async fn list(&self) -> Result<Vec<Resume>> {
+        self.load(None).await
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Resume> {
+        self.load(Some(&[id]))
+            .await?
+            .pop()
+            .ok_or(Error::ResumeNotFound(id))
+    }
+
+    /// Every resume matching the filter, ordered by id
+    pub async fn find(&self, filter: &Filter) -> Result<Vec<Resume>> {
+        let ids = self.find_ids(filter).await?;
+        self.load(Some(&ids)).await
+    }
+
+    /// Like [`Resuman::find`], but only the ids
+    pub async fn find_ids(&self, filter: &Filter) -> Result<Vec<i64>> {
+        if let Some(a) = &filter.all {
+            // filter every field with a
+            // doesn't work with bools
+            let pattern = format!("%{}%", a);
+            let resumes = sqlx::query!(
+                "SELECT id FROM resumes
+    JOIN metadata ON metadata.resume_id = resumes.id
+    WHERE company LIKE ?
+        OR \"group\" LIKE ?
+        OR template LIKE ?
+        OR position LIKE ?
+        OR created_at LIKE ?
+        OR applied_time LIKE ?
+        OR length = ?
+        OR location LIKE ?
+        OR status LIKE ?
+        OR file_path LIKE ?
+        OR copied_from LIKE ?",
+                pattern,
+                pattern,
+                pattern,
+                pattern,
+                pattern,
+                pattern,
+                a,
+                pattern,
+                pattern,
+                pattern,
+                pattern
+            )
+            .fetch_all(&self.pool)
+            .await?;
+            return Ok(resumes.into_iter().map(|r| r.id).collect());
+        }
+
+        // each filter should be AND not OR
+        // values are bound, never formatted into the sql, since `serve` passes them through from requests
+
+        let mut query: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT id FROM resumes JOIN metadata ON metadata.resume_id = resumes.id WHERE 1 = 1",
+        );
+
+        let like = [
+            ("company", &filter.company),
+            ("\"group\"", &filter.group),
+            ("template", &filter.template),
+            ("position", &filter.position),
+            ("created_at", &filter.created_at),
+            ("applied_time", &filter.applied_time),
+            ("location", &filter.location),
+            ("status", &filter.status),
+            ("file_path", &filter.file_path),
+            ("copied_from", &filter.copied_from),
+        ];
+        for (column, value) in like {
+            if let Some(v) = value {
+                query.push(format!(" AND {} LIKE ", column));
+                query.push_bind(format!("%{}%", v));
+            }
+        }
+
+        if let Some(c) = &filter.city {
+            query.push(" AND city = ").push_bind(location::city(c));
+            query.push(" COLLATE NOCASE");
+        }
+        if let Some(r) = &filter.region {
+            let code = location::region(r).map_or(r.to_uppercase(), |(code, _)| code.to_string());
+            query.push(" AND region = ").push_bind(code);
+        }
+        if let Some(c) = &filter.country {
+            let code = location::country(c).map_or(c.to_uppercase(), |code| code.to_string());
+            query.push(" AND country = ").push_bind(code);
+        }
+        if let Some(m) = &filter.work_mode {
+            let mode = m.parse::<location::WorkMode>()?;
+            query.push(" AND work_mode = ").push_bind(mode.as_str());
+        }
+
+        if let Some(h) = filter.has_cover_letter {
+            query.push(" AND has_cover_letter = ").push_bind(h);
+        }
+
+        if let Some(l) = filter.length {
+            query.push(" AND length = ").push_bind(l);
+        }
+
+        for t in &filter.tags {
+            query.push(
+                " AND id IN (SELECT resume_id FROM resume_tags JOIN tags ON tags.id = resume_tags.tag_id WHERE tags.name = ",
+            );
+            query.push_bind(tag::normalize_tag(t)?).push(")");
+        }
+        query.push(" ORDER BY id");
+
+        let resumes = query
+            .build_query_as::<(i64,)>()
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(resumes.into_iter().map(|r| r.0).collect())
+    }
+
+    /// Resumes with the given ids, or every resume
+    async fn load(&self, ids: Option<&[i64]>) -> Result<Vec<Resume>> {
+        // passed as a json array so a single prepared query handles any number of ids
+        let ids = ids.map(|ids| json!(ids).to_string());
+        let records = sqlx::query!(
+            r#"
+SELECT resumes.id, company, "group", template, position,
+    created_at AS "created_at: DateTime<Utc>", has_cover_letter, cover_letter_path, file_path,
+    applied_time AS "applied_time: DateTime<Utc>", length, location, city, region, country,
+    work_mode, status, notes, copied_from, copied_from_id, metadata_file_path,
+    (SELECT json_group_array(name) FROM (
+        SELECT tags.name FROM tags JOIN resume_tags ON resume_tags.tag_id = tags.id
+        WHERE resume_tags.resume_id = resumes.id ORDER BY tags.name
+    )) AS "tags!: String",
+    (SELECT json_group_array(json_object('id', id, 'kind', kind, 'label', label, 'url', url))
+        FROM resume_urls WHERE resume_urls.resume_id = resumes.id) AS "urls!: String"
+FROM resumes JOIN metadata ON metadata.resume_id = resumes.id
+WHERE ?1 IS NULL OR resumes.id IN (SELECT value FROM json_each(?1))
+ORDER BY resumes.id
+            "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        records
+            .into_iter()
+            .map(|r| {
+                Ok(Resume {
+                    id: r.id,
+                    company: r.company,
+                    group: r.group,
+                    template: r.template,
+                    position: r.position,
+                    created_at: r.created_at,
+                    has_cover_letter: r.has_cover_letter,
+                    cover_letter_path: r.cover_letter_path,
+                    file_path: r.file_path,
+                    tags: serde_json::from_str(&r.tags)?,
+                    metadata: MetaData {
+                        applied_time: r.applied_time,
+                        length: r.length,
+                        location: r.location,
+                        city: r.city,
+                        region: r.region,
+                        country: r.country,
+                        work_mode: r.work_mode,
+                        status: r.status,
+                        urls: serde_json::from_str(&r.urls)?,
+                        notes: r.notes,
+                        copied_from: r.copied_from,
+                        copied_from_id: r.copied_from_id,
+                        metadata_file_path: r.metadata_file_path,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Creates the resume folder, its files and db rows
+    pub async fn create(&self, new: NewResume) -> Result<Resume> {
+        // sqlite double query: insert into resumes AND metadata
+        // use a CTE to insert into resumes first, then insert into metadata
+
+        log::debug!("Creating:\n{:#?}", new);
+        let cfg = &self.cfg;
+        let pool = &self.pool;
+
+        let company_name = new.company.clone();
+        if company_name.trim().is_empty() {
+            return Err(Error::Invalid("Company must be specified".to_string()));
+        }
+
+        let group = new
+            .group
+            .clone()
+            .unwrap_or(cfg.default_group.clone().unwrap_or("default".to_string()));
+        let template = new.template.clone().unwrap_or(
+            cfg.default_template
+                .clone()
+                .unwrap_or("default".to_string()),
+        );
+        let copy_file = new.copy_from.clone(); // Option<PathBuf>
+        let has_cover_letter = new.has_cover_letter || new.letter_template.is_some();
+        let position = new
+            .position
+            .clone()
+            .unwrap_or(cfg.default_position.clone().unwrap_or("swe".to_string()))
+            .replace(" ", "-"); // replace spaces with dashes
+        let now = Utc::now();
+
+        let applied_time = new.applied_time;
+        let length = new.length.unwrap_or(cfg.default_length.unwrap_or(16)); // default 4 months
+        let location = new.location.clone(); // Option<String>
+        let status = new
+            .status
+            .clone()
+            .unwrap_or(cfg.default_status.clone().unwrap_or("applying".to_string())); // default applying
+        let urls = new.urls.clone(); // Vec<UrlArg>
+        let notes = new.notes.clone().unwrap_or("".to_string()); // default empty
+        let mut tags = new
+            .tags
+            .iter()
+            .map(|t| tag::normalize_tag(t))
+            .collect::<Result<Vec<_>>>()?;
+        tags.sort();
+        tags.dedup();
+
+        // log every property after applying defaults, in 1 big r string
+        log::debug!(
+            r#"
+Group: {}
+Template: {}
+Copy file: {:?}
+Position: {}
+Created at: {}
+Has cover letter: {}
+Applied time: {:?}
+Length: {}
+Location: {:?}
+Status: {}
+URLs: {:?}
+Notes: {}
+Tags: {:?}
+            "#,
+            group,
+            template,
+            copy_file,
+            position,
+            now,
+            has_cover_letter,
+            applied_time,
+            length,
+            location,
+            status,
+            urls,
+            notes,
+            tags,
+        );
+
+        // try to create the resume before inserting into the database using Path
+        let folder_pattern = cfg
+            .folder_pattern
+            .clone()
+            .unwrap_or("{company}_{position}_{date}".to_string());
+        let file_pattern = cfg
+            .file_pattern
+            .clone()
+            .unwrap_or("resume_{company}_{position}_{date}.tex".to_string());
+        let date_format = cfg.date_format.clone().unwrap_or("%Y-%m-%d".to_string());
+
+        let root_dir = cfg.root_dir.clone().join(group.clone());
+        log::debug!("Root directory: {:?}", root_dir);
+        let company = company_name.replace(" ", "-"); // replace spaces with dashes
+        let date = now.format(&date_format).to_string();
+        let folder = folder_pattern
+            .replace("{company}", &company)
+            .replace("{position}", &position)
+            .replace("{date}", &date); // already formatted, it is the user's fault for not using dashes
+
+        let file = file_pattern
+            .replace("{company}", &company)
+            .replace("{position}", &position)
+            .replace("{date}", &date);
+
+        let folder_path = root_dir.join(&folder);
+        let file_path = folder_path.join(&file);
+        let metadata_path = folder_path.join(
+            cfg.metadata_name
+                .clone()
+                .unwrap_or("metadata.json".to_string())
+                .replace("{company}", &company)
+                .replace("{position}", &position)
+                .replace("{date}", &date),
+        );
+
+        log::debug!("Folder path: {:?}", folder_path);
+        log::debug!("File path: {:?}", file_path);
+        log::debug!("Metadata path: {:?}", metadata_path);
+
+        // check if folder exists
+        if folder_path.exists() {
+            return Err(Error::DuplicateResume(folder_path));
+        }
+
+        // the same application written differently, like "Google" and "google"
+        if !new.force {
+            let resumes = self.list().await?;
+            let days = duplicate::window_days(cfg);
+            let found = duplicate::find(&resumes, &company_name, &position, now, days);
+            if !found.is_empty() {
+                return Err(Error::LikelyDuplicate(
+                    found
+                        .iter()
+                        .map(|r| {
+                            format!(
+                                "{} ({} {}, {})",
+                                r.id,
+                                r.company,
+                                r.position,
+                                r.created_at.format("%Y-%m-%d")
+                            )
+                        })
+                        .collect(),
+                ));
+            }
+        }
+
+        // check if file exists
+        // should be unreachable
+        if file_path.exists() {
+            return Err(Error::DuplicateResume(file_path));
+        }
+
+        // check if template or copy file exists
+        // should be unreachable
+        if template.is_empty() && copy_file.is_none() {
+            return Err(Error::Invalid(
+                "Template or copy file must be specified".to_string(),
+            ));
+        }
+
+        // check before anything is written, so a typo does not leave a half created folder
+        if let Some(name) = &new.letter_template {
+            if letter::find_template(cfg, name).is_none() {
+                return Err(Error::TemplateNotFound(
+                    letter::letter_template_dir(cfg).join(name),
+                ));
+            }
+        }
+
+        // should be unreachable
+        if metadata_path.exists() {
+            log::warn!("Metadata file already exists: {:?}", metadata_path);
+        }
+
+        let contents: String = if let Some(copy_file) = &copy_file {
+            fs::read_to_string(copy_file)
+                .map_err(|e| Error::InvalidPath(copy_file.clone(), e.to_string()))?
+        } else {
+            let template_path = template_dir(cfg).join(&template);
+            let template_path = template_path.with_extension("tex");
+            fs::read_to_string(&template_path)
+                .map_err(|_| Error::TemplateNotFound(template_path))?
+        };
+
+        // create the folder
+        fs::create_dir_all(&folder_path)?;
+
+        // create the resume file
+        fs::write(&file_path, &contents)?;
+
+        let cover_letter_path = if has_cover_letter {
+            let fields = LetterFields {
+                company: &company_name,
+                position: &position,
+                group: &group,
+                template: &template,
+                date: &date,
+                location: location.as_deref(),
+            };
+            Some(letter::write_letter(
+                cfg,
+                &folder_path,
+                &letter::letter_file_name(cfg, &company, &position, &date),
+                new.letter_template.as_deref(),
+                &fields,
+            )?)
+        } else {
+            None
+        };
+        let cover_letter_path = cover_letter_path.map(|p| p.display().to_string());
+
+        // create the metadata file
+        let metadata = Metadata {
+            schema_version: metadata::SCHEMA_VERSION,
+            company: Some(company_name.clone()),
+            group: Some(group.clone()),
+            template: Some(template.clone()),
+            position: Some(position.clone()),
+            created_at: Some(now),
+            has_cover_letter: Some(has_cover_letter),
+            cover_letter_path: cover_letter_path.clone(),
+            applied_time,
+            length: Some(length),
+            copied_from: copy_file.clone().map(|f| f.display().to_string()),
+            location: location.clone(),
+            status: Some(status.clone()),
+            urls: Some(json!(urls
+                .iter()
+                .map(|u| json!({ "kind": u.kind.as_str(), "label": u.label, "url": u.url }))
+                .collect::<Vec<_>>())),
+            notes: Some(notes.clone()),
+            tags: Some(json!(tags)),
+            extra: Default::default(),
+        };
+        metadata::write(&metadata_path, &metadata)?;
+
+        let stored_path = file_path.display().to_string();
+        let resume_id = sqlx::query!(
+            r#"
+INSERT INTO resumes (company, "group", template, position, created_at, has_cover_letter, file_path, cover_letter_path)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+RETURNING id
+            "#,
+            company_name,
+            group,
+            template, // does not support copy_file
+            position,
+            now,
+            has_cover_letter,
+            stored_path,
+            cover_letter_path,
+        )
+        .fetch_one(pool)
+        .await?
+        .id;
+        company::link(pool, resume_id, &company_name).await?;
+
+        let copied_from = copy_file.map(|f| f.display().to_string());
+        let copied_from_id = match &copied_from {
+            Some(path) => self.resolve_copied_from(path).await?,
+            None => None,
+        };
+        let metadata_file_path = metadata_path.display().to_string();
+        sqlx::query!(
+            r#"
+INSERT INTO metadata (resume_id, applied_time, copied_from, copied_from_id, metadata_file_path, length, location, status, notes)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+            resume_id,
+            applied_time,
+            copied_from,
+            copied_from_id,
+            metadata_file_path,
+            length,
+            location,
+            status,
+            notes,
+        )
+        .execute(pool)
+        .await?;
+        location::set(pool, resume_id, location.as_deref()).await?;
+
+        for u in &urls {
+            url::insert_url(pool, resume_id, u.kind, u.label.as_deref(), &u.url).await?;
+        }
+
+        for t in &tags {
+            tag::add_tag(pool, resume_id, t).await?;
+        }
+
+        let created = self.get(resume_id).await?;
+        let message = format!(
+            "Create resume {} ({} {})",
+            resume_id, created.company, created.position
+        );
+        self.commit(&created, &message, created.metadata.status == "applied");
+        Ok(created)
+    }
+
+    /// Applies a patch to the metadata file and the db
+    pub async fn update(&self, id: i64, patch: Patch) -> Result<Resume> {
+        log::debug!("Updating {} with:\n{:#?}", id, patch);
+        let cfg = &self.cfg;
+        let pool = &self.pool;
+
+        // Fetch existing resume
+        let resume = sqlx::query!(
+            "SELECT r.*, m.metadata_file_path, m.location, m.status FROM resumes r JOIN metadata m ON r.id = m.resume_id WHERE r.id = ?1",
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(Error::ResumeNotFound(id))?;
+
+        // a metadata file that cannot be read stops the update before any file is written
+        let file_path = Path::new(&resume.metadata_file_path);
+        let mut current_metadata = metadata::read(file_path)?;
+
+        // turning the cover letter on after the fact generates it, like create would have
+        let wants_letter = patch.has_cover_letter == Some(true) || patch.letter_template.is_some();
+        let has_cover_letter = if patch.letter_template.is_some() {
+            Some(true)
+        } else {
+            patch.has_cover_letter
+        };
+        let cover_letter_path = match (&resume.cover_letter_path, wants_letter) {
+            (None, true) => {
+                let date_format = cfg.date_format.clone().unwrap_or("%Y-%m-%d".to_string());
+                let date = resume.created_at.format(&date_format).to_string();
+                let company = patch.company.clone().unwrap_or(resume.company.clone());
+                let position = patch.position.clone().unwrap_or(resume.position.clone());
+                let group = patch.group.clone().unwrap_or(resume.group.clone());
+                let template = patch.template.clone().unwrap_or(resume.template.clone());
+                let location = patch.location.clone().or(resume.location.clone());
+                let fields = LetterFields {
+                    company: &company,
+                    position: &position,
+                    group: &group,
+                    template: &template,
+                    date: &date,
+                    location: location.as_deref(),
+                };
+
+                // the letter goes next to the resume, named after the folder's company and position
+                let resume_path = PathBuf::from(&resume.file_path);
+                let folder_path = resume_path.parent().unwrap_or(&resume_path);
+                let file_name = letter::letter_file_name(
+                    cfg,
+                    &resume.company.replace(" ", "-"),
+                    &resume.position,
+                    &date,
+                );
+                let path = letter::write_letter(
+                    cfg,
+                    folder_path,
+                    &file_name,
+                    patch.letter_template.as_deref(),
+                    &fields,
+                )?;
+                Some(path.display().to_string())
+            }
+            (Some(existing), _) if patch.letter_template.is_some() => {
+                return Err(Error::Invalid(format!(
+                    "Resume already has a cover letter: {}",
+                    existing
+                )));
+            }
+            _ => None,
+        };
+
+        // a letter written here is removed again if the rest of the update fails
+        let mut new_letter = NewLetter(cover_letter_path.as_ref().map(PathBuf::from));
+
+        // Update JSON file
+        let mut update_metadata = Metadata::from(&patch);
+        update_metadata.has_cover_letter = has_cover_letter;
+        update_metadata.cover_letter_path = cover_letter_path.clone();
+
+        // Update JSON fields (same as before)
+        if update_metadata.company.is_some() {
+            current_metadata.company = update_metadata.company;
+        }
+        if update_metadata.group.is_some() {
+            current_metadata.group = update_metadata.group;
+        }
+        if update_metadata.template.is_some() {
+            current_metadata.template = update_metadata.template;
+        }
+        if update_metadata.copied_from.is_some() {
+            current_metadata.copied_from = update_metadata.copied_from;
+        }
+        if update_metadata.position.is_some() {
+            current_metadata.position = update_metadata.position;
+        }
+        if update_metadata.has_cover_letter.is_some() {
+            current_metadata.has_cover_letter = update_metadata.has_cover_letter;
+        }
+        if update_metadata.cover_letter_path.is_some() {
+            current_metadata.cover_letter_path = update_metadata.cover_letter_path;
+        }
+        if update_metadata.created_at.is_some() {
+            current_metadata.created_at = update_metadata.created_at;
+        }
+        if update_metadata.applied_time.is_some() {
+            current_metadata.applied_time = update_metadata.applied_time;
+        }
+        if update_metadata.length.is_some() {
+            current_metadata.length = update_metadata.length;
+        }
+        if update_metadata.location.is_some() {
+            current_metadata.location = update_metadata.location;
+        }
+        if update_metadata.status.is_some() {
+            current_metadata.status = update_metadata.status;
+        }
+        if update_metadata.notes.is_some() {
+            current_metadata.notes = update_metadata.notes;
+        }
+
+        // Write updated metadata back to file
+        metadata::write(file_path, &current_metadata)?;
+
+        // Split updates between resumes and metadata tables
+        let mut resumes_query_parts = Vec::new();
+        let mut resumes_params: Vec<SqlParam> = Vec::new();
+
+        let mut metadata_query_parts = Vec::new();
+        let mut metadata_params: Vec<SqlParam> = Vec::new();
+
+        // Fields for resumes table
+        if patch.company.is_some() {
+            resumes_query_parts.push("company = ?");
+            resumes_params.push(SqlParam::String(&patch.company));
+        }
+        if patch.group.is_some() {
+            resumes_query_parts.push("\"group\" = ?");
+            resumes_params.push(SqlParam::String(&patch.group));
+        }
+        if patch.template.is_some() {
+            resumes_query_parts.push("template = ?");
+            resumes_params.push(SqlParam::String(&patch.template));
+        }
+        if patch.position.is_some() {
+            resumes_query_parts.push("position = ?");
+            resumes_params.push(SqlParam::String(&patch.position));
+        }
+        if has_cover_letter.is_some() {
+            resumes_query_parts.push("has_cover_letter = ?");
+            resumes_params.push(SqlParam::Bool(&has_cover_letter));
+        }
+        if cover_letter_path.is_some() {
+            resumes_query_parts.push("cover_letter_path = ?");
+            resumes_params.push(SqlParam::String(&cover_letter_path));
+        }
+
+        // Fields for metadata table
+        let copied_from = patch
+            .copied_from
+            .as_ref()
+            .map(|p| p.to_string_lossy().into_owned());
+        let copied_from_id = match &copied_from {
+            Some(path) => self
+                .resolve_copied_from(path)
+                .await?
+                .filter(|from| *from != id),
+            None => None,
+        };
+        if patch.copied_from.is_some() {
+            metadata_query_parts.push("copied_from = ?");
+            metadata_params.push(SqlParam::String(&copied_from));
+            metadata_query_parts.push("copied_from_id = ?");
+            metadata_params.push(SqlParam::I64(&copied_from_id));
+        }
+        if patch.applied_time.is_some() {
+            metadata_query_parts.push("applied_time = ?");
+            metadata_params.push(SqlParam::DateTime(&patch.applied_time));
+        }
+        if patch.length.is_some() {
+            metadata_query_parts.push("length = ?");
+            metadata_params.push(SqlParam::U16(&patch.length));
+        }
+        if patch.location.is_some() {
+            metadata_query_parts.push("location = ?");
+            metadata_params.push(SqlParam::String(&patch.location));
+        }
+        if patch.status.is_some() {
+            metadata_query_parts.push("status = ?");
+            metadata_params.push(SqlParam::String(&patch.status));
+        }
+        if patch.notes.is_some() {
+            metadata_query_parts.push("notes = ?");
+            metadata_params.push(SqlParam::String(&patch.notes));
+        }
+
+        // Execute updates for resumes table if needed
+        if !resumes_query_parts.is_empty() {
+            let query = format!(
+                "UPDATE resumes SET {} WHERE id = ?",
+                resumes_query_parts.join(", ")
+            );
+
+            let mut query = sqlx::query(&query);
+            for param in resumes_params {
+                query = param.bind_to_query(query);
+            }
+            query = query.bind(id);
+
+            query.execute(pool).await?;
+        }
+
+        // Execute updates for metadata table if needed
+        if !metadata_query_parts.is_empty() {
+            let query = format!(
+                "UPDATE metadata SET {} WHERE resume_id = ?",
+                metadata_query_parts.join(", ")
+            );
+
+            let mut query = sqlx::query(&query);
+            for param in metadata_params {
+                query = param.bind_to_query(query);
+            }
+            query = query.bind(id);
+
+            query.execute(pool).await?;
+        }
+        new_letter.keep();
+
+        if let Some(name) = &patch.company {
+            company::link(pool, id, name).await?;
+        }
+        if let Some(l) = &patch.location {
+            location::set(pool, id, Some(l)).await?;
+        }
+
+        if !patch.urls.is_empty() {
+            for u in &patch.urls {
+                url::insert_url(pool, resume.id, u.kind, u.label.as_deref(), &u.url).await?;
+            }
+            url::sync_metadata_urls(pool, resume.id).await?;
+        }
+
+        if !patch.tags.is_empty() {
+            tag::apply_edits(pool, resume.id, &patch.tags).await?;
+            tag::sync_metadata_tags(pool, resume.id).await?;
+        }
+
+        let updated = self.get(id).await?;
+        let applied = patch.status.as_deref() == Some("applied") && resume.status != "applied";
+        let message = format!(
+            "Update resume {} ({} {}): {}",
+            id,
+            updated.company,
+            updated.position,
+            patch.fields().join(", ")
+        );
+        self.commit(&updated, &message, applied);
+        Ok(updated)
+    }
+
+    /// Adds the db rows of a resume folder that already exists, e.g. one copied in by
+    /// hand, from its metadata file, what the file leaves out comes from the config
+    pub async fn import(&self, file_path: &Path, metadata_path: &Path) -> Result<Resume> {
+        let cfg = self.cfg.with_defaults();
+        let meta = metadata::read(metadata_path)?;
+        let company = meta
+            .company
+            .clone()
+            .filter(|c| !c.trim().is_empty())
+            .ok_or_else(|| {
+                Error::InvalidMetadata(
+                    metadata_path.to_path_buf(),
+                    vec!["company is missing".to_string()],
+                )
+            })?;
+        let group = meta.group.clone().or(cfg.default_group).unwrap_or_default();
+        let template = meta
+            .template
+            .clone()
+            .or(cfg.default_template)
+            .unwrap_or_default();
+        let position = meta
+            .position
+            .clone()
+            .or(cfg.default_position)
+            .unwrap_or_default();
+        let created_at = meta.created_at.unwrap_or_else(|| {
+            fs::metadata(file_path)
+                .and_then(|m| m.modified())
+                .map(DateTime::<Utc>::from)
+                .unwrap_or_else(|_| Utc::now())
+        });
+        let has_cover_letter = meta.has_cover_letter.unwrap_or(false);
+        let stored_path = file_path.display().to_string();
+        let length = meta.length.or(cfg.default_length).unwrap_or_default();
+        let status = meta
+            .status
+            .clone()
+            .or(cfg.default_status)
+            .unwrap_or_default();
+        let notes = meta.notes.clone().unwrap_or_default();
+        let metadata_file_path = metadata_path.display().to_string();
+
+        let resume_id = sqlx::query!(
+            r#"
+INSERT INTO resumes (company, "group", template, position, created_at, has_cover_letter, file_path, cover_letter_path)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+RETURNING id
+            "#,
+            company,
+            group,
+            template,
+            position,
+            created_at,
+            has_cover_letter,
+            stored_path,
+            meta.cover_letter_path,
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .id;
+        company::link(&self.pool, resume_id, &company).await?;
+
+        let copied_from_id = match &meta.copied_from {
+            Some(path) => self.resolve_copied_from(path).await?,
+            None => None,
+        };
+        sqlx::query!(
+            r#"
+INSERT INTO metadata (resume_id, applied_time, copied_from, copied_from_id, metadata_file_path, length, location, status, notes)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+            resume_id,
+            meta.applied_time,
+            meta.copied_from,
+            copied_from_id,
+            metadata_file_path,
+            length,
+            meta.location,
+            status,
+            notes,
+        )
+        .execute(&self.pool)
+        .await?;
+        location::set(&self.pool, resume_id, meta.location.as_deref()).await?;
+
+        let urls = meta.urls.as_ref().and_then(|u| u.as_array().cloned());
+        for u in urls.unwrap_or_default() {
+            let kind = u["kind"].as_str().and_then(|k| k.parse().ok());
+            if let (Some(kind), Some(link)) = (kind, u["url"].as_str()) {
+                url::insert_url(&self.pool, resume_id, kind, u["label"].as_str(), link).await?;
+            }
+        }
+        let tags = meta.tags.as_ref().and_then(|t| t.as_array().cloned());
+        for name in tags.unwrap_or_default() {
+            if let Some(name) = name.as_str().and_then(|n| tag::normalize_tag(n).ok()) {
+                tag::add_tag(&self.pool, resume_id, &name).await?;
+            }
+        }
+
+        self.get(resume_id).await
+    }
+
+    /// Points the db at the new place of a resume that was moved or renamed outside
+    /// of resuman, the cover letter moves along if it was in the same folder
+    pub async fn relocate(&self, id: i64, file_path: &Path, metadata_path: &Path) -> Result<()> {
+        let resume = self.get(id).await?;
+        let old_folder = PathBuf::from(&resume.file_path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let new_folder = file_path.parent().unwrap_or(file_path);
+        let cover_letter_path =
+            resume
+                .cover_letter_path
+                .map(|p| match PathBuf::from(&p).strip_prefix(&old_folder) {
+                    Ok(rest) => new_folder.join(rest).display().to_string(),
+                    Err(_) => p,
+                });
+
+        if let Some(path) = &cover_letter_path {
+            metadata::set_field(metadata_path, "cover_letter_path", path.as_str().into())?;
+        }
+
+        let stored_path = file_path.display().to_string();
+        let metadata_file_path = metadata_path.display().to_string();
+        sqlx::query!(
+            "UPDATE resumes SET file_path = ?, cover_letter_path = ? WHERE id = ?",
+            stored_path,
+            cover_letter_path,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query!(
+            "UPDATE metadata SET metadata_file_path = ? WHERE resume_id = ?",
+            metadata_file_path,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The resume whose .tex is at `path`, if it is a managed one
+    async fn resolve_copied_from(&self, path: &str) -> Result<Option<i64>> {
+        // --file may be relative or go through a symlink, the db has absolute paths
+        let canonical = fs::canonicalize(path)
+            .map(|p| p.display().to_string())
+            .unwrap_or(path.to_string());
+        let id = sqlx::query_scalar!(
+            "SELECT id FROM resumes WHERE file_path IN (?1, ?2) ORDER BY id LIMIT 1",
+            path,
+            canonical
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// Commits the folder of a resume when git is on, and tags it as the version that
+    /// was sent with `applied`
+    ///
+    /// The resume is saved by then, so a failing git is only a warning.
+    fn commit(&self, resume: &Resume, message: &str, applied: bool) {
+        if !git::enabled(&self.cfg) {
+            return;
+        }
+        let path = PathBuf::from(&resume.file_path);
+        let result =
+            git::commit(&self.cfg, path.parent().unwrap_or(&path), message).and_then(|rev| {
+                if applied {
+                    git::tag(&self.cfg, &git::applied_tag(resume.id))?;
+                }
+                Ok(rev)
+            });
+        match result {
+            Ok(rev) => log::debug!("Committed resume {} as {:?}", resume.id, rev),
+            Err(e) => log::warn!("Could not commit resume {}: {}", resume.id, e),
+        }
+    }
+
+    /// Builds the PDF of a resume, and of its cover letter when that is a .tex too,
+    /// returns the PDFs and commits them when git is on
+    pub async fn build(&self, id: i64) -> Result<Vec<PathBuf>> {
+        let resume = self.get(id).await?;
+        let mut sources = vec![PathBuf::from(&resume.file_path)];
+        sources.extend(
+            resume
+                .cover_letter_path
+                .as_ref()
+                .map(PathBuf::from)
+                .filter(|p| p.extension().is_some_and(|e| e == "tex")),
+        );
+        let pdfs = sources
+            .iter()
+            .map(|tex| build::build(&self.cfg, tex))
+            .collect::<Result<Vec<_>>>()?;
+        let message = format!(
+            "Build resume {} ({} {})",
+            resume.id, resume.company, resume.position
+        );
+        self.commit(&resume, &message, false);
+        Ok(pdfs)
+    }
+
+    /// Deletes a resume from the db, and its folder too with `remove_files`
+    pub async fn delete(&self, id: i64, remove_files: bool) -> Result<()> {
+        // run sql queries to get file path from db
+        let path = sqlx::query!("SELECT file_path FROM resumes WHERE id = ?", id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(Error::ResumeNotFound(id))?
+            .file_path;
+
+        if remove_files {
+            // get the entire directory of the file and remove that
+            let path = PathBuf::from(path);
+            fs::remove_dir_all(path.parent().unwrap_or(&path))?;
+        }
+
+        // run sql queries to delete resume from db
+        sqlx::query!("DELETE FROM resumes WHERE id = ?", id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the resumes whose .tex file is gone from the db, returns their ids
+    pub async fn clean(&self) -> Result<Vec<i64>> {
+        let mut removed = Vec::new();
+        for r in sqlx::query!("SELECT id, file_path FROM resumes")
+            .fetch_all(&self.pool)
+            .await?
+        {
+            if !Path::new(&r.file_path).exists() {
+                sqlx::query!("DELETE FROM resumes WHERE id = ?", r.id)
+                    .execute(&self.pool)
+                    .await?;
+                removed.push(r.id);
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// The sqlite db, `db_path` relative to `root_dir`
+pub fn db_path(cfg: &Config) -> PathBuf {
+    cfg.root_dir.join(
+        cfg.db_path
+            .clone()
+            .unwrap_or(PathBuf::from("resumandb.sqlite")),
+    )
+}
+
+/// Where the .tex templates live, `template_dir` relative to `root_dir`
+pub fn template_dir(cfg: &Config) -> PathBuf {
+    cfg.root_dir.join(
+        cfg.template_dir
+            .clone()
+            .unwrap_or(PathBuf::from("templates")),
+    )
+}
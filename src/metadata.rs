@@ -0,0 +1,233 @@
+// the metadata file next to each resume, a copy of what the db knows about it
+// that stays readable without resuman, see assets/metadata.schema.json
+
+use std::{fs, path::Path};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+
+use crate::error::{Error, Result};
+
+/// The version of the metadata files resuman writes
+///
+/// 1 had no schema_version, unix timestamps and the urls as one string, 2 has ISO-8601
+/// timestamps like the db and a list of urls.
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// The JSON Schema of the metadata files, also printed by `resuman metadata schema`
+pub const SCHEMA: &str = include_str!("../assets/metadata.schema.json");
+
+/// The metadata file of a resume
+///
+/// Keys resuman does not know, added by hand or by a newer resuman, are kept in
+/// `extra` and written back as they were.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Metadata {
+    pub schema_version: u64,
+    #[serde(serialize_with = "serialize_optional_timestamp")]
+    pub applied_time: Option<DateTime<Utc>>,
+    pub company: Option<String>,
+    pub copied_from: Option<String>,
+    #[serde(serialize_with = "serialize_optional_timestamp")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub group: Option<String>,
+    pub has_cover_letter: Option<bool>,
+    pub cover_letter_path: Option<String>,
+    pub length: Option<u16>,
+    pub location: Option<String>,
+    pub notes: Option<String>,
+    pub position: Option<String>,
+    pub status: Option<String>,
+    pub template: Option<String>,
+    // owned by the resume_urls table, see url::sync_metadata_urls
+    pub urls: Option<Value>,
+    // owned by the resume_tags table, see tag::sync_metadata_tags
+    pub tags: Option<Value>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+enum Kind {
+    Version,
+    Text,
+    Bool,
+    Timestamp,
+    Length,
+    Urls,
+    Tags,
+}
+
+const FIELDS: [(&str, Kind); 16] = [
+    ("schema_version", Kind::Version),
+    ("applied_time", Kind::Timestamp),
+    ("company", Kind::Text),
+    ("copied_from", Kind::Text),
+    ("created_at", Kind::Timestamp),
+    ("group", Kind::Text),
+    ("has_cover_letter", Kind::Bool),
+    ("cover_letter_path", Kind::Text),
+    ("length", Kind::Length),
+    ("location", Kind::Text),
+    ("notes", Kind::Text),
+    ("position", Kind::Text),
+    ("status", Kind::Text),
+    ("template", Kind::Text),
+    ("urls", Kind::Urls),
+    ("tags", Kind::Tags),
+];
+
+/// The schema version of a metadata file, 1 for files from before it had one
+pub fn version(value: &Value) -> u64 {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(1)
+}
+
+/// A timestamp the way resuman writes it, in the metadata files and its JSON output
+pub fn timestamp(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Writes a timestamp with [`timestamp`], for `#[serde(serialize_with)]`
+pub fn serialize_timestamp<S: Serializer>(
+    time: &DateTime<Utc>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&timestamp(time))
+}
+
+/// Writes a timestamp that may not be set with [`timestamp`]
+pub fn serialize_optional_timestamp<S: Serializer>(
+    time: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match time {
+        Some(time) => serialize_timestamp(time, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Every field of `value` that does not have the type the schema of its version
+/// says, e.g. `length must be a whole number of weeks, not "abc"`
+pub fn problems(value: &Value) -> Vec<String> {
+    let Some(object) = value.as_object() else {
+        return vec!["the metadata must be a JSON object".to_string()];
+    };
+    let version = version(value);
+    let mut problems = Vec::new();
+    for (key, kind) in FIELDS {
+        let Some(field) = object.get(key).filter(|f| !f.is_null()) else {
+            continue;
+        };
+        let problem = match kind {
+            Kind::Version => (field.as_u64().unwrap_or(0) < 1).then_some("a version number"),
+            Kind::Text => (!field.is_string()).then_some("text"),
+            Kind::Bool => (!field.is_boolean()).then_some("true or false"),
+            Kind::Timestamp if version < 2 => (!field.is_i64()).then_some("a unix timestamp"),
+            Kind::Timestamp => field
+                .as_str()
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .is_none()
+                .then_some("an ISO-8601 timestamp like 2025-01-31T09:00:00Z"),
+            Kind::Length => (field.as_u64().unwrap_or(u64::MAX) > u16::MAX as u64)
+                .then_some("a whole number of weeks"),
+            // 1 kept them as one string, separated by |
+            Kind::Urls if version < 2 && field.is_string() => None,
+            Kind::Urls => {
+                let url = |u: &Value| u["url"].is_string() && u["kind"].is_string();
+                (!field.as_array().is_some_and(|urls| urls.iter().all(url)))
+                    .then_some("a list of {\"kind\", \"url\"} objects")
+            }
+            Kind::Tags => (!field
+                .as_array()
+                .is_some_and(|tags| tags.iter().all(Value::is_string)))
+            .then_some("a list of tags"),
+        };
+        if let Some(expected) = problem {
+            problems.push(format!("{} must be {}, not {}", key, expected, field));
+        }
+    }
+    problems
+}
+
+/// Upgrades a metadata file to the current schema version, returns whether it changed
+///
+/// Files from a newer resuman are left alone.
+pub fn upgrade(value: &mut Value) -> bool {
+    if version(value) >= SCHEMA_VERSION {
+        return false;
+    }
+    let Some(object) = value.as_object_mut() else {
+        return false;
+    };
+
+    // 1 -> 2, unix timestamps become ISO-8601 like in the db
+    for key in ["created_at", "applied_time"] {
+        let time = object
+            .get(key)
+            .and_then(Value::as_i64)
+            .and_then(|secs| DateTime::from_timestamp(secs, 0));
+        if let Some(time) = time {
+            object.insert(key.to_string(), Value::from(timestamp(&time)));
+        }
+    }
+    // and the urls string becomes the list the resume_urls table is mirrored as
+    let urls = object.get("urls").and_then(Value::as_str).map(|urls| {
+        urls.split('|')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| json!({ "kind": "posting", "label": null, "url": url }))
+            .collect::<Vec<_>>()
+    });
+    if let Some(urls) = urls {
+        object.insert("urls".to_string(), Value::Array(urls));
+    }
+    object.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
+    true
+}
+
+/// Reads the file at `path` as JSON, without checking it
+pub fn read_value(path: &Path) -> Result<Value> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Reads and checks a metadata file, upgrading an old one in memory
+pub fn read(path: &Path) -> Result<Metadata> {
+    let mut value = read_value(path)?;
+    if version(&value) > SCHEMA_VERSION {
+        log::warn!(
+            "{} is from a newer resuman (schema_version {}), its new fields are kept as they are",
+            path.display(),
+            version(&value)
+        );
+    }
+    let problems = problems(&value);
+    if !problems.is_empty() {
+        return Err(Error::InvalidMetadata(path.to_path_buf(), problems));
+    }
+    upgrade(&mut value);
+    Ok(serde_json::from_value(value)?)
+}
+
+pub fn write(path: &Path, metadata: &Metadata) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(metadata)?)?;
+    Ok(())
+}
+
+/// Sets a single top-level field of a metadata file, other fields are left alone
+pub fn set_field(path: &Path, key: &str, value: Value) -> Result<()> {
+    let mut metadata = read_value(path)?;
+    if !metadata.is_object() {
+        return Err(Error::InvalidMetadata(
+            path.to_path_buf(),
+            problems(&metadata),
+        ));
+    }
+    upgrade(&mut metadata);
+    metadata[key] = value;
+    fs::write(path, serde_json::to_string_pretty(&metadata)?)?;
+    Ok(())
+}
@@ -1,10 +1,26 @@
-use chrono::{DateTime, TimeDelta, Utc};
+use std::path::PathBuf;
 
-// This is just architecture, not actual code
-// These structs are never used
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-#[allow(dead_code)]
+use crate::{metadata, tag::TagEdit, url::UrlArg};
+
+// the usual lifecycle of an application, in order
+// statuses are free text, these are only suggested and used for ordering
+pub const KNOWN_STATUSES: [&str; 7] = [
+    "applying",
+    "applied",
+    "oa",
+    "interview",
+    "rejected",
+    "accepted",
+    "ghosted",
+];
+
+/// A resume as stored in the db, with its metadata, tags and urls
+#[derive(Debug, Clone, Serialize)]
 pub struct Resume {
+    pub id: i64,
     pub company: String, // company name
     // INDEX: company_role_time, group is solely for organization purposes
     pub group: String, // work, hackathons, university, etc
@@ -14,53 +30,147 @@ pub struct Resume {
     pub template: String, // template name... OR the name of the .tex file it came from (i.e. used from the copy command)
     // templates folder should be configured as well
     pub position: String, // position; for the HACKATHON edge case just put "hacker"
+    #[serde(serialize_with = "metadata::serialize_timestamp")]
     pub created_at: DateTime<Utc>, // date created
     pub has_cover_letter: bool, // if applied with a cover letter
+    pub cover_letter_path: Option<String>,
+    pub file_path: String, // path to the .tex file
+    pub tags: Vec<String>, // sorted, stored in the resume_tags table
 
+    #[serde(flatten)]
     pub metadata: MetaData,
 }
 
-#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MetaData {
     // the job associated with the resume
-    pub applied_time: DateTime<Utc>, // date applied
+    #[serde(serialize_with = "metadata::serialize_optional_timestamp")]
+    pub applied_time: Option<DateTime<Utc>>, // date applied, none until it is sent
 
-    pub length: TimeDelta, // length of the job, default 4 months? configurable
+    pub length: i64, // length of the job in weeks, default 16 (4 months), configurable
     pub location: Option<String>, // location of job
+    // parsed from location
+    pub city: Option<String>,
+    pub region: Option<String>,    // state or province code
+    pub country: Option<String>,   // ISO 3166 code
+    pub work_mode: Option<String>, // onsite, hybrid or remote
 
     pub status: String, // status of application
     // may include: applying, applied, oa, interview, rejected, accepted, ghosted, etc
-    pub urls: String, // URLs to job posting, company, etc, separated by |
-    // may include: job posting, company, linkedin, url to check, etc
-    // can be empty
-    pub notes: String, // other metadata, can be JSON, default empty
+    pub urls: Vec<ResumeUrl>, // stored in the resume_urls table, can be empty
+    pub notes: String,        // other metadata, can be JSON, default empty
+
+    pub copied_from: Option<String>, // the .tex file it was copied from, if any
+    pub copied_from_id: Option<i64>, // the resume that file belongs to, if it is managed
+    pub metadata_file_path: String,
 }
 
-#[allow(dead_code)]
-impl Resume {
-    pub fn new(
-        group: String,
-        template: String,
-        company: String,
-        position: String,
-        has_cover_letter: bool,
-        length: u16,
-    ) -> Self {
-        Resume {
-            group,
-            template,
-            company,
-            position,
-            created_at: Utc::now(),
-            has_cover_letter,
-            metadata: MetaData {
-                applied_time: Utc::now(),
-                length: TimeDelta::weeks(length.into()), // should be configurable?
-                location: None,
-                status: "applying".to_string().to_ascii_lowercase(), // standard: lowercase
-                urls: "".to_string(),                                // separated by |
-                notes: "".to_string(),
-            },
-        }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeUrl {
+    pub id: i64,
+    pub kind: String, // one of: posting, company, linkedin, portal, status-check
+    pub label: Option<String>,
+    pub url: String,
+}
+
+/// Everything needed to create a resume, unset fields fall back to the config defaults
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewResume {
+    pub company: String,
+    pub group: Option<String>,
+    pub template: Option<String>,
+    pub copy_from: Option<PathBuf>, // a .tex file to copy instead of a template
+    pub position: Option<String>,
+    #[serde(default)]
+    pub has_cover_letter: bool,
+    pub letter_template: Option<String>, // implies has_cover_letter
+    pub applied_time: Option<DateTime<Utc>>,
+    pub length: Option<u16>,
+    pub location: Option<String>,
+    pub status: Option<String>,
+    #[serde(default)]
+    pub urls: Vec<UrlArg>,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub force: bool, // create it even if it looks like a duplicate
+}
+
+/// Changes to a resume, only the fields that are set are touched
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Patch {
+    pub company: Option<String>,
+    pub group: Option<String>,
+    pub template: Option<String>,
+    pub copied_from: Option<PathBuf>,
+    pub position: Option<String>,
+    pub has_cover_letter: Option<bool>,
+    pub letter_template: Option<String>, // generates the letter if there is none yet
+    pub created_at: Option<DateTime<Utc>>,
+    pub applied_time: Option<DateTime<Utc>>,
+    pub length: Option<u16>,
+    pub location: Option<String>,
+    pub status: Option<String>,
+    #[serde(default)]
+    pub urls: Vec<UrlArg>, // added, existing urls are kept
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<TagEdit>,
+}
+
+impl Patch {
+    /// The names of the fields the patch changes
+    pub fn fields(&self) -> Vec<&'static str> {
+        [
+            ("company", self.company.is_some()),
+            ("group", self.group.is_some()),
+            ("template", self.template.is_some()),
+            ("copied_from", self.copied_from.is_some()),
+            ("position", self.position.is_some()),
+            ("has_cover_letter", self.has_cover_letter.is_some()),
+            ("letter_template", self.letter_template.is_some()),
+            ("created_at", self.created_at.is_some()),
+            ("applied_time", self.applied_time.is_some()),
+            ("length", self.length.is_some()),
+            ("location", self.location.is_some()),
+            ("status", self.status.is_some()),
+            ("urls", !self.urls.is_empty()),
+            ("notes", self.notes.is_some()),
+            ("tags", !self.tags.is_empty()),
+        ]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect()
     }
 }
+
+/// Filters for [`crate::Resuman::find`], all of them must match
+///
+/// Text filters match substrings, `all` instead matches any field. The parsed
+/// location fields match whole values.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Filter {
+    pub company: Option<String>,
+    pub group: Option<String>,
+    pub template: Option<String>,
+    pub position: Option<String>,
+    pub has_cover_letter: Option<bool>,
+    pub created_at: Option<String>,
+    pub applied_time: Option<String>,
+    pub length: Option<u16>,
+    pub location: Option<String>,
+    pub city: Option<String>,      // known aliases match too, e.g. nyc
+    pub region: Option<String>,    // code or name
+    pub country: Option<String>,   // code or name
+    pub work_mode: Option<String>, // onsite, hybrid or remote
+    pub status: Option<String>,
+    pub file_path: Option<String>,
+    pub copied_from: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub all: Option<String>,
+}
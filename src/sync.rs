@@ -0,0 +1,356 @@
+// brings the db in line with the resume folders on disk, for folders that were
+// renamed, copied in or had their metadata edited without resuman
+
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    backup,
+    error::Result,
+    manager::{self, Resuman},
+    metadata::{self, Metadata},
+    resume::{Patch, Resume},
+    tag::{self, TagEdit},
+};
+
+/// One thing [`reconcile`] did, or could not do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Inserted { id: i64, path: PathBuf },
+    Moved { id: i64, from: String, to: PathBuf },
+    Updated { id: i64, fields: Vec<String> },
+    Missing { id: i64, path: String }, // left to `resuman clean`
+    Skipped { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Inserted { id, path } => write!(f, "inserted\t{}\t{}", id, path.display()),
+            Change::Moved { id, from, to } => {
+                write!(f, "moved\t{}\t{}\t{}", id, from, to.display())
+            }
+            Change::Updated { id, fields } => write!(f, "updated\t{}\t{}", id, fields.join(",")),
+            Change::Missing { id, path } => write!(f, "missing\t{}\t{}", id, path),
+            Change::Skipped { path, reason } => {
+                write!(f, "skipped\tnull\t{}\t{}", path.display(), reason)
+            }
+        }
+    }
+}
+
+/// Whether a file name fits `metadata_name`, where each placeholder stands for anything
+pub fn is_metadata_name(name: &str, pattern: &str) -> bool {
+    let mut pieces = Vec::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        pieces.push(&rest[..start]);
+        rest = &rest[start + len + 1..];
+    }
+    if pieces.is_empty() {
+        return name == pattern;
+    }
+    pieces.push(rest);
+
+    let (first, last) = (pieces[0], pieces[pieces.len() - 1]);
+    if !name.starts_with(first) || name.len() < first.len() + last.len() {
+        return false;
+    }
+    let mut middle = &name[first.len()..name.len() - last.len()];
+    if !name.ends_with(last) {
+        return false;
+    }
+    for piece in &pieces[1..pieces.len() - 1] {
+        match middle.find(piece) {
+            Some(i) => middle = &middle[i + piece.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Whether `path` is one of the files resuman keeps itself, like the db and backups,
+/// which the watcher has to ignore
+pub fn is_internal(resuman: &Resuman, path: &Path) -> bool {
+    let cfg = resuman.config();
+    let db = manager::db_path(cfg).display().to_string();
+    path.display().to_string().starts_with(&db)
+        || path.starts_with(backup::backup_dir(cfg))
+        || path
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+}
+
+/// Every metadata file under `root_dir` with the .tex files next to it, sorted
+fn scan(resuman: &Resuman) -> Result<Vec<(PathBuf, Vec<PathBuf>)>> {
+    let cfg = resuman.config();
+    let pattern = cfg.metadata_name.as_deref().unwrap_or("metadata.json");
+    let skip = [manager::template_dir(cfg), backup::backup_dir(cfg)];
+
+    let mut found = Vec::new();
+    let mut dirs = vec![cfg.root_dir.clone()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries: Vec<PathBuf> = fs::read_dir(&dir)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                !p.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .starts_with('.')
+            })
+            .collect();
+        entries.sort();
+
+        let tex: Vec<PathBuf> = entries
+            .iter()
+            .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "tex"))
+            .cloned()
+            .collect();
+        for path in entries {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if path.is_dir() && !skip.contains(&path) {
+                dirs.push(path);
+            } else if path.is_file() && is_metadata_name(&name, pattern) {
+                found.push((path, tex.clone()));
+            }
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// What in the metadata file differs from the db, as a patch and the names of the fields
+fn edits(meta: &Metadata, resume: &Resume) -> (Patch, Vec<String>) {
+    let mut patch = Patch::default();
+    let mut fields = Vec::new();
+    let mut text = |key: &str, new: &Option<String>, old: &str, field: &mut Option<String>| {
+        if let Some(new) = new.as_ref().filter(|n| n.as_str() != old) {
+            *field = Some(new.clone());
+            fields.push(key.to_string());
+        }
+    };
+    text(
+        "company",
+        &meta.company,
+        &resume.company,
+        &mut patch.company,
+    );
+    text("group", &meta.group, &resume.group, &mut patch.group);
+    text(
+        "template",
+        &meta.template,
+        &resume.template,
+        &mut patch.template,
+    );
+    text(
+        "position",
+        &meta.position,
+        &resume.position,
+        &mut patch.position,
+    );
+    text(
+        "status",
+        &meta.status,
+        &resume.metadata.status,
+        &mut patch.status,
+    );
+    text(
+        "notes",
+        &meta.notes,
+        &resume.metadata.notes,
+        &mut patch.notes,
+    );
+    let location = resume.metadata.location.clone().unwrap_or_default();
+    text("location", &meta.location, &location, &mut patch.location);
+
+    // files upgraded from unix timestamps only have whole seconds
+    if let Some(new) = meta
+        .created_at
+        .filter(|t| t.timestamp() != resume.created_at.timestamp())
+    {
+        patch.created_at = Some(new);
+        fields.push("created_at".to_string());
+    }
+    let applied = resume.metadata.applied_time.map(|t| t.timestamp());
+    if let Some(new) = meta.applied_time.filter(|t| Some(t.timestamp()) != applied) {
+        patch.applied_time = Some(new);
+        fields.push("applied_time".to_string());
+    }
+    if let Some(new) = meta.length.filter(|l| *l as i64 != resume.metadata.length) {
+        patch.length = Some(new);
+        fields.push("length".to_string());
+    }
+    if let Some(new) = meta
+        .has_cover_letter
+        .filter(|h| *h != resume.has_cover_letter)
+    {
+        patch.has_cover_letter = Some(new);
+        fields.push("has_cover_letter".to_string());
+    }
+    if let Some(new) = meta
+        .copied_from
+        .as_ref()
+        .filter(|c| Some(*c) != resume.metadata.copied_from.as_ref())
+    {
+        patch.copied_from = Some(new.into());
+        fields.push("copied_from".to_string());
+    }
+
+    if let Some(tags) = meta.tags.as_ref().and_then(|t| t.as_array()) {
+        let tags: HashSet<String> = tags
+            .iter()
+            .filter_map(|t| t.as_str().and_then(|t| tag::normalize_tag(t).ok()))
+            .collect();
+        for name in &resume.tags {
+            if !tags.contains(name) {
+                patch.tags.push(TagEdit::Remove(name.clone()));
+            }
+        }
+        let mut added: Vec<&String> = tags.iter().filter(|t| !resume.tags.contains(t)).collect();
+        added.sort();
+        for name in added {
+            patch.tags.push(TagEdit::Add(name.clone()));
+        }
+        if !patch.tags.is_empty() {
+            fields.push("tags".to_string());
+        }
+    }
+    (patch, fields)
+}
+
+// the same application: a folder that was renamed keeps these in its metadata
+fn same_application(meta: &Metadata, resume: &Resume) -> bool {
+    meta.company.as_deref() == Some(&resume.company)
+        && meta.position.as_deref() == Some(&resume.position)
+        && meta.created_at.map(|t| t.timestamp()) == Some(resume.created_at.timestamp())
+}
+
+/// Compares every resume folder under `root_dir` with the db and applies what changed
+/// on disk: new folders are inserted, moved or renamed ones are followed and edits of
+/// the metadata files are applied like update would
+///
+/// Rows whose files are gone are only reported, removing them is up to `resuman clean`.
+pub async fn reconcile(resuman: &Resuman) -> Result<Vec<Change>> {
+    let resumes = resuman.list().await?;
+    let mut changes = Vec::new();
+    let mut claimed = HashSet::new();
+    let mut unmatched = Vec::new();
+
+    let exists = |path: &str| Path::new(path).exists();
+    for (path, tex) in scan(resuman)? {
+        let stored = path.display().to_string();
+        match resumes
+            .iter()
+            .find(|r| r.metadata.metadata_file_path == stored)
+        {
+            Some(resume) => {
+                claimed.insert(resume.id);
+                // the .tex was renamed inside its folder
+                if !exists(&resume.file_path) {
+                    if let Some(file) = tex.first() {
+                        resuman.relocate(resume.id, file, &path).await?;
+                        changes.push(Change::Moved {
+                            id: resume.id,
+                            from: resume.file_path.clone(),
+                            to: file.clone(),
+                        });
+                    }
+                }
+                let meta = match metadata::read(&path) {
+                    Ok(meta) => meta,
+                    Err(e) => {
+                        changes.push(Change::Skipped {
+                            path,
+                            reason: e.to_string(),
+                        });
+                        continue;
+                    }
+                };
+                let (patch, fields) = edits(&meta, resume);
+                if !fields.is_empty() {
+                    resuman.update(resume.id, patch).await?;
+                    changes.push(Change::Updated {
+                        id: resume.id,
+                        fields,
+                    });
+                }
+            }
+            None => unmatched.push((path, tex)),
+        }
+    }
+
+    for (path, tex) in unmatched {
+        let meta = match metadata::read(&path) {
+            Ok(meta) => meta,
+            Err(e) => {
+                changes.push(Change::Skipped {
+                    path,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let Some(file) = tex.first() else {
+            changes.push(Change::Skipped {
+                path,
+                reason: "no .tex file next to it".to_string(),
+            });
+            continue;
+        };
+
+        let moved = resumes.iter().find(|r| {
+            !claimed.contains(&r.id)
+                && !exists(&r.metadata.metadata_file_path)
+                && same_application(&meta, r)
+        });
+        match moved {
+            Some(resume) => {
+                claimed.insert(resume.id);
+                resuman.relocate(resume.id, file, &path).await?;
+                changes.push(Change::Moved {
+                    id: resume.id,
+                    from: resume.file_path.clone(),
+                    to: file.clone(),
+                });
+                let (patch, fields) = edits(&meta, resume);
+                if !fields.is_empty() {
+                    resuman.update(resume.id, patch).await?;
+                    changes.push(Change::Updated {
+                        id: resume.id,
+                        fields,
+                    });
+                }
+            }
+            // e.g. a copy of a folder, which has the same company, position and date
+            None => match resuman.import(file, &path).await {
+                Ok(resume) => {
+                    claimed.insert(resume.id);
+                    changes.push(Change::Inserted {
+                        id: resume.id,
+                        path: file.clone(),
+                    });
+                }
+                Err(e) => changes.push(Change::Skipped {
+                    path,
+                    reason: e.to_string(),
+                }),
+            },
+        }
+    }
+
+    for resume in resumes.iter().filter(|r| !claimed.contains(&r.id)) {
+        if !exists(&resume.metadata.metadata_file_path) {
+            changes.push(Change::Missing {
+                id: resume.id,
+                path: resume.file_path.clone(),
+            });
+        }
+    }
+    Ok(changes)
+}
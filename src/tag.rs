@@ -0,0 +1,131 @@
+// tags shared between resumes, kept in the tags and resume_tags tables and mirrored
+// in the metadata files
+
+use std::path::Path;
+
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+use crate::{
+    error::{Error, Result},
+    metadata,
+};
+
+/// A tag edit given to update as `+tag`, `-tag` or just `tag` (same as `+tag`)
+// deserialized from "+tag" or "-tag", like --tag
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub enum TagEdit {
+    Add(String),
+    Remove(String),
+}
+
+/// Lowercases and checks a tag name, only letters, digits, '-', '_' and '.' are allowed
+pub fn normalize_tag(s: &str) -> Result<String> {
+    let name = s.trim().to_lowercase();
+    if name.is_empty() {
+        return Err(Error::Invalid("Tag name cannot be empty".to_string()));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    {
+        return Err(Error::Invalid(format!(
+            "Invalid character {:?} in tag {:?}",
+            c, s
+        )));
+    }
+    Ok(name)
+}
+
+impl TryFrom<String> for TagEdit {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        parse_tag_edit(&s)
+    }
+}
+
+pub fn parse_tag_edit(s: &str) -> Result<TagEdit> {
+    if let Some(name) = s.strip_prefix('+') {
+        Ok(TagEdit::Add(normalize_tag(name)?))
+    } else if let Some(name) = s.strip_prefix('-') {
+        Ok(TagEdit::Remove(normalize_tag(name)?))
+    } else {
+        Ok(TagEdit::Add(normalize_tag(s)?))
+    }
+}
+
+pub async fn add_tag(pool: &SqlitePool, resume_id: i64, name: &str) -> Result<()> {
+    sqlx::query!("INSERT OR IGNORE INTO tags (name) VALUES (?)", name)
+        .execute(pool)
+        .await?;
+    sqlx::query!(
+        r#"
+INSERT OR IGNORE INTO resume_tags (resume_id, tag_id)
+SELECT ?1, id FROM tags WHERE name = ?2
+        "#,
+        resume_id,
+        name,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn remove_tag(pool: &SqlitePool, resume_id: i64, name: &str) -> Result<()> {
+    sqlx::query!(
+        r#"
+DELETE FROM resume_tags
+WHERE resume_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)
+        "#,
+        resume_id,
+        name,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn apply_edits(pool: &SqlitePool, resume_id: i64, edits: &[TagEdit]) -> Result<()> {
+    for edit in edits {
+        match edit {
+            TagEdit::Add(name) => add_tag(pool, resume_id, name).await?,
+            TagEdit::Remove(name) => remove_tag(pool, resume_id, name).await?,
+        }
+    }
+    Ok(())
+}
+
+pub async fn tags_of(pool: &SqlitePool, resume_id: i64) -> Result<Vec<String>> {
+    let tags = sqlx::query!(
+        r#"
+SELECT tags.name FROM tags
+JOIN resume_tags ON resume_tags.tag_id = tags.id
+WHERE resume_tags.resume_id = ?
+ORDER BY tags.name
+        "#,
+        resume_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| r.name)
+    .collect();
+    Ok(tags)
+}
+
+/// Rewrites the "tags" field of the metadata file so it matches the db
+pub async fn sync_metadata_tags(pool: &SqlitePool, resume_id: i64) -> Result<()> {
+    let metadata_file_path = sqlx::query!(
+        "SELECT metadata_file_path FROM metadata WHERE resume_id = ?",
+        resume_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(Error::ResumeNotFound(resume_id))?
+    .metadata_file_path;
+
+    let tags = tags_of(pool, resume_id).await?;
+    metadata::set_field(Path::new(&metadata_file_path), "tags", Value::from(tags))
+}
@@ -0,0 +1,197 @@
+// the urls of a resume, kept in the resume_urls table and mirrored in its metadata file
+
+use std::{fmt, path::Path, str::FromStr};
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+
+use crate::{
+    error::{Error, Result},
+    metadata,
+};
+
+// stored as lowercase text in the db, the kebab-case names are what clap shows
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UrlKind {
+    Posting,
+    Company,
+    Linkedin,
+    Portal,
+    StatusCheck,
+}
+
+impl UrlKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UrlKind::Posting => "posting",
+            UrlKind::Company => "company",
+            UrlKind::Linkedin => "linkedin",
+            UrlKind::Portal => "portal",
+            UrlKind::StatusCheck => "status-check",
+        }
+    }
+}
+
+impl fmt::Display for UrlKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for UrlKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        <UrlKind as ValueEnum>::from_str(s, true)
+            .map_err(|_| Error::Invalid(format!("Unknown URL kind: {}", s)))
+    }
+}
+
+/// A URL given on the command line as `[kind=[label=]]url`, e.g. `portal=Workday=https://...`
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub struct UrlArg {
+    pub kind: UrlKind,
+    pub label: Option<String>,
+    pub url: String,
+}
+
+// the form parse_url_arg reads back
+impl fmt::Display for UrlArg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "{}={}={}", self.kind, label, self.url),
+            None => write!(f, "{}={}", self.kind, self.url),
+        }
+    }
+}
+
+/// Checks the syntax of a URL, only absolute URLs with a host are accepted
+pub fn validate_url(s: &str) -> Result<String> {
+    let trimmed = s.trim();
+    let parsed = ::url::Url::parse(trimmed)
+        .map_err(|e| Error::Invalid(format!("Invalid URL {:?}: {}", s, e)))?;
+    if !parsed.has_host() {
+        return Err(Error::Invalid(format!("Invalid URL {:?}: missing host", s)));
+    }
+    Ok(trimmed.to_string())
+}
+
+impl TryFrom<String> for UrlArg {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        parse_url_arg(&s)
+    }
+}
+
+pub fn parse_url_arg(s: &str) -> Result<UrlArg> {
+    // only treat the prefix as a kind if it is a known kind, urls can contain '=' too
+    if let Some((prefix, rest)) = s.split_once('=') {
+        if let Ok(kind) = prefix.parse::<UrlKind>() {
+            // a url has its scheme before any '=', a label has no ':'
+            let (label, url) = match rest.split_once('=') {
+                Some((label, url)) if !label.contains(':') => (Some(label.trim()), url),
+                _ => (None, rest),
+            };
+            return Ok(UrlArg {
+                kind,
+                label: label.filter(|l| !l.is_empty()).map(str::to_string),
+                url: validate_url(url)?,
+            });
+        }
+    }
+    Ok(UrlArg {
+        kind: UrlKind::Posting,
+        label: None,
+        url: validate_url(s)?,
+    })
+}
+
+pub async fn insert_url(
+    pool: &SqlitePool,
+    resume_id: i64,
+    kind: UrlKind,
+    label: Option<&str>,
+    url: &str,
+) -> Result<i64> {
+    let kind = kind.as_str();
+    let id = sqlx::query!(
+        r#"
+INSERT INTO resume_urls (resume_id, kind, label, url)
+VALUES (?1, ?2, ?3, ?4)
+RETURNING id
+        "#,
+        resume_id,
+        kind,
+        label,
+        url,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| Error::Invalid(format!("Could not add URL {:?}: {}", url, e)))?
+    .id;
+    Ok(id)
+}
+
+/// The migration that moved the urls of the metadata into their own table
+pub const URLS_MIGRATION: i64 = 20250104120000;
+
+/// Rewrites the urls of every metadata file from the db, a file that cannot be
+/// written is skipped with a warning
+pub async fn backfill_metadata(pool: &SqlitePool) -> Result<()> {
+    let ids = sqlx::query_scalar!("SELECT resume_id FROM metadata ORDER BY resume_id")
+        .fetch_all(pool)
+        .await?;
+    for id in ids {
+        if let Err(e) = sync_metadata_urls(pool, id).await {
+            log::warn!("Could not update the urls of resume {}: {}", id, e);
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites the "urls" field of the metadata file so it matches the db
+pub async fn sync_metadata_urls(pool: &SqlitePool, resume_id: i64) -> Result<()> {
+    let metadata_file_path = sqlx::query!(
+        "SELECT metadata_file_path FROM metadata WHERE resume_id = ?",
+        resume_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(Error::ResumeNotFound(resume_id))?
+    .metadata_file_path;
+
+    let urls = sqlx::query!(
+        "SELECT kind, label, url FROM resume_urls WHERE resume_id = ? ORDER BY id",
+        resume_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| json!({ "kind": r.kind, "label": r.label, "url": r.url }))
+    .collect::<Vec<_>>();
+
+    metadata::set_field(Path::new(&metadata_file_path), "urls", Value::Array(urls))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_args_print_the_way_they_are_parsed() {
+        for arg in [
+            "portal=Workday=https://acme.wd1.com/jobs?id=1",
+            "posting=https://acme.com/careers?q=a=b",
+            "status-check=https://acme.com/status",
+        ] {
+            assert_eq!(parse_url_arg(arg).unwrap().to_string(), arg);
+        }
+        assert_eq!(
+            parse_url_arg("https://acme.com").unwrap().to_string(),
+            "posting=https://acme.com"
+        );
+    }
+}
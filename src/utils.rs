@@ -1,54 +1,262 @@
-use crate::config::Data;
+use crate::{
+    config::{self, Config, Data},
+    Error,
+};
 use anyhow::Result;
-use dirs;
-use std::path::PathBuf;
-use toml;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
-pub fn load_config(config_path: Option<PathBuf>) -> Result<Data> {
-    if let Some(path) = config_path {
-        let file = std::fs::read_to_string(path)?;
-        return Ok(toml::from_str(&file)?);
+/// Loads the config in layers, each one winning over the ones before: the defaults,
+/// the config file, RESUMAN_* env vars and `--set key=value` flags
+///
+/// Without a config file, RESUMAN_ROOT_DIR or `--set root_dir=...` is enough.
+pub fn load_config(config_path: Option<PathBuf>, set: &[String]) -> crate::Result<Data> {
+    let overrides = config_overrides(set)?;
+    let (path, mut table) = match locate_config(config_path) {
+        Ok(location) => {
+            let file = fs::read_to_string(&location.path)?;
+            let table: toml::Table =
+                toml::from_str(&file).map_err(|e| Error::ConfigParse(location.path.clone(), e))?;
+            (Some(location.path), table)
+        }
+        Err(Error::ConfigNotFound(_)) if overrides.contains_key("root_dir") => {
+            (None, toml::Table::new())
+        }
+        Err(e) => return Err(e),
+    };
+
+    // [main] needs a root_dir to parse, the overrides go on top of the profile later
+    if let toml::Value::Table(main) = table
+        .entry("main")
+        .or_insert(toml::Value::Table(toml::Table::new()))
+    {
+        for (key, value) in &overrides {
+            main.entry(key).or_insert(value.clone());
+        }
+    }
+    let mut data: Data = table.try_into().map_err(|e| {
+        Error::ConfigParse(
+            path.clone().unwrap_or(PathBuf::from("RESUMAN_* env vars")),
+            e,
+        )
+    })?;
+
+    data.base_dir = Some(match &path {
+        Some(path) => config_dir(path)?,
+        None => std::env::current_dir()?,
+    });
+    data.overrides = overrides;
+    Ok(data)
+}
+
+/// The config values set by RESUMAN_* env vars, then by `key=value` pairs
+pub fn config_overrides(set: &[String]) -> crate::Result<toml::Table> {
+    let mut overrides = toml::Table::new();
+    for key in Config::keys() {
+        let var = config::env_var(&key);
+        if let Ok(value) = std::env::var(&var) {
+            let value = Config::parse_value(&key, &value)
+                .map_err(|e| Error::Invalid(format!("{}: {}", var, e)))?;
+            overrides.insert(key, value);
+        }
+    }
+    for pair in set {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| Error::Invalid(format!("--set takes key=value, not {:?}", pair)))?;
+        // everything is under [main]
+        let key = key.trim().trim_start_matches("main.");
+        overrides.insert(key.to_string(), Config::parse_value(key, value)?);
     }
+    Ok(overrides)
+}
+
+/// The config file that is used, and the other candidates it was picked over
+pub struct ConfigLocation {
+    pub path: PathBuf,
+    pub explicit: bool, // passed with --config, the candidates are not searched
+    pub candidates: Vec<PathBuf>, // in search order, may include `path`
+}
+
+/// Every path a config is looked for at when --config is not given, in order
+pub fn config_candidates() -> crate::Result<Vec<PathBuf>> {
     // it doesn't make sense to use a macro here
-    let home =
-        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
+    let home = home_dir()?;
 
-    // find the config file with 4 path options
-    let config_paths = [
+    // prefers .config/ over .resuman/ over .resuman.toml
+    Ok(vec![
         PathBuf::from("resuman.toml"), // For debugging
         home.join(".config/resuman/config.toml"),
         home.join(".resuman/config.toml"),
         home.join(".resuman.toml"),
-    ];
-    // prefers .config/ over .resuman/ over .resuman.toml
-    for path in &config_paths {
-        if path.exists() {
-            let file = std::fs::read_to_string(path)?;
-            return Ok(toml::from_str(&file)?);
+    ])
+}
+
+pub fn locate_config(config_path: Option<PathBuf>) -> crate::Result<ConfigLocation> {
+    if let Some(path) = config_path {
+        if !path.exists() {
+            return Err(Error::ConfigNotFound(vec![path]));
         }
+        return Ok(ConfigLocation {
+            path,
+            explicit: true,
+            candidates: Vec::new(),
+        });
+    }
+
+    let candidates = config_candidates()?;
+    match candidates.iter().find(|path| path.exists()) {
+        Some(path) => Ok(ConfigLocation {
+            path: path.clone(),
+            explicit: false,
+            candidates,
+        }),
+        // must have a config file to use resuman... specify root_dir
+        None => Err(Error::ConfigNotFound(candidates)),
     }
-    // must have a config file to use resuman... specify root_dir
-    Err(anyhow::anyhow!("Cannot find a config file"))
 }
 
-pub fn resolve_path(mut path: PathBuf) -> Result<PathBuf> {
-    // expand ~ as home_dir
-    let home =
-        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
-    log::debug!("Home directory: {:?}", home);
+/// Only the config file, without the env vars and --set flags
+pub fn parse_config(path: &Path) -> crate::Result<Data> {
+    let file = fs::read_to_string(path)?;
+    let mut data: Data =
+        toml::from_str(&file).map_err(|e| Error::ConfigParse(path.to_path_buf(), e))?;
+    data.base_dir = Some(config_dir(path)?);
+    Ok(data)
+}
+
+// the absolute folder of a config file
+fn config_dir(path: &Path) -> crate::Result<PathBuf> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    Ok(std::env::current_dir()?.join(dir))
+}
+
+pub fn home_dir() -> crate::Result<PathBuf> {
+    dirs::home_dir().ok_or_else(|| Error::Invalid("Could not determine home directory".to_string()))
+}
+
+/// Expands `~`, `$VAR` and `${VAR}`, and makes a relative path relative to `base`
+///
+/// Without a base, a relative path is an error, this is a CLI tool, we want to be explicit.
+pub fn resolve_path(path: PathBuf, base: Option<&Path>) -> crate::Result<PathBuf> {
+    let mut path = expand_vars(path)?;
 
+    // expand ~ as home_dir
     if let Some(str_path) = path.to_str() {
         if str_path.starts_with("~/") || str_path == "~" {
+            let home = home_dir()?;
+            log::debug!("Home directory: {:?}", home);
             // Replace `~` with the home directory
-            path = home.join(&str_path[2..]); // Remove `~/` and append the rest
+            path = home.join(str_path.get(2..).unwrap_or("")); // Remove `~/` and append the rest
         }
     }
 
-    // if the path is relative just throw an error
-    // this is a CLI tool, we want to be explicit
     if !path.is_absolute() {
-        return Err(anyhow::anyhow!("Path must be absolute"));
+        match base {
+            Some(base) => path = base.join(path),
+            None => return Err(Error::InvalidPath(path, "must be absolute".to_string())),
+        }
     }
 
     Ok(path)
 }
+
+// $VAR and ${VAR}, a $ that is not followed by a name is kept
+fn expand_vars(path: PathBuf) -> crate::Result<PathBuf> {
+    let Some(str_path) = path.to_str().filter(|p| p.contains('$')) else {
+        return Ok(path);
+    };
+
+    let mut expanded = String::new();
+    let mut rest = str_path;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        if name.is_empty() {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        let value = std::env::var(name)
+            .map_err(|_| Error::InvalidPath(path.clone(), format!("${} is not set", name)))?;
+        expanded.push_str(&value);
+        rest = &after[len..];
+    }
+    expanded.push_str(rest);
+    Ok(PathBuf::from(expanded))
+}
+
+/// The editor to open files in, $VISUAL, then $EDITOR, then vi
+pub fn editor() -> String {
+    std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or("vi".to_string())
+}
+
+/// Opens `path` in `editor` and waits for it to exit
+///
+/// On unix the editor runs through the shell, so values like `code --wait` or a quoted
+/// path with spaces work like they do in a terminal.
+pub fn open_in_editor(editor: &str, path: &Path) -> Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut parts = editor.split_whitespace();
+        let mut command = Command::new(parts.next().unwrap_or("notepad"));
+        command.args(parts);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("sh");
+        command
+    };
+    let status = command
+        .arg(path)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Could not run {}: {}", editor, e))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("{} exited with {}", editor, status));
+    }
+    Ok(())
+}
+
+/// Hands a path or URL to the system opener (open, xdg-open or url.dll on Windows)
+pub fn open_with_system(target: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        // not `cmd /C start`, cmd would run whatever follows a & in a URL
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let status = command
+        .arg(target)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Could not run the system opener: {}", e))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("System opener failed to open {}", target));
+    }
+    Ok(())
+}
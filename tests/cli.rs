@@ -0,0 +1,1411 @@
+// End-to-end tests, every command runs the real binary against a throwaway root_dir
+// Snapshots live in tests/snapshots/, run with UPDATE_SNAPSHOTS=1 to rewrite them
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use serde_json::Value;
+use sqlx::{sqlite::SqliteConnectOptions, SqlitePool};
+use tempfile::TempDir;
+
+const TEMPLATE: &str = "\\documentclass{article}\n\\begin{document}\nHello\n\\end{document}\n";
+const LETTER: &str = "Dear {company},\nI would like to be your {position}.\n";
+
+struct Env {
+    dir: TempDir,
+    config: PathBuf,
+}
+
+impl Env {
+    /// A root_dir with a default resume and cover letter template
+    fn new() -> Env {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("resumes");
+        fs::create_dir_all(root.join("templates/letters")).unwrap();
+        fs::write(root.join("templates/default.tex"), TEMPLATE).unwrap();
+        fs::write(root.join("templates/letters/default.txt"), LETTER).unwrap();
+
+        let config = dir.path().join("config.toml");
+        fs::write(
+            &config,
+            format!(
+                "[main]\nroot_dir = {:?}\ndb_path = \"resumes.sqlite\"\ndefault_group = \"work\"\n",
+                root
+            ),
+        )
+        .unwrap();
+        Env { dir, config }
+    }
+
+    fn root(&self) -> PathBuf {
+        self.dir.path().join("resumes")
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        resuman()
+            .arg("--config")
+            .arg(&self.config)
+            .args(args)
+            .env("RUST_LOG", "off")
+            .output()
+            .unwrap()
+    }
+
+    /// Runs a command that must succeed and returns its stdout
+    fn ok(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        assert!(
+            output.status.success(),
+            "resuman {:?} failed:\n{}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    /// Creates a resume and returns the path of its .tex file
+    fn create(&self, args: &[&str]) -> PathBuf {
+        let mut all = vec!["create"];
+        all.extend(args);
+        PathBuf::from(self.ok(&all).trim())
+    }
+
+    async fn pool(&self) -> SqlitePool {
+        let options = SqliteConnectOptions::new().filename(self.root().join("resumes.sqlite"));
+        SqlitePool::connect_with(options).await.unwrap()
+    }
+
+    /// Replaces everything that changes between runs
+    fn redact(&self, output: &str) -> String {
+        output
+            .replace(&self.root().display().to_string(), "<root>")
+            .replace(&today(), "<date>")
+    }
+}
+
+/// The binary with only PATH and HOME left of the environment, so RESUMAN_* variables,
+/// EDITOR and the like of whoever runs the tests never leak in
+fn resuman() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_resuman"));
+    command.env_clear().env("RUST_BACKTRACE", "0");
+    for var in ["PATH", "HOME"] {
+        if let Some(value) = std::env::var_os(var) {
+            command.env(var, value);
+        }
+    }
+    command
+}
+
+fn today() -> String {
+    chrono::Utc::now().format("%Y-%m-%d").to_string()
+}
+
+fn metadata(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.txt", name));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing snapshot {:?}, run with UPDATE_SNAPSHOTS=1", path));
+    assert_eq!(expected, actual, "snapshot {} does not match", name);
+}
+
+#[tokio::test]
+async fn create_writes_folder_metadata_and_row() {
+    let env = Env::new();
+    let file = env.create(&["-c", "Acme", "-p", "backend", "--tag", "Remote"]);
+
+    let date = today();
+    let folder = env.root().join(format!("work/Acme_backend_{}", date));
+    assert_eq!(
+        file,
+        folder.join(format!("resume_Acme_backend_{}.tex", date))
+    );
+    assert_eq!(fs::read_to_string(&file).unwrap(), TEMPLATE);
+
+    let meta = metadata(&folder.join("metadata.json"));
+    assert_eq!(meta["company"], "Acme");
+    assert_eq!(meta["position"], "backend");
+    assert_eq!(meta["group"], "work");
+    assert_eq!(meta["template"], "default");
+    assert_eq!(meta["status"], "applying");
+    assert_eq!(meta["length"], 16);
+    assert_eq!(meta["has_cover_letter"], false);
+    assert_eq!(meta["tags"], serde_json::json!(["remote"]));
+
+    let pool = env.pool().await;
+    let (company, group, path): (String, String, String) =
+        sqlx::query_as(r#"SELECT company, "group", file_path FROM resumes"#)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(company, "Acme");
+    assert_eq!(group, "work");
+    assert_eq!(PathBuf::from(path), file);
+    let status: String = sqlx::query_scalar("SELECT status FROM metadata")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "applying");
+}
+
+#[tokio::test]
+async fn create_renders_cover_letter() {
+    let env = Env::new();
+    let file = env.create(&["-c", "Acme", "-p", "swe", "--letter"]);
+    let folder = file.parent().unwrap();
+
+    let letter = folder.join("cover_letter.txt");
+    assert_eq!(
+        fs::read_to_string(&letter).unwrap(),
+        "Dear Acme,\nI would like to be your swe.\n"
+    );
+    let meta = metadata(&folder.join("metadata.json"));
+    assert_eq!(meta["has_cover_letter"], true);
+
+    let stored: Option<String> = sqlx::query_scalar("SELECT cover_letter_path FROM resumes")
+        .fetch_one(&env.pool().await)
+        .await
+        .unwrap();
+    assert_eq!(stored.map(PathBuf::from), Some(letter));
+}
+
+#[test]
+fn update_writes_a_letter_only_when_the_update_goes_through() {
+    let env = Env::new();
+    let broken = env.create(&["-c", "Acme", "-p", "swe"]);
+    let fine = env.create(&["-c", "Globex", "-p", "swe"]);
+    let broken = broken.parent().unwrap();
+    fs::write(broken.join("metadata.json"), "{ not json").unwrap();
+
+    let output = env.run(&["update", "1", "--letter", "true"]);
+    assert_eq!(output.status.code(), Some(11));
+    assert!(!broken.join("cover_letter.txt").exists());
+
+    env.ok(&["update", "2", "--letter", "true"]);
+    let letter = fine.parent().unwrap().join("cover_letter.txt");
+    assert_eq!(
+        fs::read_to_string(letter).unwrap(),
+        "Dear Globex,\nI would like to be your swe.\n"
+    );
+}
+
+#[test]
+fn create_with_spaces_in_company() {
+    let env = Env::new();
+    let file = env.create(&["-c", "Jane Street", "-p", "quant trader"]);
+
+    assert!(file.exists());
+    assert_eq!(
+        file.parent()
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_string_lossy(),
+        format!("Jane-Street_quant-trader_{}", today())
+    );
+    assert_eq!(env.ok(&["find", "-c", "Jane Street"]), "1\n");
+    assert_eq!(
+        env.ok(&["list", "all", "-c"]).lines().last(),
+        Some("Jane Street")
+    );
+}
+
+#[tokio::test]
+async fn duplicate_create_fails_and_keeps_the_first() {
+    let env = Env::new();
+    let file = env.create(&["-c", "Acme"]);
+    fs::write(&file, "edited").unwrap();
+
+    let output = env.run(&["create", "-c", "Acme"]);
+    assert_eq!(output.status.code(), Some(7));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Resume already exists"));
+
+    assert_eq!(fs::read_to_string(&file).unwrap(), "edited");
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM resumes")
+        .fetch_one(&env.pool().await)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn create_with_missing_template_leaves_nothing_behind() {
+    let env = Env::new();
+    let output = env.run(&["create", "-c", "Acme", "-t", "nope"]);
+    assert_eq!(output.status.code(), Some(6));
+    assert!(!env.root().join("work").exists());
+    assert_eq!(env.ok(&["list", "all", "-i"]), "id\n");
+}
+
+#[tokio::test]
+async fn update_changes_metadata_and_row() {
+    let env = Env::new();
+    let file = env.create(&["-c", "Acme"]);
+    let metadata_path = file.parent().unwrap().join("metadata.json");
+
+    let out = env.ok(&[
+        "update",
+        "1",
+        "-s",
+        "interview",
+        "-l",
+        "Toronto",
+        "--tag",
+        "dream",
+        "-n",
+        "met at a career fair",
+    ]);
+    assert_eq!(PathBuf::from(out.trim()), metadata_path);
+
+    let meta = metadata(&metadata_path);
+    assert_eq!(meta["status"], "interview");
+    assert_eq!(meta["location"], "Toronto");
+    assert_eq!(meta["notes"], "met at a career fair");
+    assert_eq!(meta["tags"], serde_json::json!(["dream"]));
+
+    let (status, location): (String, Option<String>) =
+        sqlx::query_as("SELECT status, location FROM metadata WHERE resume_id = 1")
+            .fetch_one(&env.pool().await)
+            .await
+            .unwrap();
+    assert_eq!(status, "interview");
+    assert_eq!(location.as_deref(), Some("Toronto"));
+
+    // removing the tag again
+    env.ok(&["update", "1", "--tag", "-dream"]);
+    assert_eq!(metadata(&metadata_path)["tags"], serde_json::json!([]));
+}
+
+#[test]
+fn update_unknown_id_fails() {
+    let env = Env::new();
+    let output = env.run(&["update", "42", "-s", "applied"]);
+    assert_eq!(output.status.code(), Some(8));
+}
+
+#[test]
+fn list_commands_read_db_and_fs() {
+    let env = Env::new();
+    env.create(&["-c", "Acme"]);
+    env.create(&["-c", "Globex", "-g", "hackathons", "-p", "hacker"]);
+
+    assert_eq!(env.ok(&["list", "group"]), "work\nhackathons\n");
+    let mut groups: Vec<String> = env
+        .ok(&["list", "group", "--fs"])
+        .lines()
+        .map(String::from)
+        .collect();
+    groups.sort();
+    assert_eq!(groups, ["hackathons", "templates", "work"]);
+
+    assert_eq!(env.ok(&["list", "template"]), "default\n");
+    assert_eq!(env.ok(&["list", "template", "--fs"]), "default\n");
+
+    let date = today();
+    assert_eq!(
+        env.ok(&["list", "flatten"]),
+        format!(
+            "{}\n{}\n",
+            env.root()
+                .join(format!("work/Acme_swe_{0}/resume_Acme_swe_{0}.tex", date))
+                .display(),
+            env.root()
+                .join(format!(
+                    "hackathons/Globex_hacker_{0}/resume_Globex_hacker_{0}.tex",
+                    date
+                ))
+                .display()
+        )
+    );
+}
+
+#[test]
+fn list_all_snapshot() {
+    let env = Env::new();
+    env.create(&["-c", "Acme", "-p", "backend", "--tag", "remote", "--letter"]);
+    env.create(&["-c", "Jane Street", "-g", "finance", "-l", "New York"]);
+    env.create(&[
+        "-c",
+        "Globex",
+        "-s",
+        "applied",
+        "-u",
+        "https://globex.example/jobs/1",
+    ]);
+
+    // created_at has a time of day, so it is left out
+    let columns = [
+        "list", "all", "-i", "-g", "-t", "-c", "--letter", "-f", "-a", "-m", "--length", "-l",
+        "-s", "-u", "-n", "--tags",
+    ];
+    assert_snapshot("list_all", &env.redact(&env.ok(&columns)));
+    assert_snapshot(
+        "list_all_tag",
+        &env.redact(&env.ok(&["list", "all", "-i", "-c", "--tag", "remote"])),
+    );
+}
+
+#[test]
+fn find_snapshot() {
+    let env = Env::new();
+    env.create(&["-c", "Acme", "-p", "backend", "--tag", "remote"]);
+    env.create(&["-c", "Jane Street", "-g", "finance", "-l", "New York"]);
+    env.create(&["-c", "Acme Corp", "-p", "frontend", "-s", "applied"]);
+
+    let mut out = String::new();
+    for args in [
+        vec!["-c", "Acme"],
+        vec!["-c", "Acme", "-p", "front"],
+        vec!["-g", "finance"],
+        vec!["-s", "applied"],
+        vec!["--tag", "remote"],
+        vec!["-a", "New York"],
+        vec!["-c", "Initech"],
+    ] {
+        let mut all = vec!["find"];
+        all.extend(&args);
+        out.push_str(&format!("$ resuman {}\n{}", all.join(" "), env.ok(&all)));
+    }
+    assert_snapshot("find", &env.redact(&out));
+}
+
+#[tokio::test]
+async fn delete_removes_row_and_optionally_files() {
+    let env = Env::new();
+    let kept = env.create(&["-c", "Acme"]);
+    let removed = env.create(&["-c", "Globex"]);
+
+    env.ok(&["delete", "1"]);
+    assert!(kept.exists());
+    env.ok(&["delete", "2", "--file"]);
+    assert!(!removed.parent().unwrap().exists());
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM resumes")
+        .fetch_one(&env.pool().await)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+    assert_eq!(env.run(&["delete", "1"]).status.code(), Some(8));
+}
+
+#[tokio::test]
+async fn clean_drops_rows_of_missing_files() {
+    let env = Env::new();
+    let gone = env.create(&["-c", "Acme"]);
+    env.create(&["-c", "Globex"]);
+    fs::remove_dir_all(gone.parent().unwrap()).unwrap();
+
+    env.ok(&["clean"]);
+    let companies: Vec<String> = sqlx::query_scalar("SELECT company FROM resumes")
+        .fetch_all(&env.pool().await)
+        .await
+        .unwrap();
+    assert_eq!(companies, ["Globex"]);
+}
+
+#[test]
+fn init_without_config_is_idempotent() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config/resuman.toml");
+    let root = dir.path().join("resumes");
+    let init = || {
+        let output = resuman()
+            .arg("--config")
+            .arg(&config)
+            .arg("init")
+            .arg("--root-dir")
+            .arg(&root)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .replace(&dir.path().display().to_string(), "<tmp>")
+    };
+
+    let first = init();
+    assert_eq!(
+        first.lines().filter(|l| l.starts_with("created\t")).count(),
+        7,
+        "{}",
+        first
+    );
+    for path in [
+        "config/resuman.toml",
+        "resumes/templates/default.tex",
+        "resumes/templates/letters/default.txt",
+        "resumes/resumandb.sqlite",
+    ] {
+        assert!(dir.path().join(path).is_file(), "{} is missing", path);
+    }
+    let written = fs::read_to_string(&config).unwrap();
+    assert!(written.starts_with("# resuman config"));
+    assert!(written.contains(&format!("root_dir = {:?}", root)));
+
+    // a second run keeps what the user may have changed
+    fs::write(root.join("templates/default.tex"), "mine").unwrap();
+    let second = init();
+    assert_eq!(second, first.replace("created\t", "exists\t"));
+    assert_eq!(
+        fs::read_to_string(root.join("templates/default.tex")).unwrap(),
+        "mine"
+    );
+
+    // and the result is usable right away
+    let env = Env {
+        dir,
+        config: config.clone(),
+    };
+    let file = env.create(&["-c", "Acme", "--letter"]);
+    assert!(file.parent().unwrap().join("cover_letter.txt").is_file());
+}
+
+#[test]
+fn missing_config_fails_with_hint() {
+    let env = Env::new();
+    let output = resuman()
+        .args(["--config"])
+        .arg(env.dir.path().join("nope.toml"))
+        .args(["list", "group"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Hint:"));
+}
+
+#[test]
+fn config_set_keeps_comments() {
+    let env = Env::new();
+    let contents = fs::read_to_string(&env.config).unwrap().replace(
+        "default_group = \"work\"",
+        "# where new resumes go\ndefault_group = \"work\" # for now",
+    );
+    fs::write(&env.config, contents).unwrap();
+
+    env.ok(&["config", "set", "main.default_group", "fulltime"]);
+    env.ok(&["config", "set", "default_length", "12"]);
+    let contents = fs::read_to_string(&env.config).unwrap();
+    assert!(contents.contains("# where new resumes go\ndefault_group = \"fulltime\" # for now\n"));
+    assert!(contents.contains("default_length = 12\n"));
+
+    let show = env.ok(&["config", "show"]);
+    assert!(show.contains("default_group = \"fulltime\" # [main]\n"));
+    assert!(show.contains("default_status = \"applying\" # default\n"));
+
+    // the type is checked before writing
+    assert_eq!(
+        env.run(&["config", "set", "default_length", "long"])
+            .status
+            .code(),
+        Some(2)
+    );
+    assert_eq!(
+        env.run(&["config", "set", "nope", "1"]).status.code(),
+        Some(2)
+    );
+    assert!(fs::read_to_string(&env.config)
+        .unwrap()
+        .contains("default_length = 12\n"));
+}
+
+#[test]
+fn config_validate_reports_bad_patterns() {
+    let env = Env::new();
+    env.create(&["-c", "Acme"]);
+    assert!(!env.ok(&["config", "validate"]).contains("error"));
+    assert_eq!(
+        env.ok(&["config", "path"]).trim(),
+        env.config.display().to_string()
+    );
+
+    env.ok(&["config", "set", "folder_pattern", "{compnay}_{date}"]);
+    env.ok(&["config", "set", "date_format", "%Y/%m/%d"]);
+    let output = env.run(&["config", "validate"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout
+        .contains("error\tfolder_pattern \"{compnay}_{date}\" has unknown placeholders {compnay}"));
+    assert!(stdout.contains("error\tdate_format \"%Y/%m/%d\""));
+}
+
+#[test]
+fn profiles_keep_separate_roots() {
+    let env = Env::new();
+    let mut contents = fs::read_to_string(&env.config).unwrap();
+    contents.push_str("\n[profiles.fulltime]\ndefault_length = 52\n");
+    fs::write(&env.config, contents).unwrap();
+
+    env.create(&["-c", "Acme"]);
+    env.ok(&["--profile", "fulltime", "init"]);
+    let file = PathBuf::from(
+        env.ok(&["--profile", "fulltime", "create", "-c", "Globex"])
+            .trim(),
+    );
+    // without a root_dir of its own, a profile lives in a folder of the main one
+    assert!(file.starts_with(env.root().join("fulltime")));
+    assert_eq!(
+        metadata(&file.parent().unwrap().join("metadata.json"))["length"],
+        52
+    );
+
+    assert_eq!(env.ok(&["list", "all", "-c"]), "company\nAcme\n");
+    env.ok(&["profile", "use", "fulltime"]);
+    assert_eq!(env.ok(&["list", "all", "-c"]), "company\nGlobex\n");
+    assert_eq!(
+        env.redact(&env.ok(&["profile", "list"])),
+        "  main\t<root>\n* fulltime\t<root>/fulltime\n"
+    );
+    // --profile wins over the default
+    assert_eq!(
+        env.ok(&["--profile", "main", "list", "all", "-c"]),
+        "company\nAcme\n"
+    );
+
+    let stats = env.ok(&["stats", "--all-profiles"]);
+    assert!(stats.starts_with("total\t2\n"), "{}", stats);
+    assert!(stats.contains("profile\tcount\nmain\t1\nfulltime\t1\n"));
+
+    assert_eq!(
+        env.run(&["--profile", "nope", "list", "group"])
+            .status
+            .code(),
+        Some(2)
+    );
+}
+
+#[test]
+fn env_vars_and_set_override_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let resuman = |config: Option<&Path>, args: &[&str]| {
+        let mut command = resuman();
+        if let Some(config) = config {
+            command.arg("--config").arg(config);
+        }
+        let output = command
+            .args(args)
+            .current_dir(dir.path())
+            .env("HOME", dir.path())
+            .env("TRACKER", dir.path().join("tracker"))
+            .env("RESUMAN_ROOT_DIR", "$TRACKER/env")
+            .env("RESUMAN_DEFAULT_GROUP", "from-env")
+            .env("RESUMAN_DEFAULT_LENGTH", "20")
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // no config file at all, the env is enough
+    let show = resuman(None, &["--set", "default_group=from-set", "config", "show"]);
+    let root = dir.path().join("tracker/env");
+    assert!(show.contains(&format!("root_dir = {:?} # RESUMAN_ROOT_DIR\n", root)));
+    assert!(show.contains("default_group = \"from-set\" # --set\n"));
+    assert!(show.contains("default_length = 20 # RESUMAN_DEFAULT_LENGTH\n"));
+
+    // the env wins over the file, and relative paths are relative to the file
+    let config = dir.path().join("conf/resuman.toml");
+    fs::create_dir_all(config.parent().unwrap()).unwrap();
+    fs::write(
+        &config,
+        "[main]\nroot_dir = \"resumes\"\ndefault_group = \"from-file\"\ndefault_status = \"applied\"\n",
+    )
+    .unwrap();
+    let show = resuman(Some(&config), &["config", "show"]);
+    assert!(show.contains("default_group = \"from-env\" # RESUMAN_DEFAULT_GROUP\n"));
+    assert!(show.contains("default_status = \"applied\" # [main]\n"));
+
+    resuman(Some(&config), &["--set", "root_dir=resumes", "init"]);
+    let file = PathBuf::from(
+        resuman(
+            Some(&config),
+            &["--set", "root_dir=resumes", "create", "-c", "Acme"],
+        )
+        .trim(),
+    );
+    assert!(file.starts_with(dir.path().join("conf/resumes/from-env")));
+}
+
+#[tokio::test]
+async fn backup_rotates_and_restore_rolls_back() {
+    let env = Env::new();
+    env.create(&["-c", "Acme"]);
+    let snapshot = env.ok(&["backup"]);
+    let (snapshot, version) = snapshot.trim().split_once('\t').unwrap();
+    assert!(Path::new(snapshot).starts_with(env.root().join("backups")));
+    assert_eq!(version, "20250301120000");
+
+    // only the newest backup_keep are kept
+    for _ in 0..3 {
+        env.ok(&["--set", "backup_keep=2", "backup"]);
+    }
+    assert_eq!(env.ok(&["backup", "--list"]).lines().count(), 2);
+    assert!(!Path::new(snapshot).exists());
+
+    let file = env.dir.path().join("acme.sqlite");
+    env.ok(&["backup", "-o", file.to_str().unwrap()]);
+    env.create(&["-c", "Globex"]);
+    let restored = env.ok(&["restore", file.to_str().unwrap()]);
+    assert!(restored.starts_with("backup\t"), "{}", restored);
+
+    let companies: Vec<String> = sqlx::query_scalar("SELECT company FROM resumes")
+        .fetch_all(&env.pool().await)
+        .await
+        .unwrap();
+    assert_eq!(companies, ["Acme"]);
+
+    // anything that is not a resuman db is refused, and the db is left alone
+    let junk = env.dir.path().join("junk.sqlite");
+    fs::write(&junk, "junk").unwrap();
+    assert_eq!(
+        env.run(&["restore", junk.to_str().unwrap()]).status.code(),
+        Some(2)
+    );
+    assert_eq!(env.ok(&["list", "all", "-c"]), "company\nAcme\n");
+}
+
+#[test]
+fn db_rolls_back_and_migrates() {
+    let env = Env::new();
+    env.create(&["-c", "Acme"]);
+    let states = |args: &[&str]| -> Vec<String> {
+        env.ok(args)
+            .lines()
+            .map(|l| l.split('\t').nth(2).unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(states(&["db", "status"]), ["applied"; 10]);
+
+    let reverted = env.ok(&["db", "rollback", "--to", "20250111120000"]);
+    assert_eq!(reverted.lines().count(), 7, "{}", reverted);
+    assert_eq!(
+        states(&["--no-auto-migrate", "db", "status"]),
+        [
+            "applied", "applied", "applied", "pending", "pending", "pending", "pending", "pending",
+            "pending", "pending"
+        ]
+    );
+
+    // any other command migrates first, and the data survives the round trip
+    assert_eq!(env.ok(&["list", "all", "-c"]), "company\nAcme\n");
+    assert_eq!(states(&["db", "status"]), ["applied"; 10]);
+    // one from before the rollback and one from before migrating again
+    let backups = env.ok(&["backup", "--list"]);
+    assert!(backups.contains("-pre-rollback.sqlite\t20250301120000\n"));
+    assert!(backups.contains("-pre-migration.sqlite\t20250111120000\n"));
+    assert_eq!(
+        env.run(&["db", "rollback", "--to", "5"]).status.code(),
+        Some(2)
+    );
+}
+
+#[test]
+fn urls_take_labels_and_old_metadata_files_are_upgraded() {
+    let env = Env::new();
+    let file = env.create(&[
+        "-c",
+        "Acme",
+        "-u",
+        "portal=Workday=https://acme.wd1.com/jobs?id=1",
+    ]);
+    env.ok(&[
+        "update",
+        "1",
+        "-u",
+        "posting=https://acme.com/careers?q=a=b",
+    ]);
+    assert_eq!(
+        env.ok(&["url", "list", "1"]),
+        "id\tresume_id\tkind\tlabel\turl\n\
+         1\t1\tportal\tWorkday\thttps://acme.wd1.com/jobs?id=1\n\
+         2\t1\tposting\tnull\thttps://acme.com/careers?q=a=b\n"
+    );
+    let path = file.parent().unwrap().join("metadata.json");
+    assert_eq!(metadata(&path)["urls"][0]["label"], "Workday");
+    assert_eq!(env.run(&["url", "rm", "9"]).status.code(), Some(2));
+
+    // a file written before the urls had their own table
+    env.ok(&["db", "rollback", "--to", "20241227203415"]);
+    let mut old = metadata(&path);
+    old["urls"] = "https://acme.wd1.com/jobs?id=1|https://acme.com/careers?q=a=b".into();
+    fs::write(&path, old.to_string()).unwrap();
+
+    // migrating again rewrites it like the db has it, with kinds guessed and no labels
+    env.ok(&["list", "all", "-c"]);
+    let urls = metadata(&path)["urls"].as_array().unwrap().clone();
+    let listed = env.ok(&["url", "list", "1"]);
+    assert_eq!(urls.len(), 2);
+    for u in urls {
+        assert_eq!((&u["kind"], &u["label"]), (&"posting".into(), &Value::Null));
+        assert!(listed.contains(&format!(
+            "\tposting\tnull\t{}\n",
+            u["url"].as_str().unwrap()
+        )));
+    }
+}
+
+#[test]
+fn metadata_keeps_unknown_keys_and_upgrades_old_files() {
+    let env = Env::new();
+    let file = env.create(&["-c", "Acme"]);
+    let path = file.parent().unwrap().join("metadata.json");
+    let meta = metadata(&path);
+    assert_eq!(meta["schema_version"], 2);
+    assert!(meta["created_at"].as_str().unwrap().starts_with(&today()));
+    // whole seconds, the way every timestamp resuman writes looks
+    assert_eq!(meta["created_at"].as_str().unwrap().len(), 20);
+
+    // a file from before schema_version, with a key of its own
+    let mut old = meta.clone();
+    let object = old.as_object_mut().unwrap();
+    object.remove("schema_version");
+    object.insert("created_at".into(), 1700000000.into());
+    object.insert(
+        "urls".into(),
+        "https://acme.com/jobs/1|https://acme.com".into(),
+    );
+    object.insert("referrer".into(), "Jane".into());
+    fs::write(&path, old.to_string()).unwrap();
+
+    assert!(env
+        .ok(&["metadata", "validate"])
+        .starts_with("outdated\t1\t"));
+    assert_eq!(
+        env.ok(&["metadata", "migrate"]),
+        format!("migrated\t1\t{}\n", path.display())
+    );
+    assert_eq!(env.ok(&["metadata", "migrate"]), "");
+    env.ok(&["update", "1", "-s", "applied"]);
+    let meta = metadata(&path);
+    assert_eq!(meta["created_at"], "2023-11-14T22:13:20Z");
+    assert_eq!(meta["referrer"], "Jane");
+    assert_eq!(
+        meta["urls"],
+        serde_json::json!([
+            { "kind": "posting", "label": null, "url": "https://acme.com/jobs/1" },
+            { "kind": "posting", "label": null, "url": "https://acme.com" },
+        ])
+    );
+    assert_eq!(meta["status"], "applied");
+
+    // a hand edit with the wrong type is named instead of failing somewhere in serde
+    let mut bad = meta.clone();
+    bad["length"] = "abc".into();
+    fs::write(&path, bad.to_string()).unwrap();
+    let output = env.run(&["update", "1", "-s", "oa"]);
+    assert_eq!(output.status.code(), Some(11));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("length must be a whole number of weeks, not \"abc\""));
+    assert_eq!(env.run(&["metadata", "validate"]).status.code(), Some(2));
+
+    // every key resuman writes is in the published schema
+    let schema: Value = serde_json::from_str(&env.ok(&["metadata", "schema"])).unwrap();
+    for key in meta
+        .as_object()
+        .unwrap()
+        .keys()
+        .filter(|k| *k != "referrer")
+    {
+        assert!(
+            schema["properties"].get(key).is_some(),
+            "{} is missing",
+            key
+        );
+    }
+}
+
+#[test]
+fn edit_with_update_flags_still_updates() {
+    let env = Env::new();
+    env.create(&["-c", "Acme"]);
+    let output = env.run(&["edit", "1", "--status", "applied"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("use `resuman update`"));
+    assert_eq!(env.ok(&["list", "all", "-s"]), "status\napplied\n");
+    // without them it is the editor
+    assert!(!env.run(&["edit"]).status.success());
+
+    // only the subcommand is forwarded, not an option value that happens to be edit
+    let mut contents = fs::read_to_string(&env.config).unwrap();
+    contents.push_str("\n[profiles.edit]\n");
+    fs::write(&env.config, contents).unwrap();
+    env.ok(&["--profile", "edit", "init"]);
+    env.ok(&["--profile", "edit", "create", "-c", "Globex"]);
+    env.ok(&["--profile", "edit", "edit", "1", "--status", "offer"]);
+    assert_eq!(
+        env.ok(&["--profile", "edit", "list", "all", "-s"]),
+        "status\noffer\n"
+    );
+    assert_eq!(env.ok(&["list", "all", "-s"]), "status\napplied\n");
+}
+
+#[test]
+fn edit_applies_the_changes_and_reopens_on_errors() {
+    let env = Env::new();
+    env.create(&["-c", "Acme", "--tag", "remote"]);
+
+    // the first save has a wrong type, the second one fixes it
+    let editor = env.dir.path().join("editor.sh");
+    let count = env.dir.path().join("count");
+    fs::write(
+        &editor,
+        format!(
+            "#!/bin/sh\necho x >> {count:?}\ncp \"$1\" \"$1.$(wc -l < {count:?} | tr -d ' ')\"\n\
+             edit() {{ sed \"$1\" \"$2\" > \"$2.new\" && mv \"$2.new\" \"$2\"; }}\n\
+             if [ $(wc -l < {count:?}) = 1 ]; then edit 's/\"length\": 16/\"length\": \"long\"/' \"$1\"\n\
+             else edit 's/\"long\"/20/; s/\"applying\"/\"applied\"/; s/\"remote\"/\"Remote\", \"onsite\"/' \"$1\"; fi\n"
+        ),
+    )
+    .unwrap();
+    Command::new("chmod")
+        .arg("+x")
+        .arg(&editor)
+        .status()
+        .unwrap();
+
+    let output = resuman()
+        .arg("--config")
+        .arg(&env.config)
+        .args(["edit", "1"])
+        .env("EDITOR", &editor)
+        .env("TMPDIR", env.dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "length\t16\t20\nstatus\t\"applying\"\t\"applied\"\ntags\t[\"remote\"]\t[\"onsite\",\"remote\"]\n"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid edit"));
+
+    // the second time the editor shows the error on top of what was typed
+    let second = fs::read_dir(env.dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|p| p.to_string_lossy().ends_with(".json.2"))
+        .unwrap();
+    let second = fs::read_to_string(second).unwrap();
+    assert!(second.starts_with("// Error: invalid type: string \"long\""));
+    assert!(second.contains("\"length\": \"long\""));
+
+    assert_eq!(env.ok(&["list", "all", "-s"]), "status\napplied\n");
+    let meta = metadata(
+        &env.root()
+            .join(format!("work/Acme_swe_{}/metadata.json", today())),
+    );
+    assert_eq!(meta["length"], 20);
+    assert_eq!(meta["tags"], serde_json::json!(["onsite", "remote"]));
+}
+
+#[test]
+fn watch_once_follows_moves_edits_and_copies() {
+    let env = Env::new();
+    env.create(&["-c", "Acme"]);
+    let beta = env.create(&["-c", "Beta"]);
+    let gamma = env.create(&["-c", "Gamma"]);
+    let work = env.root().join("work");
+
+    // a renamed folder, an edited and a copied one, and one that is gone
+    fs::rename(
+        work.join(format!("Acme_swe_{}", today())),
+        work.join("acme"),
+    )
+    .unwrap();
+    let beta = beta.parent().unwrap();
+    let meta = fs::read_to_string(beta.join("metadata.json")).unwrap();
+    fs::write(
+        beta.join("metadata.json"),
+        meta.replace("\"applying\"", "\"rejected\""),
+    )
+    .unwrap();
+    let copy = work.join("delta");
+    fs::create_dir(&copy).unwrap();
+    for entry in fs::read_dir(beta).unwrap() {
+        let path = entry.unwrap().path();
+        fs::copy(&path, copy.join(path.file_name().unwrap())).unwrap();
+    }
+    let meta = fs::read_to_string(copy.join("metadata.json")).unwrap();
+    fs::write(
+        copy.join("metadata.json"),
+        meta.replace("\"Beta\"", "\"Delta\""),
+    )
+    .unwrap();
+    fs::remove_dir_all(gamma.parent().unwrap()).unwrap();
+
+    let output = env.ok(&["watch", "--once"]);
+    let changes: Vec<String> = output
+        .lines()
+        .map(|l| env.redact(l.split_once('\t').unwrap().1))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            "updated\t2\tstatus",
+            "moved\t1\t<root>/work/Acme_swe_<date>/resume_Acme_swe_<date>.tex\t<root>/work/acme/resume_Acme_swe_<date>.tex",
+            "inserted\t4\t<root>/work/delta/resume_Beta_swe_<date>.tex",
+            "missing\t3\t<root>/work/Gamma_swe_<date>/resume_Gamma_swe_<date>.tex",
+        ]
+    );
+    assert_eq!(
+        env.ok(&["list", "all", "-c", "-s"]),
+        "company\tstatus\nAcme\tapplying\nBeta\trejected\nGamma\tapplying\nDelta\trejected\n"
+    );
+
+    // nothing changed since, so a second sync only reports what is still missing
+    let output = env.ok(&["watch", "--once"]);
+    assert_eq!(output.lines().count(), 1);
+    assert!(output.contains("missing\t3"));
+}
+
+#[test]
+fn git_keeps_every_version_and_checkout_restores_one() {
+    let env = Env::new();
+    let mut config = fs::read_to_string(&env.config).unwrap();
+    config.push_str("git = true\n");
+    fs::write(&env.config, config).unwrap();
+
+    let tex = env.create(&["-c", "Acme"]);
+    let original = fs::read_to_string(&tex).unwrap();
+    fs::write(&tex, format!("{}% sent\n", original)).unwrap();
+    env.ok(&["update", "1", "-s", "applied"]);
+    fs::write(&tex, format!("{}% after\n", original)).unwrap();
+    env.ok(&["update", "1", "--notes", "reworded"]);
+
+    let log = env.ok(&["log", "1"]);
+    let rows: Vec<Vec<&str>> = log
+        .lines()
+        .skip(1)
+        .map(|l| l.split('\t').collect())
+        .collect();
+    let subjects: Vec<&str> = rows.iter().map(|r| r[3]).collect();
+    assert_eq!(
+        subjects,
+        [
+            "Update resume 1 (Acme swe): notes",
+            "Update resume 1 (Acme swe): status",
+            "Create resume 1 (Acme swe)"
+        ]
+    );
+    assert_eq!(rows[1][2], "resume-1-applied");
+    assert!(env.root().join(".gitignore").exists());
+
+    // the sent version comes back, the metadata stays the way the db has it
+    env.ok(&["checkout", "1", "--at", "applied"]);
+    assert_eq!(
+        fs::read_to_string(&tex).unwrap(),
+        format!("{}% sent\n", original)
+    );
+    assert_eq!(
+        metadata(&tex.with_file_name("metadata.json"))["notes"],
+        "reworded"
+    );
+    env.ok(&["checkout", "1", "--at", rows[2][0]]);
+    assert_eq!(fs::read_to_string(&tex).unwrap(), original);
+    assert_eq!(env.ok(&["log", "1"]).lines().count(), 6);
+
+    let output = env.run(&["checkout", "1", "--at", "nope"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn git_commits_the_pdf_of_a_build_but_not_its_byproducts() {
+    let env = Env::new();
+    let mut config = fs::read_to_string(&env.config).unwrap();
+    config.push_str("git = true\n");
+    config.push_str(
+        "build_command = 'f() { cp \"$1\" \"${1%.tex}.pdf\"; touch \"${1%.tex}.aux\"; }; f'\n",
+    );
+    fs::write(&env.config, config).unwrap();
+    let tex = env.create(&["-c", "Acme"]);
+
+    env.ok(&["build", "1"]);
+    let log = env.ok(&["log", "1"]);
+    assert_eq!(
+        log.lines().nth(1).unwrap().split('\t').nth(3),
+        Some("Build resume 1 (Acme swe)")
+    );
+    let tracked = Command::new("git")
+        .arg("-C")
+        .arg(env.root())
+        .args(["ls-files"])
+        .output()
+        .unwrap();
+    let tracked = String::from_utf8_lossy(&tracked.stdout);
+    let name = |ext| {
+        tex.with_extension(ext)
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string()
+    };
+    assert!(tracked.contains(&name("pdf")), "{}", tracked);
+    assert!(!tracked.contains(&name("aux")), "{}", tracked);
+
+    // nothing new to commit, nothing committed
+    env.ok(&["build", "1"]);
+    assert_eq!(env.ok(&["log", "1"]), log);
+}
+
+#[test]
+fn build_makes_the_pdfs_of_resumes_and_tex_letters() {
+    let env = Env::new();
+    // a stand-in for latexmk, the "PDF" is a copy of the source
+    let mut config = fs::read_to_string(&env.config).unwrap();
+    config.push_str("build_command = 'f() { cp \"$1\" \"${1%.tex}.pdf\"; }; f'\n");
+    fs::write(&env.config, config).unwrap();
+    fs::write(
+        env.root().join("templates/letters/formal.tex"),
+        "Dear {company}\n",
+    )
+    .unwrap();
+    let acme = env.create(&["-c", "Acme", "--letter-template", "formal"]);
+    let globex = env.create(&["-c", "Globex", "--letter"]);
+
+    let letter = acme.with_file_name("cover_letter.pdf");
+    assert_eq!(
+        env.ok(&["build", "1", "2"]),
+        format!(
+            "{}\n{}\n{}\n",
+            acme.with_extension("pdf").display(),
+            letter.display(),
+            globex.with_extension("pdf").display()
+        )
+    );
+    assert_eq!(fs::read_to_string(letter).unwrap(), "Dear Acme\n");
+    assert!(!globex.with_file_name("cover_letter.pdf").exists());
+
+    let failed = env.run(&["--set", "build_command=false", "build", "2"]);
+    assert_eq!(failed.status.code(), Some(14));
+    assert!(String::from_utf8_lossy(&failed.stderr).contains("false exited with exit status: 1"));
+    assert_eq!(env.run(&["build", "9"]).status.code(), Some(8));
+}
+
+#[tokio::test]
+async fn submit_freezes_the_files_and_diff_shows_later_edits() {
+    let env = Env::new();
+    let tex = env.create(&["-c", "Acme", "--letter"]);
+    let pdf = tex.with_extension("pdf");
+    let frozen = tex.parent().unwrap().join("submitted");
+
+    // a copy that fails leaves no folder behind, so submit can be tried again
+    fs::create_dir(&pdf).unwrap();
+    assert_eq!(env.run(&["submit", "1"]).status.code(), Some(10));
+    assert!(!frozen.exists());
+    assert!(!tex.parent().unwrap().join(".submitted.partial").exists());
+    assert_eq!(env.ok(&["list", "all", "-s"]), "status\napplying\n");
+    fs::remove_dir(&pdf).unwrap();
+    fs::write(&pdf, b"%PDF-1.4\n\xff\xfe").unwrap();
+
+    let out = env.ok(&["submit", "1"]);
+    let kinds: Vec<&str> = out.lines().map(|l| l.split('\t').next().unwrap()).collect();
+    assert_eq!(kinds, ["resume", "pdf", "cover_letter"]);
+    assert!(fs::metadata(frozen.join(tex.file_name().unwrap()))
+        .unwrap()
+        .permissions()
+        .readonly());
+    assert_eq!(env.ok(&["list", "all", "-s"]), "status\napplied\n");
+    let sums: Vec<(String, String)> =
+        sqlx::query_as("SELECT kind, sha256 FROM submissions ORDER BY id")
+            .fetch_all(&env.pool().await)
+            .await
+            .unwrap();
+    assert_eq!(sums[1].0, "pdf");
+    assert_eq!(sums[1].1.len(), 64);
+
+    // a second submit is refused, the frozen files stay what was sent
+    assert_eq!(env.run(&["submit", "1"]).status.code(), Some(2));
+
+    let output = env.run(&["diff", "1"]);
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Nothing changed"));
+
+    fs::write(&tex, TEMPLATE.replace("Hello", "Hello again")).unwrap();
+    let diff = env.redact(&env.ok(&["diff", "1", "--submitted"]));
+    assert_eq!(
+        diff,
+        "--- <root>/work/Acme_swe_<date>/submitted/resume_Acme_swe_<date>.tex\n\
+         +++ <root>/work/Acme_swe_<date>/resume_Acme_swe_<date>.tex\n\
+         @@ -1,4 +1,4 @@\n \\documentclass{article}\n \\begin{document}\n-Hello\n+Hello again\n \\end{document}\n"
+    );
+
+    // changing a frozen file is warned about by the commands on that resume
+    let copy = frozen.join("cover_letter.txt");
+    let mut permissions = fs::metadata(&copy).unwrap().permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(&copy, permissions).unwrap();
+    fs::write(&copy, "changed").unwrap();
+    let output = env.run(&["list", "all", "-c"]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("was modified"));
+    let output = env.run(&["update", "1", "-p", "swe"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cover_letter.txt was modified"));
+}
+
+#[test]
+fn lineage_follows_copies_and_exports_dot() {
+    let env = Env::new();
+    let acme = env.create(&["-c", "Acme"]);
+    env.create(&["-c", "Beta", "--from", "1"]);
+    env.create(&["-c", "Gamma", "--from", "2"]);
+    env.create(&["-c", "Delta", "-f", acme.to_str().unwrap()]);
+    env.create(&["-c", "Solo"]);
+
+    assert_snapshot("lineage_tree", &env.redact(&env.ok(&["lineage", "3"])));
+    assert_snapshot("lineage_dot", &env.redact(&env.ok(&["lineage", "--dot"])));
+
+    // copies made before copied_from was resolved are found by their path
+    env.ok(&["db", "rollback", "--to", "20250201120000"]);
+    let tree = env.ok(&["lineage", "4"]);
+    assert!(tree.starts_with("1\tAcme"), "{}", tree);
+    assert!(tree.contains("└── 4\tDelta"), "{}", tree);
+    assert_eq!(
+        env.ok(&["lineage", "5"]),
+        format!("5\tSolo\tswe\t{}\t*\n", today())
+    );
+}
+
+#[tokio::test]
+async fn create_refuses_likely_duplicates_and_dupes_merges_them() {
+    let env = Env::new();
+    env.create(&[
+        "-c", "Google", "-p", "swe", "--tag", "remote", "-n", "first",
+    ]);
+    env.create(&["-c", "IBM", "-p", "swe"]);
+
+    let output = env.run(&["create", "-c", "google inc.", "-p", "SWE"]);
+    assert_eq!(output.status.code(), Some(7));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 (Google swe"));
+    let copy = env.create(&[
+        "-c",
+        "google inc.",
+        "-p",
+        "SWE",
+        "--force",
+        "--tag",
+        "dream",
+        "-n",
+        "second",
+    ]);
+    // a typo in a short name is another company
+    env.create(&["-c", "IBN", "-p", "swe"]);
+
+    assert_eq!(
+        env.redact(&env.ok(&["dupes"])),
+        "group\tid\tcompany\tposition\tcreated_at\tstatus\n\
+         1\t1\tGoogle\tswe\t<date>\tapplying\n\
+         1\t3\tgoogle inc.\tSWE\t<date>\tapplying\n"
+    );
+    // bad ids are refused before anything moves
+    assert_eq!(
+        env.run(&["dupes", "merge", "1", "3", "1"]).status.code(),
+        Some(2)
+    );
+    assert_eq!(
+        env.run(&["dupes", "merge", "1", "3", "9"]).status.code(),
+        Some(8)
+    );
+    assert_eq!(env.ok(&["list", "all", "-c"]).lines().count(), 5);
+    assert_eq!(env.ok(&["dupes", "merge", "1", "3", "3"]), "merged\t3\t1\n");
+    assert_eq!(
+        env.ok(&["dupes"]),
+        "group\tid\tcompany\tposition\tcreated_at\tstatus\n"
+    );
+    assert!(copy.exists());
+
+    let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM resumes ORDER BY id")
+        .fetch_all(&env.pool().await)
+        .await
+        .unwrap();
+    assert_eq!(ids, [1, 2, 4]);
+    let meta = metadata(
+        &env.root()
+            .join(format!("work/Google_swe_{}/metadata.json", today())),
+    );
+    assert_eq!(meta["notes"], "first\nsecond");
+    assert_eq!(meta["tags"], serde_json::json!(["dream", "remote"]));
+}
+
+#[test]
+fn companies_group_spellings_and_merge() {
+    let env = Env::new();
+    env.create(&["-c", "Jane Street", "-p", "trader"]);
+    env.create(&["-c", "jane-street", "-p", "dev"]);
+    env.create(&["-c", "JS Capital", "-p", "quant"]);
+
+    assert_eq!(
+        env.ok(&["company", "list"]),
+        "id\tname\taliases\tapplications\twebsite\tindustry\tsize\thq\n\
+         1\tJane Street\tjane-street\t2\t\t\t\t\n\
+         2\tJS Capital\t\t1\t\t\t\t\n"
+    );
+    // a name can only belong to one company
+    let output = env.run(&["company", "alias", "1", "js capital"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    assert_eq!(
+        env.ok(&["company", "merge", "janestreet", "2"]),
+        "merged\tJS Capital\tJane Street\n"
+    );
+    env.ok(&["company", "set", "1", "--hq", "New York"]);
+    env.create(&["-c", "JANE STREET", "-p", "sre"]);
+    assert_eq!(
+        env.redact(&env.ok(&["company", "show", "Jane Street"])),
+        "id\t1\nname\tJane Street\naliases\tJANE STREET, jane-street, JS Capital\n\
+         website\t\nindustry\t\nsize\t\nhq\tNew York\n\n\
+         id\tgroup\tcompany\tposition\tcreated_at\tapplied_time\tstatus\n\
+         1\twork\tJane Street\ttrader\t<date>\t\tapplying\n\
+         2\twork\tjane-street\tdev\t<date>\t\tapplying\n\
+         3\twork\tJS Capital\tquant\t<date>\t\tapplying\n\
+         4\twork\tJANE STREET\tsre\t<date>\t\tapplying\n"
+    );
+    assert!(env
+        .ok(&["stats"])
+        .contains("company\tcount\nJane Street\t4\n"));
+}
+
+#[tokio::test]
+async fn locations_are_parsed_for_find_and_stats() {
+    let env = Env::new();
+    env.create(&["-c", "Shopify", "-l", "Remote - Canada"]);
+    env.create(&["-c", "Jane Street", "-l", "NYC area (hybrid)"]);
+    env.create(&["-c", "Apple", "-l", "Cupertino, CA"]);
+    env.create(&["-c", "Wealthsimple", "-l", "Toronto, ON"]);
+    env.create(&["-c", "Cohere", "-l", "remote, CA"]);
+
+    assert_eq!(env.ok(&["find", "country:CA", "mode:remote"]), "1\n5\n");
+    assert_eq!(env.ok(&["find", "country:canada"]), "1\n4\n5\n");
+    // a state code after a city is the state, not the country
+    assert_eq!(env.ok(&["find", "--region", "california"]), "3\n");
+    assert_eq!(env.ok(&["find", "city:nyc", "mode:hybrid"]), "2\n");
+    assert_eq!(env.run(&["find", "mode:sometimes"]).status.code(), Some(2));
+    assert_eq!(env.run(&["find", "planet:mars"]).status.code(), Some(2));
+
+    // what is not recognized keeps its text, and only that
+    let file = env.create(&["-c", "Acme", "-l", "Somewhere odd"]);
+    let meta = metadata(&file.parent().unwrap().join("metadata.json"));
+    assert_eq!(meta["location"], "Somewhere odd");
+    assert_eq!(env.ok(&["find", "location:somewhere odd"]), "6\n");
+    assert_eq!(env.ok(&["find", "country:CA"]), "1\n4\n5\n");
+
+    // the locations from before the columns are parsed once when they are added
+    env.ok(&["db", "rollback", "--to", "20250215120000"]);
+    assert_eq!(env.ok(&["find", "country:CA"]), "1\n4\n5\n");
+    let pool = env.pool().await;
+    sqlx::query(
+        "UPDATE metadata SET city = NULL, region = NULL, country = NULL WHERE resume_id = 4",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    assert_eq!(env.ok(&["find", "country:CA"]), "1\n5\n");
+
+    // edits are parsed again
+    env.ok(&["update", "4", "-l", "Hybrid, Waterloo ON"]);
+    assert_eq!(env.ok(&["find", "city:waterloo", "mode:hybrid"]), "4\n");
+    assert!(env
+        .ok(&["stats"])
+        .contains("region\tcount\nCA\t2\nCA, US\t1\nNY, US\t1\nON, CA\t1\n"));
+}
+
+#[test]
+fn offers_are_compared_and_near_deadlines_remind() {
+    let env = Env::new();
+    env.create(&["-c", "Acme", "-p", "swe"]);
+    env.create(&["-c", "Initech", "-p", "intern", "--length", "12"]);
+    let day = |days| (chrono::Utc::now().date_naive() + chrono::Days::new(days)).to_string();
+
+    let full_time = [
+        "offer",
+        "add",
+        "1",
+        "-b",
+        "120000",
+        "--bonus",
+        "10000",
+        "-e",
+        "40000",
+        "--full-time",
+        "-d",
+    ];
+    assert_eq!(env.ok(&[&full_time[..], &[&day(10)]].concat()), "1\n");
+    // the term comes from the length of the resume
+    let internship = [
+        "offer", "add", "2", "-b", "50", "--per", "hour", "-r", "2000", "-d",
+    ];
+    assert_eq!(env.ok(&[&internship[..], &[&day(2)]].concat()), "2\n");
+    // unless the offer has its own
+    let extended = [
+        "offer",
+        "add",
+        "2",
+        "-b",
+        "50",
+        "--per",
+        "hour",
+        "--term-weeks",
+        "16",
+    ];
+    assert_eq!(env.ok(&extended), "3\n");
+
+    // only the deadline within offer_reminder_days is brought up, by any command
+    let output = env.run(&["list", "all", "-c"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        format!(
+            "Reminder: offer 2 from Initech (intern) must be decided by {} (2 days left)\n",
+            day(2)
+        )
+    );
+
+    assert_eq!(
+        env.ok(&["offer", "list", "2"]),
+        format!(
+            "id\tresume_id\tcompany\tbase\tperiod\tcurrency\tbonus\tequity\tequity_years\trelocation\tterm_weeks\tstart_date\tdeadline\tlocation\tnotes\n\
+             2\t2\tInitech\t50\thour\tUSD\t0\t0\t4\t2000\t12\tnull\t{}\tnull\t\n\
+             3\t2\tInitech\t50\thour\tUSD\t0\t0\t4\t0\t16\tnull\tnull\tnull\t\n",
+            day(2)
+        )
+    );
+    // a full-time offer is never cut down to the length of its resume
+    let compare = env.ok(&["offer", "compare"]);
+    for row in [
+        "base/year\t120000\t104000\t104000\n",
+        "equity/year\t10000\t0\t0\n",
+        "annualized\t140000\t104000\t104000\n",
+        "term (weeks)\tnull\t12\t16\n",
+        "term total\tnull\t26000\t32000\n",
+    ] {
+        assert!(compare.contains(row), "{:?} not in\n{}", row, compare);
+    }
+    env.ok(&["update", "2", "--length", "10"]);
+    assert!(env
+        .ok(&["offer", "compare", "2"])
+        .contains("term total\t22000\n"));
+
+    env.ok(&["offer", "rm", "2"]);
+    assert_eq!(env.ok(&["offer", "list"]).lines().count(), 3);
+    assert_eq!(
+        env.run(&["offer", "compare", "1", "2"]).status.code(),
+        Some(2)
+    );
+    assert_eq!(env.run(&["offer", "rm", "2"]).status.code(), Some(2));
+}
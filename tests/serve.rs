@@ -0,0 +1,183 @@
+// Tests of the http api, the router is driven in process without binding a port
+
+use std::fs;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use http_body_util::BodyExt;
+use resuman::{commands::serve::router, config::Config, Resuman};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+const TOKEN: &str = "secret";
+
+async fn app() -> (TempDir, Router) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("resumes");
+    fs::create_dir_all(root.join("templates")).unwrap();
+    fs::write(
+        root.join("templates/default.tex"),
+        "\\documentclass{article}\n\\begin{document}\nHello\n\\end{document}\n",
+    )
+    .unwrap();
+    let cfg = Config {
+        root_dir: root,
+        default_group: Some("work".to_string()),
+        ..Default::default()
+    };
+    let opened = Resuman::open(cfg).await.unwrap();
+    let resuman = Resuman::from_pool(opened.config().clone(), opened.pool().clone());
+    (dir, router(resuman, Some(TOKEN.to_string())))
+}
+
+/// Sends a request with the token and returns the status and the JSON body, if any
+async fn send(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN));
+    let request = match body {
+        Some(body) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    };
+    let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, value)
+}
+
+#[tokio::test]
+async fn api_requires_the_bearer_token() {
+    let (_dir, app) = app().await;
+    for authorization in [
+        None,
+        Some("Bearer wrong"),
+        Some("Bearer secre"),
+        Some(TOKEN),
+    ] {
+        let request = Request::builder().uri("/api/resumes");
+        let request = match authorization {
+            Some(value) => request.header(header::AUTHORIZATION, value),
+            None => request,
+        };
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::UNAUTHORIZED,
+            "{:?}",
+            authorization
+        );
+    }
+
+    let (status, resumes) = send(&app, Method::GET, "/api/resumes", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(resumes, json!([]));
+    // the dashboard itself holds no data
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn api_creates_lists_updates_finds_and_deletes() {
+    let (_dir, app) = app().await;
+    let (status, created) = send(
+        &app,
+        Method::POST,
+        "/api/resumes",
+        Some(json!({"company": "Acme", "position": "backend", "tags": ["remote"]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", created);
+    assert_eq!(created["id"], 1);
+    assert_eq!(created["group"], "work");
+    assert_eq!(created["tags"], json!(["remote"]));
+    // timestamps are written the same way as in the metadata file
+    let file = std::path::PathBuf::from(created["file_path"].as_str().unwrap());
+    let meta: Value =
+        serde_json::from_str(&fs::read_to_string(file.with_file_name("metadata.json")).unwrap())
+            .unwrap();
+    assert_eq!(created["created_at"], meta["created_at"]);
+    send(
+        &app,
+        Method::POST,
+        "/api/resumes",
+        Some(json!({"company": "Globex"})),
+    )
+    .await;
+
+    let (status, resumes) = send(&app, Method::GET, "/api/resumes", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(resumes.as_array().unwrap().len(), 2);
+
+    let (status, updated) = send(
+        &app,
+        Method::PATCH,
+        "/api/resumes/1",
+        Some(json!({"status": "interview", "tags": ["-remote"]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", updated);
+    assert_eq!(updated["status"], "interview");
+    assert_eq!(updated["tags"], json!([]));
+    let (_, got) = send(&app, Method::GET, "/api/resumes/1", None).await;
+    assert_eq!(got["status"], "interview");
+
+    let (status, ids) = send(&app, Method::GET, "/api/find?company=acme", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ids, json!([1]));
+    let (status, _) = send(&app, Method::GET, "/api/find?planet=mars", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, stats) = send(&app, Method::GET, "/api/stats", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stats["total"], 2);
+    assert!(stats["by_status"]
+        .as_array()
+        .unwrap()
+        .contains(&json!({"key": "interview", "count": 1})));
+
+    let (status, _) = send(&app, Method::DELETE, "/api/resumes/1?files=true", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, error) = send(&app, Method::GET, "/api/resumes/1", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(error["error"].is_string());
+}
+
+#[tokio::test]
+async fn api_errors_have_client_statuses() {
+    let (_dir, app) = app().await;
+    let acme = json!({"company": "Acme", "position": "swe"});
+    let (status, created) = send(&app, Method::POST, "/api/resumes", Some(acme)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // the same check as `create`, and force gets past it like --force
+    let mut dupe = json!({"company": "acme inc.", "position": "SWE"});
+    let (status, error) = send(&app, Method::POST, "/api/resumes", Some(dupe.clone())).await;
+    assert_eq!(status, StatusCode::CONFLICT, "{}", error);
+    assert!(error["error"].as_str().unwrap().starts_with("Looks like"));
+    dupe["force"] = true.into();
+    let (status, _) = send(&app, Method::POST, "/api/resumes", Some(dupe)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // a metadata file broken by hand
+    let file = std::path::PathBuf::from(created["file_path"].as_str().unwrap());
+    fs::write(file.with_file_name("metadata.json"), "{ not json").unwrap();
+    let patch = json!({"status": "oa"});
+    let (status, error) = send(&app, Method::PATCH, "/api/resumes/1", Some(patch)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", error);
+}